@@ -0,0 +1,313 @@
+//! Known answers for real puzzle inputs, to catch regressions when refactoring a solver.
+//!
+//! The inputs themselves stay out of the repo, so the answers are keyed by a hash of the input.
+//! The store is a text file with one answer per line, as tab-separated fields:
+//!
+//! ```text
+//! # day  part  variant  input hash        answer
+//! 2      2     bruter_force  0123456789abcdef  4
+//! 5      1     -        fedcba9876543210  143
+//! ```
+//!
+//! where `-` is the unnamed variant, and answers escape backslashes and newlines as `\\` and `\n`.
+
+use std::{
+    fmt::{self, Display, Formatter},
+    fs, io,
+    panic::{self, AssertUnwindSafe},
+    path::Path,
+};
+
+use crate::{
+    parsing::{parse_token, ParseError},
+    registry::Solver,
+};
+
+/// Where the known answers live, relative to the root of the repo
+pub const DEFAULT_PATH: &str = "answers.txt";
+
+const HEADER: &str = "# day\tpart\tvariant\tinput hash\tanswer\n";
+
+/// The 64-bit FNV-1a hash of `input`, ignoring trailing newlines (like the solvers do)
+pub fn input_hash(input: &str) -> u64 {
+    input
+        .trim_end_matches('\n')
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KnownAnswer {
+    pub day: u8,
+    pub part: u8,
+    /// The name of the variant, `None` for the unnamed one
+    pub variant: Option<String>,
+    pub input_hash: u64,
+    pub answer: String,
+}
+
+impl KnownAnswer {
+    fn is_for(&self, solver: &Solver, input_hash: u64) -> bool {
+        self.day == solver.day
+            && self.part == solver.part
+            && self.variant.as_deref() == solver.variant
+            && self.input_hash == input_hash
+    }
+}
+
+/// How a solver did on an input, compared to the known answer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Correct,
+    Wrong {
+        expected: String,
+        actual: String,
+    },
+    /// There is no known answer for this solver and input yet
+    Unknown {
+        actual: String,
+    },
+    /// The solver returned an error or panicked
+    Failed {
+        error: String,
+    },
+}
+
+impl Verdict {
+    /// Whether this is a regression, as opposed to a correct or a new answer
+    pub fn is_regression(&self) -> bool {
+        matches!(self, Self::Wrong { .. } | Self::Failed { .. })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AnswerStore {
+    answers: Vec<KnownAnswer>,
+}
+
+impl AnswerStore {
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        let mut answers = Vec::new();
+        for line in text.lines() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split('\t').collect();
+            let &[day, part, variant, hash, answer] = fields.as_slice() else {
+                return Err(ParseError::at(text, line, "five tab-separated fields"));
+            };
+            answers.push(KnownAnswer {
+                day: parse_token(text, day, "a day")?,
+                part: parse_token(text, part, "a part")?,
+                variant: (variant != "-").then(|| variant.to_string()),
+                input_hash: u64::from_str_radix(hash, 16)
+                    .map_err(|_| ParseError::at(text, hash, "a hexadecimal input hash"))?,
+                answer: unescape(answer),
+            });
+        }
+        Ok(Self { answers })
+    }
+
+    /// Loads the store at `path`, or an empty one if there is none yet
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        match fs::read_to_string(path) {
+            Ok(text) => Ok(Self::parse(&text)?),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    pub fn answers(&self) -> &[KnownAnswer] {
+        &self.answers
+    }
+
+    /// The known answer of `solver` for the input with the given hash
+    pub fn get(&self, solver: &Solver, input_hash: u64) -> Option<&str> {
+        self.answers
+            .iter()
+            .find(|known| known.is_for(solver, input_hash))
+            .map(|known| known.answer.as_str())
+    }
+
+    /// Remembers `answer` as the one of `solver` for `input`, replacing any previous one
+    pub fn record(&mut self, solver: &Solver, input: &str, answer: String) {
+        let input_hash = input_hash(input);
+        match self
+            .answers
+            .iter_mut()
+            .find(|known| known.is_for(solver, input_hash))
+        {
+            Some(known) => known.answer = answer,
+            None => self.answers.push(KnownAnswer {
+                day: solver.day,
+                part: solver.part,
+                variant: solver.variant.map(String::from),
+                input_hash,
+                answer,
+            }),
+        }
+        self.answers
+            .sort_by_key(|known| (known.day, known.part, known.variant.clone()));
+    }
+
+    /// Solves `input` with `solver`, and compares the answer to the known one
+    pub fn check(&self, solver: &Solver, input: &str) -> Verdict {
+        let actual = match panic::catch_unwind(AssertUnwindSafe(|| solver.solve(input))) {
+            Ok(Ok(solution)) => solution.answer,
+            Ok(Err(error)) => {
+                return Verdict::Failed {
+                    error: error.to_string(),
+                }
+            }
+            Err(_) => {
+                return Verdict::Failed {
+                    error: "panicked".to_string(),
+                }
+            }
+        };
+        match self.get(solver, input_hash(input)) {
+            Some(expected) if expected == actual => Verdict::Correct,
+            Some(expected) => Verdict::Wrong {
+                expected: expected.to_string(),
+                actual,
+            },
+            None => Verdict::Unknown { actual },
+        }
+    }
+}
+
+impl Display for AnswerStore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{HEADER}")?;
+        for known in &self.answers {
+            writeln!(
+                f,
+                "{}\t{}\t{}\t{:016x}\t{}",
+                known.day,
+                known.part,
+                known.variant.as_deref().unwrap_or("-"),
+                known.input_hash,
+                escape(&known.answer)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn escape(answer: &str) -> String {
+    answer.replace('\\', r"\\").replace('\n', r"\n")
+}
+
+fn unescape(field: &str) -> String {
+    let mut answer = String::with_capacity(field.len());
+    let mut chars = field.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n')) => {
+                answer.push('\n');
+                chars.next();
+            }
+            ('\\', Some('\\')) => {
+                answer.push('\\');
+                chars.next();
+            }
+            (c, _) => answer.push(c),
+        }
+    }
+    answer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{params, registry};
+
+    fn solver(day: u8, part: u8, variant: Option<&str>) -> &'static Solver {
+        registry::select(Some(day), Some(part), Some(variant.unwrap_or("")))
+            .next()
+            .unwrap()
+    }
+
+    #[test]
+    fn fnv_hash() {
+        assert_eq!(input_hash(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(input_hash("a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(input_hash("a\n\n"), input_hash("a"));
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut store = AnswerStore::default();
+        store.record(solver(14, 2, None), "p=0,4 v=3,-3", "a\\b\nc".to_string());
+        store.record(solver(2, 2, Some("brute_force")), "1 2", "1".to_string());
+        store.record(solver(2, 2, Some("brute_force")), "1 2", "0".to_string());
+        assert_eq!(store.answers().len(), 2);
+        assert_eq!(store.answers()[0].answer, "0");
+
+        let text = store.to_string();
+        assert!(text.starts_with(HEADER));
+        assert!(text.contains("\tbrute_force\t"));
+        assert!(text.contains("\t-\t"));
+        assert!(text.contains(r"a\\b\nc"));
+        assert_eq!(AnswerStore::parse(&text).unwrap(), store);
+    }
+
+    #[test]
+    fn parse_malformed_store() {
+        let error = AnswerStore::parse("1\t1\t-\t00ff\n1\t2\n").unwrap_err();
+        assert_eq!((error.line, error.column), (1, 1));
+        let error = AnswerStore::parse("1\t1\t-\tnothex\t2\n").unwrap_err();
+        assert_eq!((error.line, error.column), (1, 7));
+    }
+
+    #[test]
+    fn verdicts() {
+        let input = "3   4\n4   3\n2   5\n1   3\n3   9\n3   3\n";
+        let mut store = AnswerStore::default();
+        let part1 = solver(1, 1, None);
+        assert_eq!(
+            store.check(part1, input),
+            Verdict::Unknown {
+                actual: "11".to_string()
+            }
+        );
+        store.record(part1, input, "11".to_string());
+        assert_eq!(store.check(part1, input), Verdict::Correct);
+        store.record(part1, input, "12".to_string());
+        assert!(store.check(part1, input).is_regression());
+        assert!(matches!(
+            store.check(part1, "3 x\n"),
+            Verdict::Failed { .. }
+        ));
+    }
+
+    /// Checks the known answers for the real inputs in `input/2024`, where there are any
+    #[test]
+    fn real_inputs_match_known_answers() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let store = AnswerStore::load(&root.join(DEFAULT_PATH)).unwrap();
+        let mut checked = 0;
+        for solver in registry::SOLVERS {
+            let path = root.join(format!("input/2024/day{}.txt", solver.day));
+            let Ok(input) = fs::read_to_string(&path) else {
+                continue;
+            };
+            if store.get(solver, input_hash(&input)).is_none() {
+                continue;
+            }
+            let params = params::load_for_input(&path).unwrap();
+            let verdict = params::with(params, || store.check(solver, &input));
+            assert!(!verdict.is_regression(), "{solver}: {verdict:?}");
+            checked += 1;
+        }
+        if checked == 0 {
+            eprintln!("no real inputs with known answers, skipping");
+        }
+    }
+}
@@ -0,0 +1,235 @@
+//! Runs every variant of a day and part on the same input, and reports when they disagree.
+//!
+//! For inputs made of independent records, one per line (the reports of day 2,
+//! the updates of day 5, the equations of day 7, ...), a disagreement is then narrowed down
+//! to a minimal set of records the variants still disagree on, by delta debugging:
+//! keep dropping chunks of records while the disagreement persists,
+//! halving the chunk size whenever no chunk can be dropped.
+
+use std::{
+    cell::Cell,
+    panic::{self, AssertUnwindSafe},
+};
+
+use itertools::Itertools;
+
+use crate::registry::{self, Solver};
+
+/// The answer of one variant, or why it has none
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    pub solver: &'static Solver,
+    pub answer: Result<String, String>,
+}
+
+/// The outcomes of all variants of a day and part on some input
+#[derive(Debug, Clone)]
+pub struct CrossCheck {
+    pub day: u8,
+    pub part: u8,
+    pub outcomes: Vec<Outcome>,
+    /// For a disagreement on an input of records, the smallest input found that still disagrees
+    pub minimal: Option<Minimal>,
+}
+
+/// A reduced input the variants disagree on
+#[derive(Debug, Clone)]
+pub struct Minimal {
+    pub input: String,
+    /// How many of the records of the full input are left in `input`
+    pub n_records: usize,
+    pub n_records_total: usize,
+    pub outcomes: Vec<Outcome>,
+}
+
+impl CrossCheck {
+    /// Whether every variant came up with the same answer (or failed the same way)
+    pub fn agrees(&self) -> bool {
+        agree(&self.outcomes)
+    }
+}
+
+/// Cross-checks the variants of `day` and `part` on `input`,
+/// or returns `None` if there are fewer than two of them
+pub fn cross_check(day: u8, part: u8, input: &str) -> Option<CrossCheck> {
+    let solvers = registry::select(Some(day), Some(part), None).collect_vec();
+    if solvers.len() < 2 {
+        return None;
+    }
+    let outcomes = run_all(&solvers, input);
+    let minimal = if agree(&outcomes) {
+        None
+    } else {
+        records(day, input).map(|(preamble, records)| {
+            let n_records_total = records.len();
+            let records = minimise(records, |records| {
+                !agree(&run_all(&solvers, &assemble(preamble, records)))
+            });
+            let input = assemble(preamble, &records);
+            Minimal {
+                outcomes: run_all(&solvers, &input),
+                input,
+                n_records: records.len(),
+                n_records_total,
+            }
+        })
+    };
+    Some(CrossCheck {
+        day,
+        part,
+        outcomes,
+        minimal,
+    })
+}
+
+/// Splits the input of `day` into a preamble every input needs, and the records after it,
+/// or returns `None` if its input is not made of independent records
+pub fn records(day: u8, input: &str) -> Option<(&str, Vec<&str>)> {
+    match day {
+        1 | 2 | 7 => Some(("", input.lines().filter(|l| !l.is_empty()).collect())),
+        5 => {
+            let (rules, updates) = input.split_once("\n\n")?;
+            let preamble = &input[..rules.len() + 2];
+            Some((
+                preamble,
+                updates.lines().filter(|l| !l.is_empty()).collect(),
+            ))
+        }
+        _ => None,
+    }
+}
+
+fn assemble(preamble: &str, records: &[&str]) -> String {
+    let mut input = preamble.to_string();
+    for record in records {
+        input.push_str(record);
+        input.push('\n');
+    }
+    input
+}
+
+fn agree(outcomes: &[Outcome]) -> bool {
+    outcomes.iter().map(|outcome| &outcome.answer).all_equal()
+}
+
+thread_local! {
+    static IN_SOLVER: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Whether this thread is running a solver for a cross-check, so that a panic now only
+/// makes its outcome a failure (for a panic hook to keep quiet about)
+pub fn is_running_solver() -> bool {
+    IN_SOLVER.get()
+}
+
+/// Runs all `solvers` on `input`, turning errors and panics into failed outcomes
+fn run_all(solvers: &[&'static Solver], input: &str) -> Vec<Outcome> {
+    solvers
+        .iter()
+        .map(|&solver| {
+            IN_SOLVER.set(true);
+            let solved = panic::catch_unwind(AssertUnwindSafe(|| solver.solve(input)));
+            IN_SOLVER.set(false);
+            let answer = solved
+                .map_err(|_| "panicked".to_string())
+                .and_then(|solved| solved.map_err(|error| error.to_string()))
+                .map(|solution| solution.answer);
+            Outcome { solver, answer }
+        })
+        .collect()
+}
+
+/// Shrinks `records` to a subset for which `fails` still holds, and from which no single
+/// record can be dropped without `fails` turning false (assuming it holds for all `records`)
+fn minimise<'a>(
+    mut records: Vec<&'a str>,
+    mut fails: impl FnMut(&[&'a str]) -> bool,
+) -> Vec<&'a str> {
+    let mut n_chunks = 2;
+    while records.len() >= 2 {
+        let chunk_len = records.len().div_ceil(n_chunks);
+        let mut dropped_any = false;
+        let mut start = 0;
+        while start < records.len() {
+            let end = (start + chunk_len).min(records.len());
+            let rest = [&records[..start], &records[end..]].concat();
+            if !rest.is_empty() && fails(&rest) {
+                records = rest;
+                dropped_any = true;
+            } else {
+                start = end;
+            }
+        }
+        if dropped_any {
+            n_chunks = n_chunks.saturating_sub(1).max(2);
+        } else if chunk_len == 1 {
+            break;
+        } else {
+            n_chunks = (n_chunks * 2).min(records.len());
+        }
+    }
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn minimise_to_the_culprits() {
+        let records = (0..100).map(|i| i.to_string()).collect_vec();
+        let records = records.iter().map(String::as_str).collect_vec();
+        let mut calls = 0;
+        let minimal = minimise(records, |records| {
+            calls += 1;
+            records.contains(&"17") && records.contains(&"71")
+        });
+        assert_eq!(minimal, ["17", "71"]);
+        assert!(calls < 100, "took {calls} calls");
+    }
+
+    #[test]
+    fn records_of_day5() {
+        let input = "47|53\n97|13\n\n75,47\n97,61\n";
+        let (preamble, records) = records(5, input).unwrap();
+        assert_eq!(preamble, "47|53\n97|13\n\n");
+        assert_eq!(records, ["75,47", "97,61"]);
+        assert_eq!(assemble(preamble, &records), input);
+        assert!(super::records(6, input).is_none());
+    }
+
+    #[test]
+    fn day2_clever_but_wrong_is_caught() {
+        let input = indoc! {"
+            7 6 4 2 1
+            1 2 7 8 9
+            9 7 6 2 1
+            1 3 2 4 5
+            8 6 4 4 1
+            1 3 6 7 9
+            1 5 4 3 2
+            1 2 3 4 5
+        "};
+        let check = cross_check(2, 2, input).unwrap();
+        assert!(!check.agrees());
+        let minimal = check.minimal.unwrap();
+        assert_eq!(minimal.input, "1 5 4 3 2\n");
+        assert_eq!((minimal.n_records, minimal.n_records_total), (1, 8));
+        let clever = &minimal.outcomes[0];
+        assert_eq!(clever.solver.variant, Some("clever_but_wrong"));
+        assert_eq!(clever.answer, Ok("0".to_string()));
+        assert!(minimal.outcomes[1..]
+            .iter()
+            .all(|outcome| outcome.answer == Ok("1".to_string())));
+    }
+
+    #[test]
+    fn day7_variants_agree() {
+        let input = "190: 10 19\n3267: 81 40 27\n83: 17 5\n";
+        let check = cross_check(7, 2, input).unwrap();
+        assert!(check.agrees());
+        assert!(check.minimal.is_none());
+        assert!(cross_check(1, 1, input).is_none());
+    }
+}
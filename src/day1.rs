@@ -1,43 +1,76 @@
-use std::{collections::HashMap, iter::zip};
+//! Day 1: Historian Hysteria
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    env,
+    error::Error,
+    fmt::{self, Display, Formatter},
+    fs::{self, File},
+    io::{self, BufRead, BufReader, BufWriter, Read, Write},
+    iter::zip,
+    path::PathBuf,
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
 use aoc_runner_derive::{aoc, aoc_generator};
+use itertools::Itertools;
+use rustc_hash::FxHashMap;
+
+use crate::{
+    grid::{Grid, Point},
+    parsing::{parse_token, ParseError},
+    table::Table,
+};
 
+/// The left and the right list of location IDs
+pub type Input = (Vec<u32>, Vec<u32>);
+
+/// Parses the two lists from lines of two numbers each
 #[aoc_generator(day1)]
-fn parse_pt1(input: &str) -> (Vec<u32>, Vec<u32>) {
-    let line_it = input.lines().map(|line: &str| {
-        line.split_whitespace()
-            .map(|token| token.parse::<u32>().expect("expected only numbers and ws"))
-    });
+pub fn parse(input: &str) -> Result<Input, ParseError> {
     let estimated_line_no = input.len()
         / input
             .lines()
             .next()
-            .expect("expected at least one line of input")
+            .filter(|line| !line.is_empty())
+            .ok_or_else(|| ParseError::at_end(input, "at least one line of input"))?
             .len();
     let mut left_parsed = Vec::with_capacity(estimated_line_no);
     let mut right_parsed = Vec::with_capacity(estimated_line_no);
-    for parsed_it in line_it {
-        let mut parsed_it = parsed_it;
-        left_parsed.push(
-            parsed_it
-                .next()
-                .expect("expected two numbers per line, got none"),
-        );
-        right_parsed.push(
-            parsed_it
-                .next()
-                .expect("expected two numbers per line, got one"),
-        );
-        debug_assert!(
-            parsed_it.next().is_none(),
-            "expected two numbers per line, got more"
-        );
+    for line in input.lines() {
+        let (left, right) = parse_line(input, line)?;
+        left_parsed.push(left);
+        right_parsed.push(right);
     }
-    (left_parsed, right_parsed)
+    Ok((left_parsed, right_parsed))
 }
 
+/// Parses the two numbers on `line`, a sub-slice of `source`
+fn parse_line(source: &str, line: &str) -> Result<(u32, u32), ParseError> {
+    let mut token_it = line.split_whitespace();
+    let left = token_it
+        .next()
+        .ok_or_else(|| ParseError::after(source, line, "two numbers per line, got none"))?;
+    let left = parse_token(source, left, "a number")?;
+    let right = token_it
+        .next()
+        .ok_or_else(|| ParseError::after(source, line, "two numbers per line, got one"))?;
+    let right = parse_token(source, right, "a number")?;
+    if let Some(extra) = token_it.next() {
+        return Err(ParseError::at(
+            source,
+            extra,
+            "two numbers per line, got more",
+        ));
+    }
+    Ok((left, right))
+}
+
+/// The total distance between the lists, pairing up their numbers in sorted order
 #[aoc(day1, part1)]
-fn solve_pt1(left_and_right: &(Vec<u32>, Vec<u32>)) -> u32 {
+pub fn part1(left_and_right: &Input) -> u32 {
     let (mut left, mut right) = left_and_right.clone();
     left.sort_unstable();
     right.sort_unstable();
@@ -54,8 +87,9 @@ fn count_unique(numbers: &[u32]) -> HashMap<u32, u32> {
     counts
 }
 
+/// The similarity score: each number on the left times how often it appears on the right
 #[aoc(day1, part2)]
-fn solve_pt2(left_and_right: &(Vec<u32>, Vec<u32>)) -> u32 {
+pub fn part2(left_and_right: &Input) -> u32 {
     let (left, right) = left_and_right;
     let left_counts = count_unique(left);
     let right_counts = count_unique(right);
@@ -66,6 +100,660 @@ fn solve_pt2(left_and_right: &(Vec<u32>, Vec<u32>)) -> u32 {
     }
     sim_score
 }
+
+/// Two numbers paired up by part 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pairing {
+    pub left: u32,
+    pub right: u32,
+    pub distance: u32,
+}
+
+/// How part 1 adds up: the numbers of both lists paired up in sorted order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Part1Explanation {
+    pub pairings: Vec<Pairing>,
+    pub total_distance: u32,
+}
+
+/// What a number on the left adds to the similarity score of part 2
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Contribution {
+    pub number: u32,
+    pub left_count: u32,
+    pub right_count: u32,
+    /// `number * left_count * right_count`
+    pub contribution: u32,
+}
+
+/// How part 2 adds up: each distinct number on the left, in ascending order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Part2Explanation {
+    pub contributions: Vec<Contribution>,
+    pub similarity_score: u32,
+}
+
+pub fn explain_part1((left, right): &Input) -> Part1Explanation {
+    let pairings = zip(
+        left.iter().sorted_unstable(),
+        right.iter().sorted_unstable(),
+    )
+    .map(|(&left, &right)| Pairing {
+        left,
+        right,
+        distance: left.abs_diff(right),
+    })
+    .collect_vec();
+    Part1Explanation {
+        total_distance: pairings.iter().map(|pairing| pairing.distance).sum(),
+        pairings,
+    }
+}
+
+pub fn explain_part2((left, right): &Input) -> Part2Explanation {
+    let right_counts = count_unique(right);
+    let contributions = count_unique(left)
+        .into_iter()
+        .sorted_unstable()
+        .map(|(number, left_count)| {
+            let right_count = *right_counts.get(&number).unwrap_or(&0);
+            Contribution {
+                number,
+                left_count,
+                right_count,
+                contribution: number * left_count * right_count,
+            }
+        })
+        .collect_vec();
+    Part2Explanation {
+        similarity_score: contributions.iter().map(|c| c.contribution).sum(),
+        contributions,
+    }
+}
+
+impl Part1Explanation {
+    pub fn to_table(&self) -> Table {
+        let mut table = Table::new(["left", "right", "distance"]);
+        for pairing in &self.pairings {
+            table.push_row([pairing.left, pairing.right, pairing.distance]);
+        }
+        table
+    }
+}
+
+impl Part2Explanation {
+    pub fn to_table(&self) -> Table {
+        let mut table = Table::new(["number", "left count", "right count", "contribution"]);
+        for c in &self.contributions {
+            table.push_row([c.number, c.left_count, c.right_count, c.contribution]);
+        }
+        table
+    }
+}
+
+/// The table, and the total below it
+impl Display for Part1Explanation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_table())?;
+        writeln!(f, "total distance: {}", self.total_distance)
+    }
+}
+
+/// The table, and the total below it
+impl Display for Part2Explanation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_table())?;
+        writeln!(f, "similarity score: {}", self.similarity_score)
+    }
+}
+
+/// Any number of location lists, one per column of the input
+pub type Columns = Vec<Vec<u32>>;
+
+/// Parses lines with the same number of numbers each (at least one), as one list per column
+pub fn parse_columns(input: &str) -> Result<Columns, ParseError> {
+    let mut columns: Columns = Vec::new();
+    for (line_no, line) in input.lines().enumerate() {
+        let tokens = line.split_whitespace().collect_vec();
+        if line_no == 0 {
+            if tokens.is_empty() {
+                return Err(ParseError::at(input, line, "at least one number"));
+            }
+            columns = vec![Vec::new(); tokens.len()];
+        } else if tokens.len() != columns.len() {
+            return Err(ParseError::at(
+                input,
+                line,
+                format!("{} numbers, like the first line", columns.len()),
+            ));
+        }
+        for (column, token) in columns.iter_mut().zip(tokens) {
+            column.push(parse_token(input, token, "a number")?);
+        }
+    }
+    if columns.is_empty() {
+        return Err(ParseError::at_end(input, "at least one line of input"));
+    }
+    Ok(columns)
+}
+
+/// A way to compare two location lists, as a distance or as a similarity
+pub trait Metric {
+    /// A short name, for headers
+    fn name(&self) -> &str;
+
+    fn compare(&self, a: &[u32], b: &[u32]) -> f64;
+}
+
+/// The total distance when pairing up the numbers in sorted order, as in part 1
+pub struct SortedL1;
+
+/// The euclidean distance when pairing up the numbers in sorted order
+pub struct SortedL2;
+
+/// The earth mover's distance between the lists as distributions of location IDs:
+/// how far the IDs of one have to move on average to turn it into the other
+pub struct EarthMover;
+
+/// The similarity score of part 2
+pub struct Similarity;
+
+/// The size of the intersection over the size of the union of the lists as multisets
+pub struct Jaccard;
+
+/// The built-in metrics
+pub const METRICS: [&dyn Metric; 5] = [&SortedL1, &SortedL2, &EarthMover, &Similarity, &Jaccard];
+
+fn sorted_pairs(a: &[u32], b: &[u32]) -> impl Iterator<Item = f64> {
+    zip(
+        a.iter().copied().sorted_unstable(),
+        b.iter().copied().sorted_unstable(),
+    )
+    .map(|(a, b)| a.abs_diff(b) as f64)
+}
+
+impl Metric for SortedL1 {
+    fn name(&self) -> &str {
+        "sorted-l1"
+    }
+
+    fn compare(&self, a: &[u32], b: &[u32]) -> f64 {
+        sorted_pairs(a, b).sum()
+    }
+}
+
+impl Metric for SortedL2 {
+    fn name(&self) -> &str {
+        "sorted-l2"
+    }
+
+    fn compare(&self, a: &[u32], b: &[u32]) -> f64 {
+        sorted_pairs(a, b).map(|d| d * d).sum::<f64>().sqrt()
+    }
+}
+
+impl Metric for EarthMover {
+    fn name(&self) -> &str {
+        "earth-mover"
+    }
+
+    /// The area between the cumulative distributions, so the lists can differ in length
+    ///
+    /// NaN if either list is empty, as that is no distribution at all.
+    fn compare(&self, a: &[u32], b: &[u32]) -> f64 {
+        if a.is_empty() || b.is_empty() {
+            return f64::NAN;
+        }
+        let (a, b) = (
+            a.iter().copied().sorted_unstable().collect_vec(),
+            b.iter().copied().sorted_unstable().collect_vec(),
+        );
+        let (mut i, mut j) = (0, 0);
+        let mut area = 0.0;
+        let mut previous = None;
+        while i < a.len() || j < b.len() {
+            let value = *a.get(i).into_iter().chain(b.get(j)).min().unwrap();
+            if let Some(previous) = previous {
+                let gap = i as f64 / a.len() as f64 - j as f64 / b.len() as f64;
+                area += gap.abs() * (value - previous) as f64;
+            }
+            while a.get(i) == Some(&value) {
+                i += 1;
+            }
+            while b.get(j) == Some(&value) {
+                j += 1;
+            }
+            previous = Some(value);
+        }
+        area
+    }
+}
+
+impl Metric for Similarity {
+    fn name(&self) -> &str {
+        "similarity"
+    }
+
+    fn compare(&self, a: &[u32], b: &[u32]) -> f64 {
+        let b_counts = count_unique(b);
+        a.iter()
+            .map(|number| *number as f64 * *b_counts.get(number).unwrap_or(&0) as f64)
+            .sum()
+    }
+}
+
+impl Metric for Jaccard {
+    fn name(&self) -> &str {
+        "jaccard"
+    }
+
+    /// 1 for two empty lists
+    fn compare(&self, a: &[u32], b: &[u32]) -> f64 {
+        let (a_counts, b_counts) = (count_unique(a), count_unique(b));
+        let intersection: u32 = a_counts
+            .iter()
+            .map(|(number, &count)| count.min(*b_counts.get(number).unwrap_or(&0)))
+            .sum();
+        let union = a.len() as u32 + b.len() as u32 - intersection;
+        if union == 0 {
+            1.0
+        } else {
+            intersection as f64 / union as f64
+        }
+    }
+}
+
+/// Every column compared with every other by one metric
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    pub metric: String,
+    /// The result for columns `a` and `b` at `(x, y)` = `(b, a)`
+    pub matrix: Grid<f64>,
+}
+
+/// Compares every pair of columns (in both orders, and each with itself) by `metric`
+pub fn compare(columns: &[Vec<u32>], metric: &dyn Metric) -> Comparison {
+    let n = columns.len();
+    let values = (0..n)
+        .cartesian_product(0..n)
+        .map(|(a, b)| metric.compare(&columns[a], &columns[b]))
+        .collect();
+    Comparison {
+        metric: metric.name().to_string(),
+        matrix: Grid::from_vec(n, n, values),
+    }
+}
+
+impl Comparison {
+    /// The result for columns `a` and `b` (from 0)
+    pub fn get(&self, a: usize, b: usize) -> f64 {
+        self.matrix[Point::new(b, a)]
+    }
+
+    /// One line per column, with a header line, the columns numbered from 1
+    pub fn to_csv(&self) -> String {
+        let n = self.matrix.width();
+        let mut csv = format!("{},{}\n", self.metric, (1..=n).join(","));
+        for (a, row) in self.matrix.rows().enumerate() {
+            csv.push_str(&format!("{},{}\n", a + 1, row.iter().join(",")));
+        }
+        csv
+    }
+
+    /// A row per column, with the results rounded, the columns numbered from 1
+    pub fn to_table(&self) -> Table {
+        let format_value = |value: f64| {
+            if value.fract() == 0.0 {
+                format!("{value:.0}")
+            } else {
+                format!("{value:.3}")
+            }
+        };
+        let n = self.matrix.width();
+        let mut table = Table::new(
+            [self.metric.clone()]
+                .into_iter()
+                .chain((1..=n).map(|b| b.to_string())),
+        );
+        for (a, row) in self.matrix.rows().enumerate() {
+            table.push_row(
+                [(a + 1).to_string()]
+                    .into_iter()
+                    .chain(row.iter().map(|&value| format_value(value))),
+            );
+        }
+        table
+    }
+}
+
+impl Display for Comparison {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.to_table().fmt(f)
+    }
+}
+
+/// How much memory the streaming solvers may use, and where they spill the rest
+#[derive(Debug, Clone)]
+pub struct StreamOptions {
+    /// How many numbers of each list are sorted in memory, before they go into a chunk file
+    pub chunk_len: usize,
+    /// How many distinct numbers part 2 counts exactly, before it falls back to sorting
+    pub max_counters: usize,
+    /// The number of counters in each row of the count-min sketch of part 2
+    pub sketch_width: usize,
+    /// Where the chunk and spill files go (they are removed again)
+    pub temp_dir: PathBuf,
+}
+
+impl Default for StreamOptions {
+    /// About 100 MiB at most
+    fn default() -> Self {
+        Self {
+            chunk_len: 1 << 22,
+            max_counters: 1 << 20,
+            sketch_width: 1 << 20,
+            temp_dir: env::temp_dir(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum StreamError {
+    Io(io::Error),
+    Parse(ParseError),
+}
+
+impl Display for StreamError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{error}"),
+            Self::Parse(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl Error for StreamError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::Parse(error) => Some(error),
+        }
+    }
+}
+
+impl From<io::Error> for StreamError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<ParseError> for StreamError {
+    fn from(error: ParseError) -> Self {
+        Self::Parse(error)
+    }
+}
+
+/// Calls `on_pair` with the two numbers of each line of `input`
+fn for_each_pair(
+    input: impl BufRead,
+    mut on_pair: impl FnMut(u32, u32) -> io::Result<()>,
+) -> Result<(), StreamError> {
+    for (line_no, line) in input.lines().enumerate() {
+        let line = line?;
+        let (left, right) = parse_line(&line, &line).map_err(|error| ParseError {
+            line: line_no + 1,
+            ..error
+        })?;
+        on_pair(left, right)?;
+    }
+    Ok(())
+}
+
+/// Part 1 without holding the lists in memory, by an external merge sort of each list
+pub fn part1_streaming(input: impl BufRead, options: &StreamOptions) -> Result<u64, StreamError> {
+    let mut left = ExternalSort::new(options);
+    let mut right = ExternalSort::new(options);
+    for_each_pair(input, |l, r| {
+        left.push(l)?;
+        right.push(r)
+    })?;
+    let mut total = 0;
+    for (l, r) in zip(left.finish()?, right.finish()?) {
+        total += l?.abs_diff(r?) as u64;
+    }
+    Ok(total)
+}
+
+/// Part 2 without holding the lists in memory
+///
+/// The lists are spilled to disk, and a count-min sketch of the right one picks out
+/// the numbers on the left that may be on the right. Only those are counted exactly,
+/// unless there are more than [`StreamOptions::max_counters`] of them:
+/// then both lists are sorted externally and counted side by side.
+pub fn part2_streaming(input: impl BufRead, options: &StreamOptions) -> Result<u64, StreamError> {
+    let mut left = SpillFile::create(options)?;
+    let mut right = SpillFile::create(options)?;
+    let mut sketch = CountMinSketch::new(options.sketch_width);
+    for_each_pair(input, |l, r| {
+        sketch.add(r);
+        left.push(l)?;
+        right.push(r)
+    })?;
+
+    // left count, right count
+    let mut counts: FxHashMap<u32, (u64, u64)> = FxHashMap::default();
+    for l in left.read()? {
+        let l = l?;
+        if sketch.estimate(l) > 0 {
+            counts.entry(l).or_default().0 += 1;
+            if counts.len() > options.max_counters {
+                return Ok(similarity_by_sorting(left, right, options)?);
+            }
+        }
+    }
+    for r in right.read()? {
+        if let Some(count) = counts.get_mut(&r?) {
+            count.1 += 1;
+        }
+    }
+    Ok(counts
+        .into_iter()
+        .map(|(number, (left_count, right_count))| number as u64 * left_count * right_count)
+        .sum())
+}
+
+fn similarity_by_sorting(
+    left: SpillFile,
+    right: SpillFile,
+    options: &StreamOptions,
+) -> io::Result<u64> {
+    let sorted = |mut spill: SpillFile| {
+        let mut sort = ExternalSort::new(options);
+        for number in spill.read()? {
+            sort.push(number?)?;
+        }
+        sort.finish()
+    };
+    let mut left = sorted(left)?.peekable();
+    let mut right = sorted(right)?.peekable();
+    let mut score = 0;
+    // count the runs of each number on both sides
+    while let Some(number) = left.next() {
+        let number = number?;
+        let mut left_count = 1;
+        while let Some(Ok(next)) = left.peek() {
+            if *next != number {
+                break;
+            }
+            left.next();
+            left_count += 1;
+        }
+        let mut right_count = 0;
+        while let Some(next) = right.peek() {
+            match next {
+                Ok(next) if *next < number => {}
+                Ok(next) if *next == number => right_count += 1,
+                Ok(_) => break,
+                Err(_) => return Err(right.next().unwrap().unwrap_err()),
+            }
+            right.next();
+        }
+        score += number as u64 * left_count * right_count;
+    }
+    Ok(score)
+}
+
+/// A count-min sketch: counts in fixed memory that are never too low, but may be too high
+struct CountMinSketch {
+    rows: Vec<Vec<u64>>,
+}
+
+impl CountMinSketch {
+    const DEPTH: usize = 4;
+
+    fn new(width: usize) -> Self {
+        Self {
+            rows: vec![vec![0; width.max(1)]; Self::DEPTH],
+        }
+    }
+
+    fn column(&self, row: usize, number: u32) -> usize {
+        let hash = (number as u64 ^ (row as u64).wrapping_mul(0x517c_c1b7_2722_0a95))
+            .wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        ((hash >> 32) % self.rows[row].len() as u64) as usize
+    }
+
+    fn add(&mut self, number: u32) {
+        for row in 0..Self::DEPTH {
+            let column = self.column(row, number);
+            self.rows[row][column] += 1;
+        }
+    }
+
+    fn estimate(&self, number: u32) -> u64 {
+        (0..Self::DEPTH)
+            .map(|row| self.rows[row][self.column(row, number)])
+            .min()
+            .unwrap_or(0)
+    }
+}
+
+/// A file of numbers in a temporary directory, removed when dropped
+struct SpillFile {
+    path: PathBuf,
+    writer: BufWriter<File>,
+}
+
+impl SpillFile {
+    fn create(options: &StreamOptions) -> io::Result<Self> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = options.temp_dir.join(format!(
+            "aoc2024-day1-{}-{}.bin",
+            process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let writer = BufWriter::new(File::create(&path)?);
+        Ok(Self { path, writer })
+    }
+
+    fn push(&mut self, number: u32) -> io::Result<()> {
+        self.writer.write_all(&number.to_le_bytes())
+    }
+
+    /// The numbers written so far, in order
+    fn read(&mut self) -> io::Result<impl Iterator<Item = io::Result<u32>>> {
+        self.writer.flush()?;
+        let mut reader = BufReader::new(File::open(&self.path)?);
+        Ok(std::iter::from_fn(move || {
+            let mut bytes = [0; 4];
+            match reader.read_exact(&mut bytes) {
+                Ok(()) => Some(Ok(u32::from_le_bytes(bytes))),
+                Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => None,
+                Err(error) => Some(Err(error)),
+            }
+        }))
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Sorts more numbers than fit in memory, as sorted chunk files merged on the way out
+struct ExternalSort<'a> {
+    options: &'a StreamOptions,
+    chunk: Vec<u32>,
+    chunks: Vec<SpillFile>,
+}
+
+impl<'a> ExternalSort<'a> {
+    fn new(options: &'a StreamOptions) -> Self {
+        Self {
+            options,
+            chunk: Vec::new(),
+            chunks: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, number: u32) -> io::Result<()> {
+        self.chunk.push(number);
+        if self.chunk.len() >= self.options.chunk_len.max(1) {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    fn spill(&mut self) -> io::Result<()> {
+        self.chunk.sort_unstable();
+        let mut file = SpillFile::create(self.options)?;
+        for &number in &self.chunk {
+            file.push(number)?;
+        }
+        self.chunks.push(file);
+        self.chunk.clear();
+        Ok(())
+    }
+
+    /// All the numbers, in ascending order
+    fn finish(mut self) -> io::Result<impl Iterator<Item = io::Result<u32>>> {
+        if !self.chunk.is_empty() {
+            self.spill()?;
+        }
+        let mut chunks = self.chunks;
+        let mut readers = chunks
+            .iter_mut()
+            .map(SpillFile::read)
+            .collect::<io::Result<Vec<_>>>()?;
+        // the smallest next number of each chunk, and which chunk it came from
+        let mut heap = BinaryHeap::new();
+        for (index, reader) in readers.iter_mut().enumerate() {
+            if let Some(number) = reader.next() {
+                heap.push(Reverse((number?, index)));
+            }
+        }
+        let mut failed = false;
+        Ok(std::iter::from_fn(move || {
+            // keep the chunk files around for as long as they are read
+            let _ = &chunks;
+            if failed {
+                return None;
+            }
+            let Reverse((number, index)) = heap.pop()?;
+            match readers[index].next() {
+                Some(Ok(next)) => heap.push(Reverse((next, index))),
+                Some(Err(error)) => {
+                    failed = true;
+                    return Some(Err(error));
+                }
+                None => {}
+            }
+            Some(Ok(number))
+        }))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -84,17 +772,34 @@ mod test {
         let expected_left = [3u32, 4, 2, 1, 3, 3];
         let expected_right = [4u32, 3, 5, 3, 9, 3];
 
-        let parsed = parse_pt1(&input);
+        let parsed = parse(input).unwrap();
 
         assert_eq!(parsed.0, expected_left);
         assert_eq!(parsed.1, expected_right);
     }
 
+    #[test]
+    fn parse_malformed_line() {
+        let input = indoc! {"
+            3   4
+            4   3 5
+            "};
+        let error = parse(input).unwrap_err();
+        assert_eq!((error.line, error.column), (2, 7));
+        assert_eq!(error.snippet, "5");
+
+        let error = parse("3   4\n4\n").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 2));
+
+        let error = parse("3   x\n").unwrap_err();
+        assert_eq!(error.expected, "a number");
+    }
+
     #[test]
     fn solve_example_pt1() {
         let left_and_right = (vec![3u32, 4, 2, 1, 3, 3], vec![4u32, 3, 5, 3, 9, 3]);
 
-        let solved = solve_pt1(&left_and_right);
+        let solved = part1(&left_and_right);
 
         assert_eq!(solved, 11u32);
     }
@@ -103,8 +808,136 @@ mod test {
     fn solve_example_pt2() {
         let left_and_right = (vec![3u32, 4, 2, 1, 3, 3], vec![4u32, 3, 5, 3, 9, 3]);
 
-        let solved = solve_pt2(&left_and_right);
+        let solved = part2(&left_and_right);
 
         assert_eq!(solved, 31u32);
     }
+
+    #[test]
+    fn parse_any_number_of_columns() {
+        let columns = parse_columns("3 4 7\n4 3 7\n").unwrap();
+        assert_eq!(columns, [vec![3, 4], vec![4, 3], vec![7, 7]]);
+        assert_eq!(parse_columns("3\n4\n").unwrap(), [vec![3, 4]]);
+
+        let error = parse_columns("3 4 7\n4 3\n").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 1));
+        assert_eq!(error.expected, "3 numbers, like the first line");
+        assert!(parse_columns("").is_err());
+    }
+
+    #[test]
+    fn metrics_on_example() {
+        let left = [3, 4, 2, 1, 3, 3];
+        let right = [4, 3, 5, 3, 9, 3];
+        assert_eq!(SortedL1.compare(&left, &right), 11.0);
+        assert_eq!(SortedL2.compare(&left, &right), 35f64.sqrt());
+        assert!((EarthMover.compare(&left, &right) - 11.0 / 6.0).abs() < 1e-12);
+        assert_eq!(Similarity.compare(&left, &right), 31.0);
+        assert_eq!(Jaccard.compare(&left, &right), 0.5);
+
+        assert_eq!(EarthMover.compare(&[1, 3], &[2]), 1.0);
+        assert!(EarthMover.compare(&[1, 3], &[]).is_nan());
+        assert!(EarthMover.compare(&[], &[]).is_nan());
+        assert_eq!(Jaccard.compare(&[], &[]), 1.0);
+    }
+
+    #[test]
+    fn compare_columns() {
+        let columns = vec![vec![3, 4, 2], vec![4, 3, 5], vec![1, 1, 1]];
+        let comparison = compare(&columns, &SortedL1);
+        assert_eq!(comparison.get(0, 1), 3.0);
+        assert_eq!(comparison.get(2, 1), 9.0);
+        assert_eq!(comparison.get(1, 1), 0.0);
+        assert_eq!(
+            comparison.to_csv(),
+            "sorted-l1,1,2,3\n1,0,3,6\n2,3,0,9\n3,6,9,0\n"
+        );
+        let jaccard = compare(&columns[..2], &Jaccard);
+        assert_eq!(
+            jaccard.to_string(),
+            indoc! {"
+                jaccard      1      2
+                1            1  0.500
+                2        0.500      1
+            "}
+        );
+    }
+
+    #[test]
+    fn explain_example() {
+        let left_and_right = (vec![3u32, 4, 2, 1, 3, 3], vec![4u32, 3, 5, 3, 9, 3]);
+        let part1 = explain_part1(&left_and_right);
+        assert_eq!(part1.total_distance, 11);
+        assert_eq!(
+            part1.to_string(),
+            indoc! {"
+                left  right  distance
+                1         3         2
+                2         3         1
+                3         3         0
+                3         4         1
+                3         5         2
+                4         9         5
+                total distance: 11
+            "}
+        );
+
+        let part2 = explain_part2(&left_and_right);
+        assert_eq!(part2.similarity_score, 31);
+        assert_eq!(
+            part2.to_table().to_csv(),
+            indoc! {"
+                number,left count,right count,contribution
+                1,1,0,0
+                2,1,0,0
+                3,3,3,27
+                4,1,1,4
+            "}
+        );
+    }
+
+    /// Options spilling into a directory of their own, named after `dir` and this process
+    fn streaming_options(dir: &str, chunk_len: usize, max_counters: usize) -> StreamOptions {
+        let temp_dir = env::temp_dir().join(format!("{dir}-{}", process::id()));
+        fs::create_dir_all(&temp_dir).unwrap();
+        StreamOptions {
+            chunk_len,
+            max_counters,
+            sketch_width: 64,
+            temp_dir,
+        }
+    }
+
+    #[test]
+    fn streaming_matches_in_memory() {
+        let tight = streaming_options("aoc2024-day1-tight", 7, 5);
+        let roomy = streaming_options("aoc2024-day1-roomy", 1000, 1000);
+        for seed in 0..10 {
+            let input = crate::inputs::generate(1, seed, 100).unwrap();
+            let lists = parse(&input).unwrap();
+            let (part1, part2) = (part1(&lists) as u64, part2(&lists) as u64);
+            for options in [&tight, &roomy] {
+                assert_eq!(part1_streaming(input.as_bytes(), options).unwrap(), part1);
+                assert_eq!(part2_streaming(input.as_bytes(), options).unwrap(), part2);
+            }
+        }
+        for options in [tight, roomy] {
+            let leftovers = fs::read_dir(&options.temp_dir).unwrap().count();
+            fs::remove_dir(&options.temp_dir).unwrap();
+            assert_eq!(leftovers, 0);
+        }
+    }
+
+    #[test]
+    fn streaming_parse_error() {
+        let options = streaming_options("aoc2024-day1-error", 2, 2);
+        let error = part1_streaming("3   4\n4   3\n4   x\n".as_bytes(), &options).unwrap_err();
+        let StreamError::Parse(error) = error else {
+            panic!("expected a parse error, got {error}");
+        };
+        assert_eq!((error.line, error.column), (3, 5));
+        assert!(part2_streaming("3   4\n4\n".as_bytes(), &options).is_err());
+        assert_eq!(fs::read_dir(&options.temp_dir).unwrap().count(), 0);
+        fs::remove_dir(&options.temp_dir).unwrap();
+    }
 }
@@ -1,57 +1,46 @@
-use std::ops::{Add, Index, IndexMut};
+//! Day 10: Hoof It
 
 use aoc_runner_derive::{aoc, aoc_generator};
 use rustc_hash::FxHashSet;
 
-#[aoc_generator(day10)]
-fn parse(input: &str) -> Grid<u8> {
-    let mut buffer = Vec::with_capacity(input.len());
-    let mut width = None;
-    let mut height = 0;
-    for line in input.trim().lines() {
-        if let Some(width) = width {
-            debug_assert_eq!(line.len(), width);
-        }
-        width = Some(line.len());
+use crate::{
+    grid::{Direction, Grid},
+    parsing::ParseError,
+};
 
-        buffer.extend(
-            line.chars()
-                .map(|c| c.to_digit(10).expect("non-digit char found") as u8),
-        );
-        height += 1;
-    }
-    let width = width.expect("empty input");
-    Grid::<u8> {
-        data: buffer,
-        width,
-        height,
-    }
+/// The topographic map of heights 0 to 9
+pub type Input = Grid<u8>;
+
+/// Parses the map of single-digit heights
+#[aoc_generator(day10)]
+pub fn parse(input: &str) -> Result<Input, ParseError> {
+    Grid::from_char_map(input.trim(), "a height (a digit)", |_, c| {
+        c.to_digit(10).map(|d| d as u8)
+    })
 }
 
+/// The sum of the scores of all trailheads: how many 9s each can reach
 #[aoc(day10, part1)]
-fn part1(input: &Grid<u8>) -> u64 {
+pub fn part1(input: &Grid<u8>) -> u64 {
     // shy flow flows downhill like a liquid, but is scared of heights (only flows when diff is == -1)
     // the vector collects the directions from which this point will be shyly-flowed-into
-    let mut shy_flows = Grid::<Vec<Direction>> {
-        data: vec![Vec::with_capacity(4); input.data.len()],
-        width: input.width,
-        height: input.height,
-    };
+    let mut shy_flows =
+        Grid::<Vec<Direction>>::new(input.width(), input.height(), Vec::with_capacity(4));
     // while doing the first N iterations, we can already collect the points by height
     // which will allow us to build the complete paths from top to bottom in a known, fixed,
     // 9 scans of the grid
     let mut point_idx_by_height = [
-        Vec::with_capacity(input.data.len() / 10),
-        Vec::with_capacity(input.data.len() / 10),
-        Vec::with_capacity(input.data.len() / 10),
-        Vec::with_capacity(input.data.len() / 10),
-        Vec::with_capacity(input.data.len() / 10),
-        Vec::with_capacity(input.data.len() / 10),
-        Vec::with_capacity(input.data.len() / 10),
-        Vec::with_capacity(input.data.len() / 10),
-        Vec::with_capacity(input.data.len() / 10),
+        Vec::with_capacity(input.len() / 10),
+        Vec::with_capacity(input.len() / 10),
+        Vec::with_capacity(input.len() / 10),
+        Vec::with_capacity(input.len() / 10),
+        Vec::with_capacity(input.len() / 10),
+        Vec::with_capacity(input.len() / 10),
+        Vec::with_capacity(input.len() / 10),
+        Vec::with_capacity(input.len() / 10),
+        Vec::with_capacity(input.len() / 10),
     ];
-    for (idx, &height) in input.data.iter().enumerate() {
+    for (idx, &height) in input.iter().enumerate() {
         debug_assert!(height < 10);
         // no flows end in the peak
         if height == 9 {
@@ -59,10 +48,9 @@ fn part1(input: &Grid<u8>) -> u64 {
         }
         point_idx_by_height[height as usize].push(idx);
         let point = input.point_index(idx).unwrap();
-        for direction in DIRECTIONS {
-            let nearest_neighbour = point + direction.step();
-            if let Some(&neighbour_height) = input.get(nearest_neighbour) {
-                if neighbour_height == height + 1 {
+        for direction in Direction::ALL {
+            if let Some(nearest_neighbour) = input.step(point, direction.vector()) {
+                if input[nearest_neighbour] == height + 1 {
                     shy_flows[point].push(direction);
                 }
             }
@@ -71,16 +59,13 @@ fn part1(input: &Grid<u8>) -> u64 {
     let shy_flows = shy_flows;
     let point_idx_by_height = point_idx_by_height;
 
-    let mut flow_sources_ending_at = Grid::<FxHashSet<usize>> {
-        data: vec![FxHashSet::default(); input.data.len()],
-        width: input.width,
-        height: input.height,
-    };
+    let mut flow_sources_ending_at =
+        Grid::<FxHashSet<usize>>::new(input.width(), input.height(), FxHashSet::default());
     // for height 8 (one below the highest) the sources _are_ the higher neighbours
     for flat_idx in &point_idx_by_height[8] {
         let sink_point = shy_flows.point_index(*flat_idx).unwrap();
         for dir in &shy_flows[sink_point] {
-            let source_point = sink_point + dir.step();
+            let source_point = sink_point + dir.vector();
             flow_sources_ending_at[sink_point].insert(shy_flows.flat_index(source_point).unwrap());
         }
     }
@@ -90,7 +75,7 @@ fn part1(input: &Grid<u8>) -> u64 {
         for flat_idx in isoheight_indices {
             let sink_point = shy_flows.point_index(*flat_idx).unwrap();
             for dir in &shy_flows[sink_point] {
-                let source_point = sink_point + dir.step();
+                let source_point = sink_point + dir.vector();
                 // temp borrow is required for borrow checker (I think)
                 // since otherwise both both extend and iter would borrow flow_sources_ending_at,
                 // which is not allowed since the extend-call is a requires a mutable reference.
@@ -107,7 +92,7 @@ fn part1(input: &Grid<u8>) -> u64 {
     // are its score, which is now just a matter of counting.
     point_idx_by_height[0]
         .iter()
-        .map(|flat_idx| flow_sources_ending_at.data[*flat_idx].len() as u64)
+        .map(|flat_idx| flow_sources_ending_at[*flat_idx].len() as u64)
         .sum::<u64>()
 }
 
@@ -115,25 +100,23 @@ fn part1(input: &Grid<u8>) -> u64 {
 /// we now _rate_ them. Completely different!
 /// (rating  = counting unique trails starting from the trail-head)
 /// (scoring = counting unique height-9 peaks reachable from the trail-head)
+/// The sum of the ratings of all trailheads: how many distinct trails each starts
 #[aoc(day10, part2)]
-fn part2(input: &Grid<u8>) -> u64 {
-    let mut shy_flows = Grid::<Vec<Direction>> {
-        data: vec![Vec::with_capacity(4); input.data.len()],
-        width: input.width,
-        height: input.height,
-    };
+pub fn part2(input: &Grid<u8>) -> u64 {
+    let mut shy_flows =
+        Grid::<Vec<Direction>>::new(input.width(), input.height(), Vec::with_capacity(4));
     let mut point_idx_by_height = [
-        Vec::with_capacity(input.data.len() / 10),
-        Vec::with_capacity(input.data.len() / 10),
-        Vec::with_capacity(input.data.len() / 10),
-        Vec::with_capacity(input.data.len() / 10),
-        Vec::with_capacity(input.data.len() / 10),
-        Vec::with_capacity(input.data.len() / 10),
-        Vec::with_capacity(input.data.len() / 10),
-        Vec::with_capacity(input.data.len() / 10),
-        Vec::with_capacity(input.data.len() / 10),
+        Vec::with_capacity(input.len() / 10),
+        Vec::with_capacity(input.len() / 10),
+        Vec::with_capacity(input.len() / 10),
+        Vec::with_capacity(input.len() / 10),
+        Vec::with_capacity(input.len() / 10),
+        Vec::with_capacity(input.len() / 10),
+        Vec::with_capacity(input.len() / 10),
+        Vec::with_capacity(input.len() / 10),
+        Vec::with_capacity(input.len() / 10),
     ];
-    for (idx, &height) in input.data.iter().enumerate() {
+    for (idx, &height) in input.iter().enumerate() {
         debug_assert!(height < 10);
         // no flows end in the peak
         if height == 9 {
@@ -141,10 +124,9 @@ fn part2(input: &Grid<u8>) -> u64 {
         }
         point_idx_by_height[height as usize].push(idx);
         let point = input.point_index(idx).unwrap();
-        for direction in DIRECTIONS {
-            let nearest_neighbour = point + direction.step();
-            if let Some(&neighbour_height) = input.get(nearest_neighbour) {
-                if neighbour_height == height + 1 {
+        for direction in Direction::ALL {
+            if let Some(nearest_neighbour) = input.step(point, direction.vector()) {
+                if input[nearest_neighbour] == height + 1 {
                     shy_flows[point].push(direction);
                 }
             }
@@ -156,15 +138,11 @@ fn part2(input: &Grid<u8>) -> u64 {
     // welcome to the first difference w.r.t. part 1:
     // rather than tracking the sources, we track the unique ways any flow from any source
     // can reach this point.
-    let mut unique_flows_through_here = Grid::<u64> {
-        data: vec![0; input.data.len()],
-        width: input.width,
-        height: input.height,
-    };
+    let mut unique_flows_through_here = Grid::<u64>::new(input.width(), input.height(), 0);
     // for height 8 (one below the highest) the sources _are_ the higher neighbours
     for flat_idx in &point_idx_by_height[8] {
         // one step from the source, every source is a unique flow, bijectively
-        unique_flows_through_here.data[*flat_idx] = shy_flows.data[*flat_idx].len() as u64;
+        unique_flows_through_here[*flat_idx] = shy_flows[*flat_idx].len() as u64;
     }
     // for the other heights, we can step down height by height and aggregate the **flow counts**,
     // according to the local flow (and we skip 8 because we already did it)
@@ -172,7 +150,7 @@ fn part2(input: &Grid<u8>) -> u64 {
         for flat_idx in isoheight_indices {
             let sink_point = shy_flows.point_index(*flat_idx).unwrap();
             for dir in &shy_flows[sink_point] {
-                let source_point = sink_point + dir.step();
+                let source_point = sink_point + dir.vector();
                 // no borrow checker shenanigans because u64 is Copy, so just implicitly cloned here
                 unique_flows_through_here[sink_point] += unique_flows_through_here[source_point];
             }
@@ -185,96 +163,10 @@ fn part2(input: &Grid<u8>) -> u64 {
     // and the number in each height-0 point is already our trail-head rating 🥳.
     point_idx_by_height[0]
         .iter()
-        .map(|flat_idx| flow_sources_ending_at.data[*flat_idx])
+        .map(|flat_idx| flow_sources_ending_at[*flat_idx])
         .sum::<u64>()
 }
 
-struct Grid<Item> {
-    data: Vec<Item>,
-    width: usize,
-    height: usize,
-}
-
-impl<Item> Grid<Item> {
-    fn flat_index(&self, point: Point) -> Option<usize> {
-        if point.x < self.width && point.y < self.height {
-            Some(point.y * self.width + point.x)
-        } else {
-            None
-        }
-    }
-
-    fn point_index(&self, flat_index: usize) -> Option<Point> {
-        if flat_index >= self.data.len() {
-            return None;
-        }
-        Some(Point {
-            x: flat_index % self.width,
-            y: flat_index / self.width,
-        })
-    }
-
-    fn get(&self, point: Point) -> Option<&Item> {
-        self.flat_index(point).map(|idx| &self.data[idx])
-    }
-}
-
-#[derive(Clone, Copy)]
-struct Point {
-    x: usize,
-    y: usize,
-}
-
-impl Add<(isize, isize)> for Point {
-    type Output = Point;
-
-    fn add(self, (dx, dy): (isize, isize)) -> Self::Output {
-        Point {
-            x: (self.x as isize + dx) as usize,
-            y: (self.y as isize + dy) as usize,
-        }
-    }
-}
-
-impl<Item> Index<Point> for Grid<Item> {
-    type Output = Item;
-
-    fn index(&self, point: Point) -> &Self::Output {
-        &self.data[point.y * self.width + point.x]
-    }
-}
-
-impl<Item> IndexMut<Point> for Grid<Item> {
-    fn index_mut(&mut self, point: Point) -> &mut Self::Output {
-        &mut self.data[point.y * self.width + point.x]
-    }
-}
-
-#[derive(Clone, Copy)]
-enum Direction {
-    North,
-    East,
-    South,
-    West,
-}
-impl Direction {
-    fn step(&self) -> (isize, isize) {
-        match self {
-            Direction::North => (0, -1),
-            Direction::East => (1, 0),
-            Direction::South => (0, 1),
-            Direction::West => (-1, 0),
-        }
-    }
-}
-
-const DIRECTIONS: [Direction; 4] = [
-    Direction::North,
-    Direction::East,
-    Direction::South,
-    Direction::West,
-];
-
 #[cfg(test)]
 mod tests {
     use indoc::indoc;
@@ -294,11 +186,11 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse(EXAMPLE_PART_1)), 36);
+        assert_eq!(part1(&parse(EXAMPLE_PART_1).unwrap()), 36);
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse(EXAMPLE_PART_1)), 81);
+        assert_eq!(part2(&parse(EXAMPLE_PART_1).unwrap()), 81);
     }
 }
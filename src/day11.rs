@@ -1,24 +1,34 @@
+//! Day 11: Plutonian Pebbles
+
 use aoc_runner_derive::{aoc, aoc_generator};
 use memoize::memoize;
 
+use crate::parsing::{parse_token, ParseError};
+
+/// The numbers engraved on the stones
+pub type Input = Vec<u64>;
+
+/// Parses the whitespace-separated numbers on the stones
 #[aoc_generator(day11)]
-fn parse(input: &str) -> Vec<u64> {
+pub fn parse(input: &str) -> Result<Input, ParseError> {
     input
         .split_whitespace()
-        .map(|tok| tok.parse().unwrap())
+        .map(|tok| parse_token(input, tok, "a stone (a number)"))
         .collect()
 }
 
+/// The number of stones after blinking 25 times
 #[aoc(day11, part1)]
-fn part1(stones: &[u64]) -> u64 {
+pub fn part1(stones: &[u64]) -> u64 {
     stones
         .iter()
         .map(|&stone| part1_apply_rules_and_count(stone, 25))
         .sum()
 }
 
+/// The number of stones after blinking 75 times
 #[aoc(day11, part2)]
-fn part2(stones: &[u64]) -> u64 {
+pub fn part2(stones: &[u64]) -> u64 {
     // > 1s without memoization, lol
     stones
         .iter()
@@ -49,7 +59,7 @@ fn part1_apply_rules_and_count(stone: u64, times_remaining: u8) -> u64 {
 
 fn split_if_even_digits(stone: u64) -> Option<(u64, u64)> {
     let n_digits = stone.ilog10() + 1;
-    if n_digits % 2 != 0 {
+    if !n_digits.is_multiple_of(2) {
         return None;
     }
     let half_n_digits = n_digits / 2;
@@ -66,6 +76,6 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse(EXAMPLE_PART_1)), 55312);
+        assert_eq!(part1(&parse(EXAMPLE_PART_1).unwrap()), 55312);
     }
 }
@@ -1,30 +1,27 @@
-use std::ops::{Add, Index, IndexMut, Sub};
+//! Day 12: Garden Groups
 
 use aoc_runner_derive::{aoc, aoc_generator};
 use rustc_hash::FxHashMap;
-#[aoc_generator(day12)]
-fn parse(input: &str) -> Grid<char> {
-    let mut data = Vec::with_capacity(input.len());
-    let mut height = 0;
-    let mut width = 0;
-    for line in input.trim().lines() {
-        height += 1;
-        width = line.len();
-        for character in line.chars() {
-            data.push(character);
-        }
-    }
-    debug_assert_eq!(data.len(), height * width);
 
-    Grid {
-        data,
-        width,
-        height,
-    }
+use crate::{
+    grid::{Diagonal, Direction, Grid, Point},
+    parsing::ParseError,
+};
+
+/// The map of garden plots, labelled by plant
+pub type Input = Grid<char>;
+
+/// Parses the map of plant letters
+#[aoc_generator(day12)]
+pub fn parse(input: &str) -> Result<Input, ParseError> {
+    Grid::from_char_map(input.trim(), "a plant (any character)", |_, character| {
+        Some(character)
+    })
 }
 
+/// The total price of fencing all regions: area times perimeter
 #[aoc(day12, part1)]
-fn part1(input: &Grid<char>) -> u64 {
+pub fn part1(input: &Grid<char>) -> u64 {
     let regions = mark_regions_flood_fill(input);
     let region_areas = measure_region_areas(&regions);
     let region_perimeters = measure_region_perimeters(&regions);
@@ -36,8 +33,9 @@ fn part1(input: &Grid<char>) -> u64 {
     total_price
 }
 
+/// The total price with the bulk discount: area times number of sides
 #[aoc(day12, part2)]
-fn part2(input: &Grid<char>) -> u64 {
+pub fn part2(input: &Grid<char>) -> u64 {
     let regions = mark_regions_flood_fill(input);
     let region_areas = measure_region_areas(&regions);
     let region_side_counts = count_region_sides(&regions);
@@ -55,27 +53,18 @@ fn part2(input: &Grid<char>) -> u64 {
 /// The output is the map from points to the most north-westerly point
 /// in the connected region at which the plant can be found.
 fn mark_regions_flood_fill(garden_map: &Grid<char>) -> Grid<Point> {
-    let mut result = Grid {
-        data: vec![None; garden_map.data.len()],
-        width: garden_map.width,
-        height: garden_map.height,
-    };
-    for flat_idx in 0..garden_map.data.len() {
-        let plant_pos = garden_map.point_index(flat_idx).unwrap();
+    let mut result = Grid::new(garden_map.width(), garden_map.height(), None);
+    for plant_pos in garden_map.points() {
         flood_fill(plant_pos, garden_map, plant_pos, &mut result);
     }
-    Grid {
-        data: result.data.iter().map(|opt_pt| opt_pt.unwrap()).collect(),
-        width: result.width,
-        height: result.height,
-    }
+    result.map(|opt_pt| opt_pt.unwrap())
 }
 
 /// Implements the recursive step for flood-fill
 ///
 /// Arguments:
 ///     - `fill_at`:
-///         The point being investigated at the current step
+///         The (in-bounds) point being investigated at the current step
 ///     - `garden`:
 ///         The map of points to plant types
 ///     - `region`:
@@ -93,47 +82,37 @@ fn flood_fill(
     region: Point,
     result: &mut Grid<Option<Point>>,
 ) {
-    match result.get(fill_at) {
-        None => {
-            // out-of-bounds
-            return;
-        }
-        Some(Some(_)) => {
-            // already visited
-            return;
-        }
-        Some(None) => (), // in-bounds, not yet visited
+    if result[fill_at].is_some() {
+        // already visited
+        return;
     }
     if garden[fill_at] != garden[region] {
         return;
     }
     result[fill_at] = Some(region);
-    for direction in DIRECTIONS {
-        flood_fill(fill_at + direction.step(), garden, region, result);
+    for neighbour in garden.neighbours(fill_at) {
+        flood_fill(neighbour, garden, region, result);
     }
 }
 
 fn measure_region_areas(regions: &Grid<Point>) -> FxHashMap<Point, u64> {
     let mut region_areas = FxHashMap::default();
-    for region in regions.data.iter() {
+    for region in regions.iter() {
         *region_areas.entry(*region).or_insert(0u64) += 1;
     }
     region_areas
 }
 
 fn measure_region_perimeters(regions: &Grid<Point>) -> FxHashMap<Point, u64> {
-    let mut perimeter_parts = Grid {
-        data: vec![0u32; regions.height * regions.width],
-        width: regions.width,
-        height: regions.height,
-    };
-    for (flat_idx, plant_region) in regions.data.iter().enumerate() {
-        let plant_pos = regions.point_index(flat_idx).unwrap();
-        for direction in DIRECTIONS {
-            let neighbour_pos = plant_pos + direction.step();
-            match regions.get(neighbour_pos) {
+    let mut perimeter_parts = Grid::new(regions.width(), regions.height(), 0u32);
+    for (plant_pos, plant_region) in regions.enumerate() {
+        for direction in Direction::ALL {
+            match regions
+                .step(plant_pos, direction.vector())
+                .map(|neighbour_pos| regions[neighbour_pos])
+            {
                 Some(neighbour_region) => {
-                    if neighbour_region != plant_region {
+                    if neighbour_region != *plant_region {
                         perimeter_parts[plant_pos] += 1;
                     }
                 }
@@ -144,43 +123,27 @@ fn measure_region_perimeters(regions: &Grid<Point>) -> FxHashMap<Point, u64> {
     let perimeter_parts = perimeter_parts;
 
     let mut region_perimeters = FxHashMap::default();
-    for flat_idx in 0..perimeter_parts.data.len() {
-        let region = regions.data[flat_idx];
-        *region_perimeters.entry(region).or_insert(0u64) += perimeter_parts.data[flat_idx] as u64;
+    for (region, perimeter_part) in regions.iter().zip(perimeter_parts.iter()) {
+        *region_perimeters.entry(*region).or_insert(0u64) += *perimeter_part as u64;
     }
     region_perimeters
 }
 
 fn count_region_sides(regions: &Grid<Point>) -> FxHashMap<Point, u64> {
     let mut side_counts = FxHashMap::default();
-    for (flat_index, region) in regions.data.iter().enumerate() {
-        let plant_pos = regions.point_index(flat_index).unwrap();
+    for (plant_pos, region) in regions.enumerate() {
+        let is_in_region = |step| {
+            regions
+                .step(plant_pos, step)
+                .is_some_and(|pos| regions[pos] == *region)
+        };
         for diag in [Diagonal::NE, Diagonal::NW, Diagonal::SE, Diagonal::SW] {
-            let mut diag_is_in_region = false;
-            if let Some(diag_neighbour) = plant_pos.checked_add(diag.step()) {
-                if let Some(r) = regions.get(diag_neighbour) {
-                    if r == region {
-                        diag_is_in_region = true;
-                    }
-                }
-            }
+            let diag_is_in_region = is_in_region(diag.vector());
             // if the diagonal neigbour is not of our own region,
             // but the plants (counter-)clock wise either both are, or both
             // are not, it is a corner
-            let clockwise_in_region = match plant_pos.checked_add(diag.step_cw()) {
-                None => false,
-                Some(pos) => match regions.get(pos) {
-                    None => false,
-                    Some(r) => r == region,
-                },
-            };
-            let counter_clockwise_in_region = match plant_pos.checked_add(diag.step_ccw()) {
-                None => false,
-                Some(pos) => match regions.get(pos) {
-                    None => false,
-                    Some(r) => r == region,
-                },
-            };
+            let clockwise_in_region = is_in_region(diag.clockwise().vector());
+            let counter_clockwise_in_region = is_in_region(diag.counter_clockwise().vector());
             // note that even if the diagonal is of our region,
             // it can have "snuck around" and may not be directly connected,
             // in the case of the outward corner.
@@ -197,154 +160,6 @@ fn count_region_sides(regions: &Grid<Point>) -> FxHashMap<Point, u64> {
     side_counts
 }
 
-#[derive(Debug)]
-struct Grid<Item> {
-    data: Vec<Item>,
-    width: usize,
-    height: usize,
-}
-
-impl<Item> Grid<Item> {
-    fn flat_index(&self, point: Point) -> Option<usize> {
-        if point.x < self.width && point.y < self.height {
-            Some(point.y * self.width + point.x)
-        } else {
-            None
-        }
-    }
-
-    fn point_index(&self, flat_index: usize) -> Option<Point> {
-        if flat_index >= self.data.len() {
-            return None;
-        }
-        Some(Point {
-            x: flat_index % self.width,
-            y: flat_index / self.width,
-        })
-    }
-
-    fn get(&self, point: Point) -> Option<&Item> {
-        self.flat_index(point).map(|idx| &self.data[idx])
-    }
-}
-
-impl<Item> Index<Point> for Grid<Item> {
-    type Output = Item;
-
-    fn index(&self, point: Point) -> &Self::Output {
-        &self.data[point.y * self.width + point.x]
-    }
-}
-
-impl<Item> IndexMut<Point> for Grid<Item> {
-    fn index_mut(&mut self, point: Point) -> &mut Self::Output {
-        &mut self.data[point.y * self.width + point.x]
-    }
-}
-
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
-struct Point {
-    x: usize,
-    y: usize,
-}
-
-impl Point {
-    fn checked_add(&self, (dx, dy): (isize, isize)) -> Option<Point> {
-        if dx < 0 && dx.unsigned_abs() > self.x {
-            return None;
-        }
-        if dy < 0 && dx.unsigned_abs() > self.y {
-            return None;
-        }
-        Some(*self + (dx, dy))
-    }
-}
-
-impl Add<(isize, isize)> for Point {
-    type Output = Point;
-
-    fn add(self, (dx, dy): (isize, isize)) -> Self::Output {
-        Point {
-            x: (self.x as isize + dx) as usize,
-            y: (self.y as isize + dy) as usize,
-        }
-    }
-}
-
-impl Sub<(isize, isize)> for Point {
-    type Output = Point;
-
-    fn sub(self, (dx, dy): (isize, isize)) -> Self::Output {
-        debug_assert!(dx <= self.x as isize);
-        debug_assert!(dy <= self.y as isize);
-
-        Point {
-            x: (self.x as isize - dx) as usize,
-            y: (self.y as isize - dy) as usize,
-        }
-    }
-}
-
-#[derive(Debug)]
-enum Diagonal {
-    NE,
-    SE,
-    SW,
-    NW,
-}
-impl Diagonal {
-    fn step(&self) -> (isize, isize) {
-        match self {
-            Diagonal::NE => (1, -1),
-            Diagonal::SE => (1, 1),
-            Diagonal::SW => (-1, 1),
-            Diagonal::NW => (-1, -1),
-        }
-    }
-
-    fn step_cw(&self) -> (isize, isize) {
-        match self {
-            Diagonal::NE => Direction::East.step(),
-            Diagonal::SE => Direction::South.step(),
-            Diagonal::SW => Direction::West.step(),
-            Diagonal::NW => Direction::North.step(),
-        }
-    }
-
-    fn step_ccw(&self) -> (isize, isize) {
-        match self {
-            Diagonal::NE => Direction::North.step(),
-            Diagonal::SE => Direction::East.step(),
-            Diagonal::SW => Direction::South.step(),
-            Diagonal::NW => Direction::West.step(),
-        }
-    }
-}
-
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-enum Direction {
-    North,
-    East,
-    South,
-    West,
-}
-impl Direction {
-    fn step(&self) -> (isize, isize) {
-        match self {
-            Direction::West => (-1, 0),
-            Direction::North => (0, -1),
-            Direction::East => (1, 0),
-            Direction::South => (0, 1),
-        }
-    }
-}
-
-const DIRECTIONS: [Direction; 4] = [
-    Direction::North,
-    Direction::East,
-    Direction::South,
-    Direction::West,
-];
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -380,17 +195,17 @@ mod tests {
 
     #[test]
     fn part1_example_small() {
-        assert_eq!(part1(&parse(EXAMPLE_INPUT_SMALL)), 140);
+        assert_eq!(part1(&parse(EXAMPLE_INPUT_SMALL).unwrap()), 140);
     }
 
     #[test]
     fn part1_example_islands() {
-        assert_eq!(part1(&parse(EXAMPLE_INPUT_ISLANDS)), 772);
+        assert_eq!(part1(&parse(EXAMPLE_INPUT_ISLANDS).unwrap()), 772);
     }
 
     #[test]
     fn part1_example_large() {
-        assert_eq!(part1(&parse(EXAMPLE_INPUT_LARGE)), 1930);
+        assert_eq!(part1(&parse(EXAMPLE_INPUT_LARGE).unwrap()), 1930);
     }
 
     #[test]
@@ -399,16 +214,17 @@ mod tests {
             AA
             AA
         "};
-        assert_eq!(part1(&parse(input)), 32);
+        assert_eq!(part1(&parse(input).unwrap()), 32);
     }
 
     #[test]
+    #[allow(clippy::identity_op)] // spelled out as area * perimeter per region
     fn part1_concave_corner() {
         let input = indoc! {"
             ez
             zz
         "};
-        assert_eq!(part1(&parse(input)), 1 * 4 + 3 * (3 + 3 + 2));
+        assert_eq!(part1(&parse(input).unwrap()), 1 * 4 + 3 * (3 + 3 + 2));
     }
 
     #[test]
@@ -419,7 +235,7 @@ mod tests {
             6--7
             --89
         "};
-        assert_eq!(part1(&parse(input)), 9 * 4 + 7 * (2 * 3 + 5 * 2));
+        assert_eq!(part1(&parse(input).unwrap()), 9 * 4 + 7 * (2 * 3 + 5 * 2));
     }
 
     #[test]
@@ -429,22 +245,25 @@ mod tests {
             #..#
             ####
         "};
-        assert_eq!(part1(&parse(input)), 32 + 8 * (4 + 2 * 3 + 3 * 2 + 2));
+        assert_eq!(
+            part1(&parse(input).unwrap()),
+            32 + 8 * (4 + 2 * 3 + 3 * 2 + 2)
+        );
     }
 
     #[test]
     fn part2_example_small() {
-        assert_eq!(part2(&parse(EXAMPLE_INPUT_SMALL)), 80);
+        assert_eq!(part2(&parse(EXAMPLE_INPUT_SMALL).unwrap()), 80);
     }
 
     #[test]
     fn part2_example_islands() {
-        assert_eq!(part2(&parse(EXAMPLE_INPUT_ISLANDS)), 436);
+        assert_eq!(part2(&parse(EXAMPLE_INPUT_ISLANDS).unwrap()), 436);
     }
 
     #[test]
     fn part2_example_large() {
-        assert_eq!(part2(&parse(EXAMPLE_INPUT_LARGE)), 1206);
+        assert_eq!(part2(&parse(EXAMPLE_INPUT_LARGE).unwrap()), 1206);
     }
 
     #[test]
@@ -457,7 +276,7 @@ mod tests {
             EXXXX
             EEEEE
         "};
-        assert_eq!(part2(&parse(input)), 236);
+        assert_eq!(part2(&parse(input).unwrap()), 236);
     }
 
     #[test]
@@ -471,7 +290,7 @@ mod tests {
             ABBAAA
             AAAAAA
         "};
-        assert_eq!(part2(&parse(input)), 368);
+        assert_eq!(part2(&parse(input).unwrap()), 368);
     }
 
     #[test]
@@ -480,7 +299,7 @@ mod tests {
             AB
             BA
         "};
-        assert_eq!(part2(&parse(input)), 4 * 4);
+        assert_eq!(part2(&parse(input).unwrap()), 4 * 4);
     }
 
     #[test]
@@ -491,7 +310,7 @@ mod tests {
             ~#~~
             ~~~~
         "};
-        assert_eq!(part2(&parse(input)), 2 * 4 + (4 * 4 - 2) * (3 * 4));
+        assert_eq!(part2(&parse(input).unwrap()), 2 * 4 + (4 * 4 - 2) * (3 * 4));
     }
 
     #[test]
@@ -501,7 +320,7 @@ mod tests {
             ~#~
             ~~~
         "};
-        assert_eq!(part2(&parse(input)), 2 * 4 + 7 * 10);
+        assert_eq!(part2(&parse(input).unwrap()), 2 * 4 + 7 * 10);
     }
 
     #[test]
@@ -509,7 +328,7 @@ mod tests {
         let input = indoc! {"
             a
         "};
-        assert_eq!(part2(&parse(input)), 4);
+        assert_eq!(part2(&parse(input).unwrap()), 4);
     }
 
     #[test]
@@ -518,7 +337,7 @@ mod tests {
             ab
             bb
         "};
-        assert_eq!(part2(&parse(input)), 4 + 3 * 6);
+        assert_eq!(part2(&parse(input).unwrap()), 4 + 3 * 6);
     }
 
     #[test]
@@ -528,7 +347,7 @@ mod tests {
             OXOXO
             OXXXO
         "};
-        assert_eq!(part2(&parse(input)), 160);
+        assert_eq!(part2(&parse(input).unwrap()), 160);
     }
 
     #[test]
@@ -542,7 +361,7 @@ mod tests {
             .AAA.
             .....
         "};
-        assert_eq!(part2(&parse(input)), 452);
+        assert_eq!(part2(&parse(input).unwrap()), 452);
     }
 
     #[test]
@@ -555,6 +374,6 @@ mod tests {
             XXXTXXX
             XXXXXXX
         "};
-        assert_eq!(part2(&parse(input)), 8 * 8 + 12 * (7 * 6 - 8));
+        assert_eq!(part2(&parse(input).unwrap()), 8 * 8 + 12 * (7 * 6 - 8));
     }
 }
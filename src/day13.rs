@@ -1,67 +1,99 @@
+//! Day 13: Claw Contraption
+
 use aoc_runner_derive::{aoc, aoc_generator};
 use nalgebra::{Matrix2, Vector2};
 use regex::Regex;
 
+use crate::parsing::{parse_token, ParseError};
+
+/// The claw machines
+pub type Input = Vec<ClawMachine>;
+
+/// Parses the claw machines, each two button lines and a prize line
 #[aoc_generator(day13)]
-fn parse(input: &str) -> Vec<ClawMachine> {
-    let button_regex = Regex::new(r"Button [AB]: X\+(\d+), Y\+(\d+)").unwrap();
-    let prize_regex = Regex::new(r"Prize: X=(\d+), Y=(\d+)").unwrap();
+pub fn parse(input: &str) -> Result<Input, ParseError> {
+    let button_regex = Regex::new(r"^Button ([AB]): X\+(\d+), Y\+(\d+)$").unwrap();
+    let prize_regex = Regex::new(r"^Prize: X=(\d+), Y=(\d+)$").unwrap();
     input
         .trim()
         .split("\n\n")
         .map(|para| {
             let mut lines = para.lines();
-            let (_, button_a) = button_regex
-                .captures(lines.next().unwrap())
-                .unwrap()
+            let mut next_line = |expected: &str| {
+                lines
+                    .next()
+                    .ok_or_else(|| ParseError::after(input, para, expected))
+            };
+            let mut parse_button = |name: &str| {
+                let expected = format!("a line like 'Button {name}: X+94, Y+34'");
+                let line = next_line(&expected)?;
+                match button_regex.captures(line).map(|caps| caps.extract()) {
+                    Some((_, [found_name, x, y])) if found_name == name => Ok([
+                        parse_token::<f64>(input, x, "a number")?,
+                        parse_token::<f64>(input, y, "a number")?,
+                    ]),
+                    _ => Err(ParseError::at(input, line, expected)),
+                }
+            };
+            let button_a = parse_button("A")?;
+            let button_b = parse_button("B")?;
+            let expected = "a line like 'Prize: X=8400, Y=5400'";
+            let line = next_line(expected)?;
+            let (_, [x, y]) = prize_regex
+                .captures(line)
+                .ok_or_else(|| ParseError::at(input, line, expected))?
                 .extract();
-            let button_a: [f64; 2] = button_a.map(|tok| tok.parse::<f64>().unwrap());
-            let (_, button_b) = button_regex
-                .captures(lines.next().unwrap())
-                .unwrap()
-                .extract();
-            let button_b: [f64; 2] = button_b.map(|tok| tok.parse::<f64>().unwrap());
-            let (_, prize) = prize_regex
-                .captures(lines.next().unwrap())
-                .unwrap()
-                .extract();
-            let prize: [f64; 2] = prize.map(|tok| tok.parse::<f64>().unwrap());
-            ClawMachine {
+            let prize = [
+                parse_token::<f64>(input, x, "a number")?,
+                parse_token::<f64>(input, y, "a number")?,
+            ];
+            if let Some(extra) = lines.next() {
+                return Err(ParseError::at(
+                    input,
+                    extra,
+                    "a blank line between claw machines",
+                ));
+            }
+            Ok(ClawMachine {
                 buttons: Matrix2::new(button_a[0], button_b[0], button_a[1], button_b[1]),
                 prize_location: Vector2::new(prize[0], prize[1]),
-            }
+            })
         })
         .collect()
 }
 
+/// The fewest tokens needed to win every prize that can be won
 #[aoc(day13, part1)]
-fn part1(claw_machines: &[ClawMachine]) -> u64 {
+pub fn part1(claw_machines: &[ClawMachine]) -> u64 {
+    claw_machines.iter().filter_map(min_tokens).sum()
+}
+
+/// The fewest tokens needed to win the prize of `claw_machine`, or `None` if it cannot be won
+///
+/// Panics if the buttons move the claw in the same direction.
+pub fn min_tokens(claw_machine: &ClawMachine) -> Option<u64> {
     let a_price = 3f64;
     let b_price = 1f64;
-    claw_machines
-        .iter()
-        .map(|claw_machine| {
-            let inverse = claw_machine
-                .buttons
-                .try_inverse()
-                .unwrap_or_else(|| panic!("not invertible: {:?}", claw_machine));
-            let combo = inverse * claw_machine.prize_location;
-            // numbers of button presses must inherently be non-negative and integer
-            let e = 1e-2;
-            if (combo[(0, 0)].fract() > e && combo[(0, 0)].fract() < 1. - e)
-                || (combo[(1, 0)].fract() > e && combo[(1, 0)].fract() < 1. - e)
-                || combo[(0, 0)] < 0.0
-                || combo[(1, 0)] < 0.0
-            {
-                return 0;
-            }
-            (combo[(0, 0)] * a_price + combo[(1, 0)] * b_price).round() as u64
-        })
-        .sum()
+    let inverse = claw_machine
+        .buttons
+        .try_inverse()
+        .unwrap_or_else(|| panic!("not invertible: {:?}", claw_machine));
+    let combo = inverse * claw_machine.prize_location;
+    // numbers of button presses must inherently be non-negative and integer
+    let e = 1e-2;
+    if (combo[(0, 0)].fract() > e && combo[(0, 0)].fract() < 1. - e)
+        || (combo[(1, 0)].fract() > e && combo[(1, 0)].fract() < 1. - e)
+        || combo[(0, 0)] < 0.0
+        || combo[(1, 0)] < 0.0
+    {
+        return None;
+    }
+    Some((combo[(0, 0)] * a_price + combo[(1, 0)] * b_price).round() as u64)
 }
 
+/// The same, with the prizes moved 10000000000000 further away
 #[aoc(day13, part2)]
-fn part2(claw_machines: &[ClawMachine]) -> u64 {
+pub fn part2(claw_machines: &[ClawMachine]) -> u64 {
     let correction = 10000000000000.0;
     let with_corrected_locations: Vec<ClawMachine> = claw_machines
         .iter()
@@ -73,10 +105,11 @@ fn part2(claw_machines: &[ClawMachine]) -> u64 {
     part1(&with_corrected_locations)
 }
 
+/// A claw machine, with how far buttons A and B move the claw as the columns of `buttons`
 #[derive(Debug)]
-struct ClawMachine {
-    buttons: Matrix2<f64>,
-    prize_location: Vector2<f64>,
+pub struct ClawMachine {
+    pub buttons: Matrix2<f64>,
+    pub prize_location: Vector2<f64>,
 }
 
 #[cfg(test)]
@@ -104,11 +137,30 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse(PART_1_EXAMPLE_INPUT)), 480);
+        assert_eq!(part1(&parse(PART_1_EXAMPLE_INPUT).unwrap()), 480);
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse(PART_1_EXAMPLE_INPUT)), 875318608908);
+        assert_eq!(part2(&parse(PART_1_EXAMPLE_INPUT).unwrap()), 875318608908);
+    }
+
+    #[test]
+    fn parse_malformed_claw_machine() {
+        let input = indoc! {"
+            Button A: X+94, Y+34
+            Button B: X+22, Y+67
+            Prize: X=8400, Y=5400
+
+            Button B: X+26, Y+66
+            Button A: X+67, Y+21
+            Prize: X=12748, Y=12176
+        "};
+        let error = parse(input).unwrap_err();
+        assert_eq!((error.line, error.column), (5, 1));
+        assert_eq!(error.expected, "a line like 'Button A: X+94, Y+34'");
+
+        let error = parse("Button A: X+94, Y+34\nButton B: X+22, Y+67\n").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 21));
     }
 }
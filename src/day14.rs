@@ -1,3 +1,5 @@
+//! Day 14: Restroom Redoubt
+
 use std::{
     cmp::Ordering,
     ops::{Add, Mul},
@@ -7,36 +9,48 @@ use aoc_runner_derive::{aoc, aoc_generator};
 use regex::Regex;
 use rustc_hash::FxHashSet;
 
+use crate::{
+    params::{self, Day14Params},
+    parsing::{parse_token, ParseError},
+};
+
+/// The robots, with their initial positions and velocities
+pub type Input = Vec<Robot>;
+
+/// Parses one robot like `p=0,4 v=3,-3` per line
 #[aoc_generator(day14)]
-fn parse(input: &str) -> Vec<Robot> {
-    let robo_regex = Regex::new(r"p=(\d+),(\d+) v=(-?\d+),(-?\d+)").unwrap();
+pub fn parse(input: &str) -> Result<Input, ParseError> {
+    let robo_regex = Regex::new(r"^p=(\d+),(\d+) v=(-?\d+),(-?\d+)$").unwrap();
     input
         .trim()
-        .split('\n')
+        .lines()
         .map(|line| {
-            let (_, [px, py, vx, vy]) = robo_regex.captures(line).unwrap().extract();
-            Robot {
+            let (_, [px, py, vx, vy]) = robo_regex
+                .captures(line)
+                .ok_or_else(|| ParseError::at(input, line, "a robot like 'p=0,4 v=3,-3'"))?
+                .extract();
+            Ok(Robot {
                 pos: Point {
-                    x: px.parse().unwrap(),
-                    y: py.parse().unwrap(),
+                    x: parse_token(input, px, "a position")?,
+                    y: parse_token(input, py, "a position")?,
                 },
                 vel: Velocity {
-                    x: vx.parse().unwrap(),
-                    y: vy.parse().unwrap(),
+                    x: parse_token(input, vx, "a velocity")?,
+                    y: parse_token(input, vy, "a velocity")?,
                 },
-            }
+            })
         })
         .collect()
 }
 
+/// The safety factor after 100 seconds: the product of the number of robots in each quadrant
 #[aoc(day14, part1)]
-fn part1(initial_state: &[Robot]) -> u64 {
-    let (width, height) = match initial_state.iter().map(|r| r.pos.y).max() {
-        None => panic!("empty input"),
-        Some(0..7) => (11i64, 7i64),   // example
-        Some(7..) => (101i64, 103i64), // real
-        Some(..0) => panic!("negative space"),
-    };
+pub fn part1(initial_state: &[Robot]) -> u64 {
+    part1_with(initial_state, params::current().day14)
+}
+
+pub fn part1_with(initial_state: &[Robot], params: Day14Params) -> u64 {
+    let Day14Params { width, height } = params;
     let after_100s: Vec<Robot> = initial_state
         .iter()
         .map(|initial_robot| initial_robot.walk_n_seconds(100, &(width, height)))
@@ -45,10 +59,14 @@ fn part1(initial_state: &[Robot]) -> u64 {
     security_score(&after_100s, width, height)
 }
 
+/// A report of when the robots look most like a Christmas tree, with drawings of the robots
 #[aoc(day14, part2)]
-fn part2(initial_state: &[Robot]) -> String {
-    let width = 101i64;
-    let height = 103i64;
+pub fn part2(initial_state: &[Robot]) -> String {
+    part2_with(initial_state, params::current().day14)
+}
+
+pub fn part2_with(initial_state: &[Robot], params: Day14Params) -> String {
+    let Day14Params { width, height } = params;
 
     let secs_to_vars = (0..(width * height))
         .map(|seconds| {
@@ -240,10 +258,11 @@ fn radial_variance(robots: &[Robot]) -> f64 {
         .fold(0f64, |partial, r| partial + (r / robots.len() as f64))
 }
 
+/// A security robot, walking in a straight line and teleporting around the edges
 #[derive(Debug, Clone)]
-struct Robot {
-    pos: Point,
-    vel: Velocity,
+pub struct Robot {
+    pub pos: Point,
+    pub vel: Velocity,
 }
 
 impl Robot {
@@ -268,9 +287,9 @@ impl Add<Velocity> for Point {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Point {
-    x: i64,
-    y: i64,
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
 }
 
 impl Point {
@@ -283,10 +302,11 @@ impl Point {
     }
 }
 
+/// Tiles per second
 #[derive(Debug, Clone, Copy)]
-struct Velocity {
-    x: i64,
-    y: i64,
+pub struct Velocity {
+    pub x: i64,
+    pub y: i64,
 }
 
 impl Mul<i64> for Velocity {
@@ -321,6 +341,9 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse(EXAMPLE)), 12);
+        assert_eq!(
+            part1_with(&parse(EXAMPLE).unwrap(), Day14Params::EXAMPLE),
+            12
+        );
     }
 }
@@ -1,240 +1,100 @@
+//! Day 16: Reindeer Maze
+
 use aoc_runner_derive::{aoc, aoc_generator};
-use itertools::Itertools;
-use rustc_hash::{FxHashMap, FxHashSet};
-use std::cmp::Ordering;
-use std::collections::BinaryHeap;
-use std::ops::{Add, Index};
+use rustc_hash::FxHashSet;
+
+use crate::{
+    grid::{Direction, Grid, Point},
+    parsing::ParseError,
+    search::{self, Predecessors, SearchResult},
+};
 
+/// The maze, with its start and end tiles
+pub type Input = Maze;
+
+/// Parses the maze of `#`, `.`, and one `S` and `E` each
 #[aoc_generator(day16)]
-fn parse(input: &str) -> Maze {
-    let mut width = 0;
-    let mut height = 0;
-    let mut data = Vec::with_capacity(input.len());
-    for (y, line) in input.trim().lines().enumerate() {
-        height = y;
-        for (x, c) in line.chars().enumerate() {
-            width = x;
-            data.push(match c {
-                '#' => Location::Wall,
-                '.' => Location::Empty,
-                'S' => Location::Start,
-                'E' => Location::End,
-                _ => panic!("Invalid character at ({}, {}): {}", x, y, c),
-            });
+pub fn parse(input: &str) -> Result<Input, ParseError> {
+    let input = input.trim();
+    let maze = Grid::from_char_map(input, "'#', '.', 'S' or 'E'", |_, c| match c {
+        '#' => Some(Location::Wall),
+        '.' => Some(Location::Empty),
+        'S' => Some(Location::Start),
+        'E' => Some(Location::End),
+        _ => None,
+    })?;
+    for (location, name) in [
+        (Location::Start, "start ('S')"),
+        (Location::End, "end ('E')"),
+    ] {
+        if maze.iter().filter(|&&l| l == location).count() != 1 {
+            return Err(ParseError::at(
+                input,
+                input,
+                format!("a maze with exactly one {name}"),
+            ));
         }
     }
-    width += 1;
-    height += 1;
-    debug_assert_eq!(data.len(), width * height);
-    Maze {
-        data,
-        width,
-        height,
-    }
+    Ok(maze)
 }
 
+/// The lowest score a reindeer could get from the start to the end
 #[aoc(day16, part1)]
-fn part1(maze: &Maze) -> u64 {
-    let (distances, _) = kinda_edsger(maze, false);
-    let end = maze.find_end();
-    *[
-        Direction::North,
-        Direction::East,
-        Direction::South,
-        Direction::West,
-    ]
-    .iter()
-    .filter_map(|&d| distances.get(&Reindeer { at: end, to: d }))
-    .min()
-    .unwrap()
+pub fn part1(maze: &Maze) -> u64 {
+    kinda_edsger(maze, Predecessors::None)
+        .goal_distance()
+        .unwrap()
 }
 
+/// The number of tiles on at least one of the best paths
 #[aoc(day16, part2)]
-fn part2(maze: &Maze) -> u64 {
-    let (distances, mut previous) = kinda_edsger(maze, true);
-    let end = maze.find_end();
-    let opti_deer = [
-        Direction::North,
-        Direction::East,
-        Direction::South,
-        Direction::West,
-    ]
-    .iter()
-    .filter_map(|&d| {
-        let rudi = Reindeer { at: end, to: d };
-        let dist = *distances.get(&rudi)?;
-        Some(QueueItem {
-            reindeer: rudi,
-            distance: dist,
-        })
-    })
-    // Because the Ord implmentation for QueueItem inverts distance order,
-    // here we select max_set to get the set with smallest distances.
-    .max_set();
-
-    let helper = opti_deer.iter().map(|x| x.distance).min().unwrap();
-
-    let mut optimal_path_seats = FxHashSet::default();
-    let mut todo = Vec::new();
-    for end_deer in opti_deer {
-        todo.push(end_deer.reindeer);
-    }
-    while let Some(best_reindeer) = todo.pop() {
-        debug_assert!(distances[&best_reindeer] <= helper);
-        optimal_path_seats.insert(best_reindeer.at);
-        if let Some(previous_reindeer) = previous.get_mut(&best_reindeer) {
-            for previous_reindeer in previous_reindeer.drain(..) {
-                debug_assert!(distances[&previous_reindeer] < distances[&best_reindeer]);
-                todo.push(previous_reindeer);
-            }
-        }
-    }
+pub fn part2(maze: &Maze) -> u64 {
+    let optimal_path_seats: FxHashSet<Point> = kinda_edsger(maze, Predecessors::All)
+        .states_on_optimal_paths()
+        .into_iter()
+        .map(|reindeer| reindeer.at)
+        .collect();
     optimal_path_seats.len() as u64
 }
 
-fn kinda_edsger(
-    maze: &Maze,
-    track_paths: bool,
-) -> (FxHashMap<Reindeer, u64>, FxHashMap<Reindeer, Vec<Reindeer>>) {
-    let mut queue = BinaryHeap::new();
-    let mut distances = FxHashMap::default();
-    let mut previous = FxHashMap::default();
-
-    let start = maze.find_start();
+fn kinda_edsger(maze: &Maze, track: Predecessors) -> SearchResult<Reindeer> {
     let start = Reindeer {
-        at: start,
+        at: maze.find_start(),
         to: Direction::East,
     };
-
-    distances.insert(start, 0);
-    queue.push(QueueItem {
-        reindeer: start,
-        distance: 0,
-    });
-
-    let mut min_end = u64::MAX;
-    while let Some(current_best) = queue.pop() {
-        if maze[current_best.reindeer.at] == Location::End && current_best.distance < min_end {
-            min_end = current_best.distance;
-        }
-        if current_best.distance > min_end {
-            continue;
-        }
-        for nearest_reindeer in current_best.reindeer.reachable() {
-            if maze[nearest_reindeer.at] == Location::Wall {
-                continue;
-            }
-            let new_distance = match nearest_reindeer.to == current_best.reindeer.to {
-                true => current_best.distance + 1,     // walking costs 1
-                false => current_best.distance + 1000, // turning costs 1000
-            };
-            match new_distance.cmp(distances.get(&nearest_reindeer).unwrap_or(&u64::MAX)) {
-                Ordering::Greater => continue,
-                Ordering::Less => {
-                    distances.insert(nearest_reindeer, new_distance);
-                    queue.push(QueueItem {
-                        reindeer: nearest_reindeer,
-                        distance: new_distance,
-                    });
-                    if !track_paths {
-                        continue;
-                    }
-                    previous.insert(nearest_reindeer, vec![current_best.reindeer]);
-                }
-                Ordering::Equal => {
-                    if !track_paths {
-                        continue;
-                    }
-                    // it is possible that more than one optimal path leads to the same location,
-                    // so we need to keep track of all previous Reindeer that lead to the same location
-                    previous
-                        .get_mut(&nearest_reindeer)
-                        .unwrap()
-                        .push(current_best.reindeer);
-                }
-            }
-        }
-    }
-    (distances, previous)
-}
-
-struct Maze {
-    data: Vec<Location>,
-    width: usize,
-    height: usize,
-}
-
-impl Index<Point> for Maze {
-    type Output = Location;
-
-    fn index(&self, point: Point) -> &Self::Output {
-        &self.data[self.flat_index(point)]
-    }
-}
+    search::dijkstra(
+        start,
+        |&reindeer: &Reindeer| {
+            reindeer
+                .reachable()
+                .into_iter()
+                .filter(|next| maze[next.at] != Location::Wall)
+                .map(move |next| match next.to == reindeer.to {
+                    true => (next, 1),     // walking costs 1
+                    false => (next, 1000), // turning costs 1000
+                })
+        },
+        |reindeer| maze[reindeer.at] == Location::End,
+        track,
+    )
+}
+
+pub type Maze = Grid<Location>;
 
 impl Maze {
-    fn flat_index(&self, Point { x, y }: Point) -> usize {
-        debug_assert!(x < self.width);
-        debug_assert!(y < self.height);
-        y * self.width + x
-    }
-
-    fn point_index(&self, index: usize) -> Point {
-        debug_assert!(index < self.data.len());
-        Point {
-            x: index % self.width,
-            y: index / self.width,
-        }
-    }
-
     fn find_start(&self) -> Point {
-        self.data
-            .iter()
-            .position(|&l| l == Location::Start)
-            .map(|i| self.point_index(i))
-            .unwrap()
-    }
-
-    fn find_end(&self) -> Point {
-        self.data
-            .iter()
-            .position(|&l| l == Location::End)
-            .map(|i| self.point_index(i))
-            .unwrap()
+        self.position(|&l| l == Location::Start).unwrap()
     }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Location {
+pub enum Location {
     Empty,
     Wall,
     Start,
     End,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct QueueItem {
-    reindeer: Reindeer,
-    distance: u64,
-}
-
-impl Ord for QueueItem {
-    // We stick these into a std::collections::BinaryHeap above, to implement
-    // the queue in Dijkstra's shortest path algo. However, the aforementioned struct
-    // implements max-heap, and we want a min-heap. So this custom impl. flips
-    // when Ordering::Less is returned vs Ordering::Greater, by flipping
-    // callee (self) and arg (other) when forwarding to u64::cmp.
-    fn cmp(&self, other: &Self) -> Ordering {
-        other.distance.cmp(&self.distance)
-    }
-}
-
-impl PartialOrd for QueueItem {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct Reindeer {
     at: Point,
@@ -268,69 +128,6 @@ impl Reindeer {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Point {
-    x: usize,
-    y: usize,
-}
-
-impl Add<Vector> for Point {
-    type Output = Point;
-
-    fn add(self, Vector { dx, dy }: Vector) -> Self::Output {
-        let x = self.x as isize + dx;
-        let y = self.y as isize + dy;
-        debug_assert!(x >= 0);
-        debug_assert!(y >= 0);
-        Point {
-            x: x as usize,
-            y: y as usize,
-        }
-    }
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum Direction {
-    North,
-    East,
-    South,
-    West,
-}
-
-impl Direction {
-    fn vector(&self) -> Vector {
-        match self {
-            Direction::North => Vector { dx: 0, dy: -1 },
-            Direction::East => Vector { dx: 1, dy: 0 },
-            Direction::South => Vector { dx: 0, dy: 1 },
-            Direction::West => Vector { dx: -1, dy: 0 },
-        }
-    }
-
-    fn turn_left(&self) -> Self {
-        match self {
-            Direction::North => Direction::West,
-            Direction::East => Direction::North,
-            Direction::South => Direction::East,
-            Direction::West => Direction::South,
-        }
-    }
-
-    fn turn_right(&self) -> Self {
-        match self {
-            Direction::North => Direction::East,
-            Direction::East => Direction::South,
-            Direction::South => Direction::West,
-            Direction::West => Direction::North,
-        }
-    }
-}
-
-struct Vector {
-    dx: isize,
-    dy: isize,
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -376,21 +173,21 @@ mod tests {
 
     #[test]
     fn part1_example_1() {
-        assert_eq!(part1(&parse(EXAMPLE_1)), 7036);
+        assert_eq!(part1(&parse(EXAMPLE_1).unwrap()), 7036);
     }
 
     #[test]
     fn part1_example_2() {
-        assert_eq!(part1(&parse(EXAMPLE_2)), 11048);
+        assert_eq!(part1(&parse(EXAMPLE_2).unwrap()), 11048);
     }
 
     #[test]
     fn part2_example_1() {
-        assert_eq!(part2(&parse(EXAMPLE_1)), 45);
+        assert_eq!(part2(&parse(EXAMPLE_1).unwrap()), 45);
     }
 
     #[test]
     fn part2_example_2() {
-        assert_eq!(part2(&parse(EXAMPLE_2)), 64);
+        assert_eq!(part2(&parse(EXAMPLE_2).unwrap()), 64);
     }
 }
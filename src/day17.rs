@@ -1,37 +1,67 @@
+//! Day 17: Chronospatial Computer
+
 use std::fmt::{self, Debug, Display, Formatter};
 
 use aoc_runner_derive::{aoc, aoc_generator};
-use itertools::Itertools; // for Iterator::next_tuple
+use itertools::Itertools; // for Iterator::join
+
+use crate::parsing::{parse_token, split_once, ParseError};
+
+/// The initial registers of the device, and its program
+pub type Input = (StrangeDevice, Vec<u8>);
 
+/// Parses the registers A, B and C, a blank line, and the program
 #[aoc_generator(day17)]
-fn parse(input: &str) -> (StrangeDevice, Vec<u8>) {
-    let input = input.replace("\r\n", "\n");
-    let (register_str, program_str) = input.trim().split("\n\n").next_tuple().unwrap();
-
-    // Parse the registers, trusting that the input is well-formed
-    let mut reg_it = register_str
-        .lines()
-        .map(|line| line.split(": ").last().unwrap().parse().unwrap());
-    let initial_state = StrangeDevice::with_registers(
-        reg_it.next().unwrap(),
-        reg_it.next().unwrap(),
-        reg_it.next().unwrap(),
-    );
-
-    let program = program_str
-        .split(' ')
-        .next_tuple::<(&str, &str)>()
-        .unwrap()
-        .1
+pub fn parse(input: &str) -> Result<Input, ParseError> {
+    let input = input.trim().replace("\r\n", "\n");
+    let (register_str, program_str) = split_once(
+        &input,
+        &input,
+        "\n\n",
+        "a blank line between the registers and the program",
+    )?;
+
+    let mut registers = [0u64; 3];
+    let mut reg_lines = register_str.lines();
+    for (register, name) in registers.iter_mut().zip(["A", "B", "C"]) {
+        let expected = format!("a line like 'Register {name}: 729'");
+        let line = reg_lines
+            .next()
+            .ok_or_else(|| ParseError::after(&input, register_str, &expected))?;
+        let value = line
+            .strip_prefix(&format!("Register {name}: "))
+            .ok_or_else(|| ParseError::at(&input, line, &expected))?;
+        *register = parse_token(&input, value, "a register value (a number)")?;
+    }
+    if let Some(extra) = reg_lines.next() {
+        return Err(ParseError::at(&input, extra, "only registers A, B and C"));
+    }
+    let initial_state = StrangeDevice::with_registers(registers[0], registers[1], registers[2]);
+
+    let program_str = program_str
+        .strip_prefix("Program: ")
+        .ok_or_else(|| ParseError::at(&input, program_str, "a line like 'Program: 0,1,5,4'"))?;
+    let program: Vec<u8> = program_str
         .split(',')
-        .map(|tok| tok.parse().unwrap())
-        .collect();
+        .map(|tok| match tok.parse::<u8>() {
+            Ok(code) if code < 8 => Ok(code),
+            _ => Err(ParseError::at(&input, tok, "a 3-bit number")),
+        })
+        .collect::<Result<_, _>>()?;
+    if !program.len().is_multiple_of(2) {
+        return Err(ParseError::after(
+            &input,
+            program_str,
+            "an operand after the last instruction",
+        ));
+    }
 
-    (initial_state, program)
+    Ok((initial_state, program))
 }
 
+/// The comma-separated output of the program
 #[aoc(day17, part1)]
-fn part1((initial_state, program): &(StrangeDevice, Vec<u8>)) -> String {
+pub fn part1((initial_state, program): &(StrangeDevice, Vec<u8>)) -> String {
     eval_program(program, initial_state)
         .output_buffer
         .iter()
@@ -40,8 +70,9 @@ fn part1((initial_state, program): &(StrangeDevice, Vec<u8>)) -> String {
 
 const BE_LOUD: bool = false;
 
+/// The lowest value of register A for which the program outputs a copy of itself
 #[aoc(day17, part2)]
-fn part2((initial_state, program): &(StrangeDevice, Vec<u8>)) -> u64 {
+pub fn part2((initial_state, program): &(StrangeDevice, Vec<u8>)) -> u64 {
     if BE_LOUD {
         println!(
             "Finding initial state of register A that makes a quine of: {}\n",
@@ -131,7 +162,8 @@ fn get_output(program: &[u8], register_a: u64, initial_state: &StrangeDevice) ->
     num_cat(&eval_program(program, &state).output_buffer)
 }
 
-fn eval_program(program: &[u8], state: &StrangeDevice) -> StrangeDevice {
+/// Runs `program` on the device in `state` until it halts, returning the final state
+pub fn eval_program(program: &[u8], state: &StrangeDevice) -> StrangeDevice {
     let mut state = state.clone();
     loop {
         let instruction = Instruction::from_opcode(program[state.instruction_pointer]);
@@ -141,7 +173,7 @@ fn eval_program(program: &[u8], state: &StrangeDevice) -> StrangeDevice {
         );
         // we are set up to deal with instructions being interpreted as operands and vice versa,
         // but I don't think that will happen for our inputs, so I assert to check if it does
-        debug_assert!(state.instruction_pointer % 2 == 0);
+        debug_assert!(state.instruction_pointer.is_multiple_of(2));
         instruction.apply(operand, &mut state);
         // + 1 because we take the operand from the pointer's increment
         if state.instruction_pointer + 1 > program.len() {
@@ -154,17 +186,18 @@ fn num_cat(parts: &[u8]) -> u64 {
     parts.iter().fold(0, |acc, &part| acc * 10 + part as u64)
 }
 
+/// The state of the device: its registers, and what it has output so far
 #[derive(Debug, Clone)]
-struct StrangeDevice {
-    register_a: u64,
-    register_b: u64,
-    register_c: u64,
-    instruction_pointer: usize,
-    output_buffer: Vec<u8>,
+pub struct StrangeDevice {
+    pub register_a: u64,
+    pub register_b: u64,
+    pub register_c: u64,
+    pub instruction_pointer: usize,
+    pub output_buffer: Vec<u8>,
 }
 
 impl StrangeDevice {
-    fn with_registers(register_a: u64, register_b: u64, register_c: u64) -> Self {
+    pub fn with_registers(register_a: u64, register_b: u64, register_c: u64) -> Self {
         Self {
             register_a,
             register_b,
@@ -385,12 +418,12 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse(EXAMPLE_PT1)), "4,6,3,5,6,3,5,2,1,0");
+        assert_eq!(part1(&parse(EXAMPLE_PT1).unwrap()), "4,6,3,5,6,3,5,2,1,0");
     }
 
     #[test]
     fn part1_example_parse() {
-        let (initial_state, program) = parse(EXAMPLE_PT1);
+        let (initial_state, program) = parse(EXAMPLE_PT1).unwrap();
         assert_eq!(initial_state.register_a, 729);
         assert_eq!(initial_state.register_b, 0);
         assert_eq!(initial_state.register_c, 0);
@@ -401,6 +434,18 @@ mod tests {
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse(EXAMPLE_PT2)), 117440);
+        assert_eq!(part2(&parse(EXAMPLE_PT2).unwrap()), 117440);
+    }
+
+    #[test]
+    fn parse_malformed_program() {
+        let error =
+            parse("Register A: 729\nRegister B: 0\nRegister C: 0\n\nProgram: 0,1,8,4").unwrap_err();
+        assert_eq!((error.line, error.column), (5, 14));
+        let error =
+            parse("Register A: 729\nRegister B: 0\nRegister C: 0\n\nProgram: 0,1,5").unwrap_err();
+        assert_eq!(error.expected, "an operand after the last instruction");
+        let error = parse("Register A: 729\nRegister C: 0\n\nProgram: 0,1").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 1));
     }
 }
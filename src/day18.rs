@@ -1,77 +1,67 @@
+//! Day 18: RAM Run
+
 use aoc_runner_derive::{aoc, aoc_generator};
-use itertools::Itertools;
-use rustc_hash::{FxHashMap, FxHashSet};
-use std::{
-    cmp::Ordering,
-    collections::BinaryHeap,
-    fmt::Display,
-    ops::{Add, Index, IndexMut},
-}; // next_tuple
 
+use crate::{
+    grid::{Grid, Point},
+    params::{self, Day18Params},
+    parsing::{parse_token, split_once, ParseError},
+    search::{self, Predecessors},
+};
+
+/// The positions of the falling bytes, in the order they fall
+pub type Input = Vec<Point>;
+
+/// Parses one byte position like `5,4` per line
 #[aoc_generator(day18)]
-fn parse(input: &str) -> Vec<Point> {
+pub fn parse(input: &str) -> Result<Input, ParseError> {
     let input = input.trim().replace("\r\n", "\n");
     input
         .lines()
         .map(|line| {
-            let (x, y) = line
-                .split(',')
-                .map(|s| s.parse().unwrap())
-                .next_tuple()
-                .unwrap();
-            Point { x, y }
+            let (x, y) = split_once(&input, line, ",", "a byte position like '5,4'")?;
+            let x = parse_token(&input, x, "an x coordinate")?;
+            let y = parse_token(&input, y, "a y coordinate")?;
+            Ok(Point::new(x, y))
         })
         .collect()
 }
 
+/// The fewest steps to the exit after the first bytes have fallen (all of them, if there are
+/// fewer than `n_fallen`)
 #[aoc(day18, part1)]
-fn part1(input: &[Point]) -> u64 {
-    let (width, height, n_fallen) = if input
-        .iter()
-        .flat_map(|&Point { x, y }| [x, y])
-        .max()
-        .unwrap()
-        > 6
-    {
-        (71, 71, 1024)
-    } else {
-        (7, 7, 12)
-    };
-    let memory = Memory::new(width, height).with_corrupted(&input[..n_fallen]);
+pub fn part1(input: &[Point]) -> u64 {
+    part1_with(input, params::current().day18)
+}
+
+pub fn part1_with(input: &[Point], params: Day18Params) -> u64 {
+    let Day18Params {
+        width,
+        height,
+        n_fallen,
+    } = params;
+    let memory =
+        Memory::new(width, height, State::Safe).with_corrupted(&input[..n_fallen.min(input.len())]);
 
-    let end = Point {
-        x: width - 1,
-        y: height - 1,
-    };
-    let distances = shortest_path_distances(&memory, end);
-    distances[&end]
+    let end = Point::new(width - 1, height - 1);
+    shortest_path(&memory, end).expect("no path to the exit")
 }
 
+/// The position of the first byte that cuts off the exit
 #[aoc(day18, part2)]
-fn part2(input: &[Point]) -> Point {
-    let (width, height) = if input
-        .iter()
-        .flat_map(|&Point { x, y }| [x, y])
-        .max()
-        .unwrap()
-        > 6
-    {
-        (71, 71)
-    } else {
-        (7, 7)
-    };
+pub fn part2(input: &[Point]) -> Point {
+    part2_with(input, params::current().day18)
+}
 
-    let end = Point {
-        x: width - 1,
-        y: height - 1,
-    };
+pub fn part2_with(input: &[Point], params: Day18Params) -> Point {
+    let Day18Params { width, height, .. } = params;
+    let end = Point::new(width - 1, height - 1);
     let mut before = 0;
     let mut after = input.len() - 1;
     loop {
         let at = (before + after) / 2;
-        let memory = Memory::new(width, height).with_corrupted(&input[..at]);
-        let visited = shortest_path_visited(&memory, end);
-        if visited.contains(&end) {
+        let memory = Memory::new(width, height, State::Safe).with_corrupted(&input[..at]);
+        if shortest_path(&memory, end).is_some() {
             before = at;
         } else {
             after = at;
@@ -82,149 +72,23 @@ fn part2(input: &[Point]) -> Point {
     }
 }
 
-fn shortest_path_distances(memory: &Memory, end: Point) -> FxHashMap<Point, u64> {
-    let mut queue = BinaryHeap::new();
-    let mut distances = FxHashMap::default();
-    let start = Point { x: 0, y: 0 };
-    let mut min_end = u64::MAX;
-
-    distances.insert(start, 0);
-    queue.push(QueueItem {
-        point: start,
-        distance: 0,
-    });
-
-    while let Some(current_best) = queue.pop() {
-        if current_best.point == end && current_best.distance < min_end {
-            min_end = current_best.distance;
-        }
-        if current_best.distance > min_end {
-            continue;
-        }
-        for neighbour in current_best.point.reachable() {
-            if memory.get(neighbour).is_none_or(|s| s == State::Corrupted) {
-                continue;
-            }
-            let new_distance = current_best.distance + 1;
-            if new_distance < *distances.get(&neighbour).unwrap_or(&u64::MAX) {
-                distances.insert(neighbour, new_distance);
-                queue.push(QueueItem {
-                    point: neighbour,
-                    distance: new_distance,
-                });
-            }
-        }
-    }
-    distances
-}
-
-fn shortest_path_visited(memory: &Memory, end: Point) -> FxHashSet<Point> {
-    let mut queue = BinaryHeap::new();
-    let mut distances = FxHashMap::default();
-    let mut visited = FxHashSet::default();
-    let start = Point { x: 0, y: 0 };
-    let mut min_end = u64::MAX;
-
-    distances.insert(start, 0);
-    queue.push(QueueItem {
-        point: start,
-        distance: 0,
-    });
-
-    while let Some(current_best) = queue.pop() {
-        if current_best.point == end && current_best.distance < min_end {
-            min_end = current_best.distance;
-        }
-        if current_best.distance > min_end {
-            continue;
-        }
-        for neighbour in current_best.point.reachable() {
-            if memory.get(neighbour).is_none_or(|s| s == State::Corrupted) {
-                continue;
-            }
-            let new_distance = current_best.distance + 1;
-            match new_distance.cmp(distances.get(&neighbour).unwrap_or(&u64::MAX)) {
-                Ordering::Greater | Ordering::Equal => continue,
-                Ordering::Less => {
-                    distances.insert(neighbour, new_distance);
-                    queue.push(QueueItem {
-                        point: neighbour,
-                        distance: new_distance,
-                    });
-                    visited.insert(neighbour);
-                }
-            }
-        }
-    }
-    visited
-}
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct QueueItem {
-    point: Point,
-    distance: u64,
-}
-
-impl Ord for QueueItem {
-    // We stick these into a std::collections::BinaryHeap above, to implement
-    // the queue in Dijkstra's shortest path algo. However, the aforementioned struct
-    // implements max-heap, and we want a min-heap. So this custom impl. flips
-    // when Ordering::Less is returned vs Ordering::Greater, by flipping
-    // callee (self) and arg (other) when forwarding to u64::cmp.
-    fn cmp(&self, other: &Self) -> Ordering {
-        other.distance.cmp(&self.distance)
-    }
-}
-
-impl PartialOrd for QueueItem {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-struct Memory {
-    data: Vec<State>,
-    width: usize,
-    height: usize,
+fn shortest_path(memory: &Memory, end: Point) -> Option<u64> {
+    search::bfs(
+        Point::new(0, 0),
+        |&point| {
+            memory
+                .neighbours(point)
+                .filter(|&neighbour| memory[neighbour] == State::Safe)
+        },
+        |&point| point == end,
+        Predecessors::None,
+    )
+    .goal_distance()
 }
 
-impl Index<Point> for Memory {
-    type Output = State;
-
-    fn index(&self, point: Point) -> &Self::Output {
-        &self.data[self.flat_index(point)]
-    }
-}
-
-impl IndexMut<Point> for Memory {
-    fn index_mut(&mut self, point: Point) -> &mut Self::Output {
-        let flat_index = self.flat_index(point);
-        &mut self.data[flat_index]
-    }
-}
+type Memory = Grid<State>;
 
 impl Memory {
-    fn flat_index(&self, Point { x, y }: Point) -> usize {
-        debug_assert!(x < self.width);
-        debug_assert!(y < self.height);
-        y * self.width + x
-    }
-
-    fn get(&self, point: Point) -> Option<State> {
-        if point.x >= self.width || point.y >= self.height {
-            None
-        } else {
-            Some(self[point])
-        }
-    }
-
-    fn new(width: usize, height: usize) -> Self {
-        Self {
-            data: vec![State::Safe; width * height],
-            width,
-            height,
-        }
-    }
-
     fn with_corrupted(mut self, corrupted: &[Point]) -> Self {
         for &point in corrupted {
             self[point] = State::Corrupted;
@@ -239,82 +103,6 @@ enum State {
     Corrupted,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Point {
-    x: usize,
-    y: usize,
-}
-
-impl Add<Vector> for Point {
-    type Output = Point;
-
-    fn add(self, Vector { dx, dy }: Vector) -> Self::Output {
-        let x = self.x as isize + dx;
-        let y = self.y as isize + dy;
-        debug_assert!(x >= 0);
-        debug_assert!(y >= 0);
-        Point {
-            x: x as usize,
-            y: y as usize,
-        }
-    }
-}
-
-impl Display for Point {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{},{}", self.x, self.y)
-    }
-}
-
-impl Point {
-    fn checked_add(&self, Vector { dx, dy }: Vector) -> Option<Self> {
-        let x = self.x as isize + dx;
-        let y = self.y as isize + dy;
-        if x < 0 || y < 0 {
-            None
-        } else {
-            Some(Self {
-                x: x as usize,
-                y: y as usize,
-            })
-        }
-    }
-
-    fn reachable(&self) -> impl Iterator<Item = Point> + '_ {
-        [
-            Direction::North,
-            Direction::East,
-            Direction::South,
-            Direction::West,
-        ]
-        .iter()
-        .filter_map(move |&direction| self.checked_add(direction.vector()))
-    }
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum Direction {
-    North,
-    East,
-    South,
-    West,
-}
-
-impl Direction {
-    fn vector(&self) -> Vector {
-        match self {
-            Direction::North => Vector { dx: 0, dy: -1 },
-            Direction::East => Vector { dx: 1, dy: 0 },
-            Direction::South => Vector { dx: 0, dy: 1 },
-            Direction::West => Vector { dx: -1, dy: 0 },
-        }
-    }
-}
-
-struct Vector {
-    dx: isize,
-    dy: isize,
-}
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -350,11 +138,27 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse(EXAMPLE)), 22);
+        assert_eq!(
+            part1_with(&parse(EXAMPLE).unwrap(), Day18Params::EXAMPLE),
+            22
+        );
+    }
+
+    #[test]
+    fn part1_with_more_fallen_than_bytes() {
+        let bytes = parse(EXAMPLE).unwrap();
+        let params = Day18Params {
+            n_fallen: 100_000,
+            ..Day18Params::EXAMPLE
+        };
+        assert_eq!(part1_with(&bytes[..12], params), 22);
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse(EXAMPLE)), Point { x: 6, y: 1 });
+        assert_eq!(
+            part2_with(&parse(EXAMPLE).unwrap(), Day18Params::EXAMPLE),
+            Point::new(6, 1)
+        );
     }
 }
@@ -1,20 +1,50 @@
+//! Day 19: Linen Layout
+
 use aoc_runner_derive::{aoc, aoc_generator};
-// next_tuple
-use itertools::Itertools;
 use regex::{Regex, RegexSet};
 use rustc_hash::FxHashMap;
 
+use crate::parsing::{split_once, ParseError};
+
+/// The available towel patterns, and the desired designs
+pub type Input = (Vec<String>, Vec<String>);
+
+/// Parses the comma-separated patterns, a blank line, and one design per line
 #[aoc_generator(day19)]
-fn parse(input: &str) -> (Vec<String>, Vec<String>) {
+pub fn parse(input: &str) -> Result<Input, ParseError> {
     let input = input.trim().replace("\r\n", "\n");
-    let (towel_patterns, towel_designs): (&str, &str) = input.split("\n\n").next_tuple().unwrap();
-    let towel_patterns: Vec<String> = towel_patterns.split(", ").map(|s| s.to_string()).collect();
-    let towel_designs: Vec<String> = towel_designs.lines().map(|s| s.to_string()).collect();
-    (towel_patterns, towel_designs)
+    let (towel_patterns, towel_designs) = split_once(
+        &input,
+        &input,
+        "\n\n",
+        "a blank line between the patterns and the designs",
+    )?;
+    // both end up in regexes, so make sure there is nothing but stripes in there
+    let stripes = |s: &str| {
+        if !s.is_empty() && s.chars().all(|c| c.is_ascii_lowercase()) {
+            Ok(s.to_string())
+        } else {
+            Err(ParseError::at(
+                &input,
+                s,
+                "colored stripes (lowercase letters)",
+            ))
+        }
+    };
+    let towel_patterns = towel_patterns
+        .split(", ")
+        .map(stripes)
+        .collect::<Result<_, _>>()?;
+    let towel_designs = towel_designs
+        .lines()
+        .map(stripes)
+        .collect::<Result<_, _>>()?;
+    Ok((towel_patterns, towel_designs))
 }
 
+/// The number of designs that can be made from the patterns
 #[aoc(day19, part1)]
-fn part1((towel_patterns, towel_designs): &(Vec<String>, Vec<String>)) -> u64 {
+pub fn part1((towel_patterns, towel_designs): &(Vec<String>, Vec<String>)) -> u64 {
     let patterns = towel_patterns.join("|");
     let patterns = format!("^({})+$", patterns);
     let re = Regex::new(&patterns).unwrap();
@@ -24,8 +54,9 @@ fn part1((towel_patterns, towel_designs): &(Vec<String>, Vec<String>)) -> u64 {
         .count() as u64
 }
 
+/// The total number of ways the designs can be made from the patterns
 #[aoc(day19, part2)]
-fn part2((towel_patterns, towel_designs): &(Vec<String>, Vec<String>)) -> u64 {
+pub fn part2((towel_patterns, towel_designs): &(Vec<String>, Vec<String>)) -> u64 {
     let towel_patterns_for_re: Vec<String> =
         towel_patterns.iter().map(|s| format!("^{}", s)).collect();
     let re_partial = RegexSet::new(towel_patterns_for_re).unwrap();
@@ -87,12 +118,12 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse(EXAMPLE)), 6);
+        assert_eq!(part1(&parse(EXAMPLE).unwrap()), 6);
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse(EXAMPLE)), 16);
+        assert_eq!(part2(&parse(EXAMPLE).unwrap()), 16);
     }
 
     #[test]
@@ -102,7 +133,7 @@ mod tests {
 
             brwrr
         "};
-        assert_eq!(part2(&parse(example)), 2);
+        assert_eq!(part2(&parse(example).unwrap()), 2);
     }
 
     #[test]
@@ -112,7 +143,7 @@ mod tests {
 
             bggr
         "};
-        assert_eq!(part2(&parse(example)), 1);
+        assert_eq!(part2(&parse(example).unwrap()), 1);
     }
 
     #[test]
@@ -122,7 +153,7 @@ mod tests {
 
             gbbr
         "};
-        assert_eq!(part2(&parse(example)), 4);
+        assert_eq!(part2(&parse(example).unwrap()), 4);
     }
 
     #[test]
@@ -132,7 +163,7 @@ mod tests {
 
             rrbgbr
         "};
-        assert_eq!(part2(&parse(example)), 6);
+        assert_eq!(part2(&parse(example).unwrap()), 6);
     }
 
     #[test]
@@ -142,7 +173,7 @@ mod tests {
 
             ubwu
         "};
-        assert_eq!(part2(&parse(example)), 0);
+        assert_eq!(part2(&parse(example).unwrap()), 0);
     }
 
     #[test]
@@ -152,7 +183,7 @@ mod tests {
 
             bwurrg
         "};
-        assert_eq!(part2(&parse(example)), 1);
+        assert_eq!(part2(&parse(example).unwrap()), 1);
     }
 
     #[test]
@@ -162,7 +193,7 @@ mod tests {
 
             brgr
         "};
-        assert_eq!(part2(&parse(example)), 2);
+        assert_eq!(part2(&parse(example).unwrap()), 2);
     }
 
     #[test]
@@ -172,6 +203,14 @@ mod tests {
 
             bbrgwb
         "};
-        assert_eq!(part2(&parse(example)), 0);
+        assert_eq!(part2(&parse(example).unwrap()), 0);
+    }
+
+    #[test]
+    fn parse_malformed_towels() {
+        let error = parse("r, wr, b|g\n\nbrwrr\n").unwrap_err();
+        assert_eq!((error.line, error.column), (1, 8));
+        let error = parse("r, wr, b\n\nbrwrr\nbr.r\n").unwrap_err();
+        assert_eq!((error.line, error.column), (4, 1));
     }
 }
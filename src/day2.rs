@@ -1,26 +1,407 @@
-use std::collections::HashSet;
+//! Day 2: Red-Nosed Reports
+
+use std::{
+    cmp::Ordering,
+    collections::HashSet,
+    fmt::{self, Display, Formatter},
+};
 
 use aoc_runner_derive::{aoc, aoc_generator};
+use itertools::Itertools;
+
+use crate::{
+    params,
+    parsing::{parse_token, ParseError},
+    table::Table,
+};
+
+/// The reports, each a list of levels
+pub type Input = Vec<Vec<u64>>;
+
+/// Parses one report of whitespace-separated levels per line
 #[aoc_generator(day2)]
-fn parse(input: &str) -> Vec<Vec<u64>> {
+pub fn parse(input: &str) -> Result<Input, ParseError> {
     input
         .lines()
         .map(|line| {
+            if line.trim().is_empty() {
+                return Err(ParseError::at(
+                    input,
+                    line,
+                    "a report of at least one level",
+                ));
+            }
             line.split_whitespace()
-                .map(|token| token.parse::<u64>().expect("only numbers or ws in inputs"))
+                .map(|token| parse_token(input, token, "a level (a number)"))
                 .collect()
         })
         .collect()
 }
 
-fn is_level_pair_safe_no_sign(prev: u64, next: u64) -> bool {
-    if prev == next {
-        return false;
-    }
-    if prev.abs_diff(next) > 3 {
-        return false;
+/// The rules a report has to follow to be safe
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SafetyPolicy {
+    /// The smallest allowed difference between neighbouring levels
+    pub min_step: u64,
+    /// The largest allowed difference between neighbouring levels
+    pub max_step: u64,
+    /// Whether equal neighbours break the monotonicity (if `min_step` allows them at all)
+    pub strictly_monotonic: bool,
+    /// How often the levels may switch between increasing and decreasing
+    pub max_direction_changes: usize,
+    /// How many levels the Problem Dampener may remove to make a report safe
+    pub max_removals: usize,
+}
+
+impl SafetyPolicy {
+    /// All increasing or all decreasing, by 1 to 3 at a time
+    pub const PART1: Self = Self {
+        min_step: 1,
+        max_step: 3,
+        strictly_monotonic: true,
+        max_direction_changes: 0,
+        max_removals: 0,
     };
-    true
+    /// The same, but with the Problem Dampener removing up to one level
+    pub const PART2: Self = Self {
+        max_removals: 1,
+        ..Self::PART1
+    };
+
+    fn is_step_size_safe(&self, prev: u64, next: u64) -> bool {
+        (self.min_step..=self.max_step).contains(&prev.abs_diff(next))
+    }
+
+    fn is_step_safe(&self, prev: u64, next: u64) -> bool {
+        self.is_step_size_safe(prev, next) && !(self.strictly_monotonic && prev == next)
+    }
+
+    /// Whether `levels` follow the rules as they are, without removing any
+    pub fn follows_rules(&self, levels: &[u64]) -> bool {
+        self.first_violation(levels).is_none()
+    }
+
+    /// The first pair of neighbouring `levels` that breaks the rules, if any
+    pub fn first_violation(&self, levels: &[u64]) -> Option<Violation> {
+        let mut direction = None;
+        let mut n_changes = 0;
+        for (index, (&prev, &next)) in levels.iter().tuple_windows().enumerate() {
+            let violation = |rule| {
+                Some(Violation {
+                    index,
+                    levels: (prev, next),
+                    rule,
+                })
+            };
+            if prev.abs_diff(next) > self.max_step {
+                return violation(Rule::Jump);
+            }
+            if !self.is_step_safe(prev, next) {
+                return violation(Rule::Flat);
+            }
+            match next.cmp(&prev) {
+                Ordering::Equal => {}
+                step => {
+                    if direction.is_some_and(|direction| direction != step) {
+                        n_changes += 1;
+                        if n_changes > self.max_direction_changes {
+                            return violation(Rule::DirectionChange);
+                        }
+                    }
+                    direction = Some(step);
+                }
+            }
+        }
+        None
+    }
+
+    /// Why `report` is unsafe (without the Problem Dampener), and which single removals fix it
+    pub fn diagnose(&self, report: &[u64]) -> Diagnosis {
+        let first_violation = self.first_violation(report);
+        let fixing_removals = match first_violation {
+            None => Vec::new(),
+            Some(_) => (0..report.len())
+                .filter(|&removed| {
+                    let mut dampened = report.to_vec();
+                    dampened.remove(removed);
+                    self.follows_rules(&dampened)
+                })
+                .collect(),
+        };
+        Diagnosis {
+            first_violation,
+            fixing_removals,
+        }
+    }
+
+    /// Whether `report` follows the rules after removing at most `max_removals` levels
+    pub fn is_safe(&self, report: &[u64]) -> bool {
+        self.dampen(report).is_some()
+    }
+
+    /// The fewest levels (by index, ascending) to remove so that `report` follows the rules,
+    /// or `None` if that takes more than `max_removals`
+    ///
+    /// Dynamic programming over the level kept last: the best way to keep it depends only on
+    /// that level, the direction so far and the direction changes used up, and it can only
+    /// follow one of the `max_removals + 1` levels before it.
+    /// So for n levels and k removals this takes O(n·k) steps (per allowed direction change).
+    pub fn dampen(&self, report: &[u64]) -> Option<Vec<usize>> {
+        let n_levels = report.len();
+        let max_removals = self.max_removals;
+        let n_changes = self.max_direction_changes.min(n_levels) + 1;
+        // the direction so far: none yet, decreasing or increasing
+        let direction_index = |direction: Option<Ordering>| match direction {
+            None => 0,
+            Some(Ordering::Less) => 1,
+            Some(_) => 2,
+        };
+        let state_index = |level: usize, direction, changes: usize| {
+            (level * 3 + direction_index(direction)) * n_changes + changes
+        };
+        // the fewest removals to keep a level in a state, and the state of the level kept before
+        let mut best: Vec<Option<(usize, Option<usize>)>> = vec![None; n_levels * 3 * n_changes];
+
+        for level in 0..n_levels.min(max_removals + 1) {
+            best[state_index(level, None, 0)] = Some((level, None));
+        }
+        for level in 1..n_levels {
+            for previous in level.saturating_sub(max_removals + 1)..level {
+                if !self.is_step_safe(report[previous], report[level]) {
+                    continue;
+                }
+                let skipped = level - previous - 1;
+                let step = report[level].cmp(&report[previous]);
+                for direction in [None, Some(Ordering::Less), Some(Ordering::Greater)] {
+                    for changes in 0..n_changes {
+                        let from = state_index(previous, direction, changes);
+                        let Some((removals, _)) = best[from] else {
+                            continue;
+                        };
+                        let removals = removals + skipped;
+                        if removals > max_removals {
+                            continue;
+                        }
+                        let (direction, changes) = match (direction, step) {
+                            (direction, Ordering::Equal) => (direction, changes),
+                            (Some(direction), step) if direction != step => {
+                                (Some(step), changes + 1)
+                            }
+                            (_, step) => (Some(step), changes),
+                        };
+                        if changes == n_changes {
+                            continue;
+                        }
+                        let to = state_index(level, direction, changes);
+                        if best[to].is_none_or(|(best_removals, _)| removals < best_removals) {
+                            best[to] = Some((removals, Some(from)));
+                        }
+                    }
+                }
+            }
+        }
+
+        let per_level = 3 * n_changes;
+        let last = best
+            .iter()
+            .enumerate()
+            .filter_map(|(state, best)| {
+                let (removals, _) = (*best)?;
+                let removals = removals + (n_levels - 1 - state / per_level);
+                Some((removals, state))
+            })
+            .min();
+        match last {
+            Some((removals, state)) if removals <= max_removals => {
+                let mut kept = vec![false; n_levels];
+                let mut state = Some(state);
+                while let Some(current) = state {
+                    kept[current / per_level] = true;
+                    state = best[current].and_then(|(_, previous)| previous);
+                }
+                Some((0..n_levels).filter(|&level| !kept[level]).collect())
+            }
+            // removing everything leaves nothing to break the rules
+            _ if n_levels <= max_removals => Some((0..n_levels).collect()),
+            _ => None,
+        }
+    }
+
+    /// Like [`Self::is_safe`], but trying every combination of removals
+    pub fn is_safe_by_brute_force(&self, report: &[u64]) -> bool {
+        (0..=self.max_removals.min(report.len())).any(|n_removals| {
+            (0..report.len()).combinations(n_removals).any(|removed| {
+                let dampened = report
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| !removed.contains(i))
+                    .map(|(_, &level)| level)
+                    .collect_vec();
+                self.follows_rules(&dampened)
+            })
+        })
+    }
+}
+
+/// The number of reports that are safe by `policy`
+pub fn count_safe(reports: &[Vec<u64>], policy: &SafetyPolicy) -> u64 {
+    reports
+        .iter()
+        .filter(|report| policy.is_safe(report))
+        .count() as u64
+}
+
+/// The rules a pair of neighbouring levels can break
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rule {
+    /// They differ by less than the policy's `min_step` (usually: not at all)
+    Flat,
+    /// They differ by more than the policy's `max_step`
+    Jump,
+    /// They turn the report around once more than the policy allows
+    DirectionChange,
+}
+
+impl Rule {
+    pub const ALL: [Rule; 3] = [Rule::Flat, Rule::Jump, Rule::DirectionChange];
+}
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Rule::Flat => "flat",
+            Rule::Jump => "jump too large",
+            Rule::DirectionChange => "direction change",
+        })
+    }
+}
+
+/// A pair of neighbouring levels breaking a rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Violation {
+    /// The index of the first level of the pair
+    pub index: usize,
+    pub levels: (u64, u64),
+    pub rule: Rule,
+}
+
+/// Whether a report is safe, and if not, why and how to fix it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnosis {
+    /// The first pair breaking the rules, `None` for safe reports
+    pub first_violation: Option<Violation>,
+    /// The indices of the levels whose removal alone makes an unsafe report safe
+    pub fixing_removals: Vec<usize>,
+}
+
+impl Diagnosis {
+    pub fn is_safe(&self) -> bool {
+        self.first_violation.is_none()
+    }
+
+    /// Whether the report is unsafe, but removing one level would make it safe
+    pub fn is_fixable(&self) -> bool {
+        !self.fixing_removals.is_empty()
+    }
+}
+
+impl Display for Diagnosis {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let Some(violation) = self.first_violation else {
+            return write!(f, "safe");
+        };
+        let (prev, next) = violation.levels;
+        write!(
+            f,
+            "unsafe: {prev} {next} at levels {} and {} is {}",
+            violation.index,
+            violation.index + 1,
+            violation.rule
+        )?;
+        if self.is_fixable() {
+            write!(
+                f,
+                "; fixed by removing level {}",
+                self.fixing_removals.iter().join(" or ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The diagnoses of all reports, with statistics over them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Analysis {
+    pub diagnoses: Vec<Diagnosis>,
+}
+
+impl Analysis {
+    pub fn n_safe(&self) -> usize {
+        self.diagnoses.iter().filter(|d| d.is_safe()).count()
+    }
+
+    pub fn n_fixable(&self) -> usize {
+        self.diagnoses.iter().filter(|d| d.is_fixable()).count()
+    }
+
+    /// The number of unsafe reports that first break `rule`
+    pub fn n_first_broken(&self, rule: Rule) -> usize {
+        self.diagnoses
+            .iter()
+            .filter(|d| d.first_violation.is_some_and(|v| v.rule == rule))
+            .count()
+    }
+
+    /// The unsafe reports by the rule they break first, and how many of them one removal fixes
+    pub fn to_table(&self) -> Table {
+        let mut table = Table::new(["first violation", "reports", "fixable"]);
+        for rule in Rule::ALL {
+            let broken = self
+                .diagnoses
+                .iter()
+                .filter(|d| d.first_violation.is_some_and(|v| v.rule == rule));
+            let (n_broken, n_fixable) = broken.fold((0, 0), |(n_broken, n_fixable), d| {
+                (n_broken + 1, n_fixable + d.is_fixable() as usize)
+            });
+            table.push_row([
+                rule.to_string(),
+                n_broken.to_string(),
+                n_fixable.to_string(),
+            ]);
+        }
+        table
+    }
+}
+
+impl Display for Analysis {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let n_reports = self.diagnoses.len();
+        let n_safe = self.n_safe();
+        writeln!(
+            f,
+            "{n_reports} reports: {n_safe} safe, {} unsafe, {} of which one removal fixes",
+            n_reports - n_safe,
+            self.n_fixable()
+        )?;
+        write!(f, "{}", self.to_table())
+    }
+}
+
+/// Diagnoses every report by `policy`
+pub fn analyse(reports: &[Vec<u64>], policy: &SafetyPolicy) -> Analysis {
+    Analysis {
+        diagnoses: reports
+            .iter()
+            .map(|report| policy.diagnose(report))
+            .collect(),
+    }
+}
+
+/// Whether `policy` is what the pairwise variants below assume: strictly monotonic levels,
+/// and the Problem Dampener removing a single level. For other policies they fall back to
+/// [`SafetyPolicy::is_safe_by_brute_force`].
+fn fits_pairwise_checks(policy: &SafetyPolicy) -> bool {
+    policy.strictly_monotonic && policy.max_direction_changes == 0 && policy.max_removals == 1
 }
 
 fn does_sign_match(prev: u64, next: u64, sign: &i64) -> bool {
@@ -31,34 +412,29 @@ fn does_sign_match(prev: u64, next: u64, sign: &i64) -> bool {
     true
 }
 
-fn is_level_pair_safe(prev: u64, next: u64, sign: &i64) -> bool {
-    if !is_level_pair_safe_no_sign(prev, next) {
+fn is_level_pair_safe(policy: &SafetyPolicy, prev: u64, next: u64, sign: &i64) -> bool {
+    if !policy.is_step_safe(prev, next) {
         return false;
     }
     does_sign_match(prev, next, sign)
 }
 
-fn part_1_is_report_safe(report: &[u64]) -> bool {
-    debug_assert!(report.len() > 1, "got report with less than two readings");
-    let n_pairs = report.len() - 1;
-    let sign = report[1] as i64 - report[0] as i64;
-    for i in 0..n_pairs {
-        if !is_level_pair_safe(report[i], report[i + 1], &sign) {
-            return false;
-        }
-    }
-    true
+fn part_1_is_report_safe(report: &[u64], policy: &SafetyPolicy) -> bool {
+    policy.follows_rules(report)
 }
 
 // clever but wrong
-fn part_2_is_report_safe(report: &[u64]) -> bool {
+fn part_2_is_report_safe(report: &[u64], policy: &SafetyPolicy) -> bool {
+    if !fits_pairwise_checks(policy) {
+        return policy.is_safe_by_brute_force(report);
+    }
     debug_assert!(report.len() > 1, "got report with less than two readings");
     let n_pairs = report.len() - 1;
     let mut have_removed = None;
     let mut sign = report[1] as i64 - report[0] as i64;
     for i in 0..n_pairs {
         if have_removed.is_some_and(|r| r == i)
-            || is_level_pair_safe(report[i], report[i + 1], &sign)
+            || is_level_pair_safe(policy, report[i], report[i + 1], &sign)
         {
             continue;
         }
@@ -70,7 +446,7 @@ fn part_2_is_report_safe(report: &[u64]) -> bool {
             1 => sign = report[2] as i64 - report[0] as i64,
             _ => sign = report[1] as i64 - report[0] as i64,
         }
-        if i > 0 && is_level_pair_safe(report[i - 1], report[i + 1], &sign) {
+        if i > 0 && is_level_pair_safe(policy, report[i - 1], report[i + 1], &sign) {
             have_removed = Some(i);
             continue;
         }
@@ -78,7 +454,7 @@ fn part_2_is_report_safe(report: &[u64]) -> bool {
             1 => sign = report[2] as i64 - report[0] as i64,
             _ => sign = report[1] as i64 - report[0] as i64,
         }
-        if i < report.len() - 2 && is_level_pair_safe(report[i], report[i + 2], &sign) {
+        if i < report.len() - 2 && is_level_pair_safe(policy, report[i], report[i + 2], &sign) {
             have_removed = Some(i + 1);
             continue;
         }
@@ -96,19 +472,29 @@ fn part_2_is_report_safe(report: &[u64]) -> bool {
     true
 }
 
-#[aoc(day2, part1)]
-fn part1(input: &[Vec<u64>]) -> u64 {
-    input
+/// The number of reports that are safe by `policy`, by brute force
+pub fn count_safe_by_brute_force(reports: &[Vec<u64>], policy: &SafetyPolicy) -> u64 {
+    reports
         .iter()
-        .map(|report| part_1_is_report_safe(report) as u64)
-        .sum()
+        .filter(|report| policy.is_safe_by_brute_force(report))
+        .count() as u64
+}
+
+/// The number of safe reports
+#[aoc(day2, part1)]
+pub fn part1(input: &[Vec<u64>]) -> u64 {
+    count_safe(input, &params::current().day2.part1)
 }
 
 #[aoc(day2, part2, clever_but_wrong)]
-fn part2(input: &[Vec<u64>]) -> u64 {
+fn part2_clever(input: &[Vec<u64>]) -> u64 {
+    part2_clever_with(input, &params::current().day2.part2)
+}
+
+fn part2_clever_with(input: &[Vec<u64>], policy: &SafetyPolicy) -> u64 {
     input
         .iter()
-        .map(|report| part_2_is_report_safe(report) as u64)
+        .map(|report| part_2_is_report_safe(report, policy) as u64)
         .sum()
 }
 
@@ -119,9 +505,16 @@ fn part2(input: &[Vec<u64>]) -> u64 {
 // below.
 #[aoc(day2, part2, brute_force)]
 fn part2_brute(input: &[Vec<u64>]) -> u64 {
+    part2_brute_with(input, &params::current().day2.part2)
+}
+
+fn part2_brute_with(input: &[Vec<u64>], policy: &SafetyPolicy) -> u64 {
+    if !fits_pairwise_checks(policy) {
+        return count_safe_by_brute_force(input, policy);
+    }
     let undampened_unsafe: Vec<&Vec<u64>> = input
         .iter()
-        .filter(|&report| !part_1_is_report_safe(report))
+        .filter(|&report| !part_1_is_report_safe(report, policy))
         .collect();
     // initialise with already (undampened) safe reports
     let mut safe = (input.len() - undampened_unsafe.len()) as u64;
@@ -133,8 +526,8 @@ fn part2_brute(input: &[Vec<u64>]) -> u64 {
             - unsafe_report[unsafe_report.len() - 2] as i64;
         let mut legal_to_remove = HashSet::new();
         for i in 0..n_pairs {
-            if !(is_level_pair_safe(unsafe_report[i], unsafe_report[i + 1], &sign_lo)
-                && is_level_pair_safe(unsafe_report[i], unsafe_report[i + 1], &sign_hi))
+            if !(is_level_pair_safe(policy, unsafe_report[i], unsafe_report[i + 1], &sign_lo)
+                && is_level_pair_safe(policy, unsafe_report[i], unsafe_report[i + 1], &sign_hi))
             {
                 legal_to_remove.insert(i);
                 legal_to_remove.insert(i + 1);
@@ -143,7 +536,7 @@ fn part2_brute(input: &[Vec<u64>]) -> u64 {
         for i in legal_to_remove {
             let mut dampened = unsafe_report.to_vec();
             dampened.remove(i);
-            if part_1_is_report_safe(&dampened) {
+            if part_1_is_report_safe(&dampened, policy) {
                 safe += 1;
                 break;
             }
@@ -152,44 +545,16 @@ fn part2_brute(input: &[Vec<u64>]) -> u64 {
     safe
 }
 
+/// By brute force: tries removing every level (or combination of levels, for other policies).
 #[aoc(day2, part2, bruter_force)]
 fn part2_bruter(input: &[Vec<u64>]) -> u64 {
-    let undampened_unsafe: Vec<&Vec<u64>> = input
-        .iter()
-        .filter(|&report| !part_1_is_report_safe(report))
-        .collect();
-    // initialise with already (undampened) safe reports
-    let mut safe = (input.len() - undampened_unsafe.len()) as u64;
-    for unsafe_report in undampened_unsafe.iter() {
-        for i in 0..unsafe_report.len() {
-            let mut dampened = unsafe_report.to_vec();
-            dampened.remove(i);
-            if part_1_is_report_safe(&dampened) {
-                safe += 1;
-                break;
-            }
-        }
-    }
-    safe
+    count_safe_by_brute_force(input, &params::current().day2.part2)
 }
 
-#[aoc(day2, part2, find_the_difference)]
-fn part2_find_the_difference(input: &[Vec<u64>]) -> u64 {
-    println!("let's find the difference");
-    let mut diffsum = 0u64;
-    for i in 0..input.len() {
-        let brute = part2_bruter(&input[i..i + 1]);
-        let clever = part2(&input[i..i + 1]);
-        if brute == clever {
-            print!(".");
-            continue;
-        }
-        diffsum += 1;
-        let inp = &input[i];
-        println!();
-        println!("difference @{i}: brute={brute}, clever={clever}, input={inp:?}")
-    }
-    diffsum
+/// The number of reports that are safe after removing at most one level
+#[aoc(day2, part2, dynamic_programming)]
+pub fn part2(input: &[Vec<u64>]) -> u64 {
+    count_safe(input, &params::current().day2.part2)
 }
 
 #[cfg(test)]
@@ -217,43 +582,53 @@ mod tests {
             [1u64, 3, 6, 7, 9],
         ];
 
-        assert_eq!(&parse(input), &expected);
+        assert_eq!(&parse(input).unwrap(), &expected);
+    }
+
+    #[test]
+    fn parse_malformed_report() {
+        let error = parse("7 6 4\n1 2 x 8\n").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 5));
+        assert_eq!(error.snippet, "x");
+
+        let error = parse("7 6 4\n\n1 2 7\n").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 1));
     }
 
     #[test]
     fn part1_example_report1() {
         let report = [7u64, 6, 4, 2, 1];
-        assert!(part_1_is_report_safe(&report));
+        assert!(part_1_is_report_safe(&report, &SafetyPolicy::PART1));
     }
 
     #[test]
     fn part1_example_report2() {
         let report = [1u64, 2, 7, 8, 9];
-        assert!(!part_1_is_report_safe(&report));
+        assert!(!part_1_is_report_safe(&report, &SafetyPolicy::PART1));
     }
 
     #[test]
     fn part1_example_report3() {
         let report = [9u64, 7, 6, 2, 1];
-        assert!(!part_1_is_report_safe(&report));
+        assert!(!part_1_is_report_safe(&report, &SafetyPolicy::PART1));
     }
 
     #[test]
     fn part1_example_report4() {
         let report = [1u64, 3, 2, 4, 5];
-        assert!(!part_1_is_report_safe(&report));
+        assert!(!part_1_is_report_safe(&report, &SafetyPolicy::PART1));
     }
 
     #[test]
     fn part1_example_report5() {
         let report = [8u64, 6, 4, 4, 1];
-        assert!(!part_1_is_report_safe(&report));
+        assert!(!part_1_is_report_safe(&report, &SafetyPolicy::PART1));
     }
 
     #[test]
     fn part1_example_report6() {
         let report = [1u64, 3, 6, 7, 9];
-        assert!(part_1_is_report_safe(&report));
+        assert!(part_1_is_report_safe(&report, &SafetyPolicy::PART1));
     }
 
     #[test]
@@ -273,41 +648,41 @@ mod tests {
     #[test]
     fn part2_example_report1() {
         let report = [7u64, 6, 4, 2, 1];
-        assert!(part_2_is_report_safe(&report));
+        assert!(part_2_is_report_safe(&report, &SafetyPolicy::PART2));
     }
 
     #[test]
     fn part2_example_report2() {
         let report = [1u64, 2, 7, 8, 9];
-        assert!(!part_2_is_report_safe(&report));
+        assert!(!part_2_is_report_safe(&report, &SafetyPolicy::PART2));
     }
 
     #[test]
     fn part2_example_report3() {
         let report = [9u64, 7, 6, 2, 1];
-        assert!(!part_2_is_report_safe(&report));
+        assert!(!part_2_is_report_safe(&report, &SafetyPolicy::PART2));
     }
 
     #[test]
     fn part2_example_report4() {
         let report = [1u64, 3, 2, 4, 5];
-        assert!(part_2_is_report_safe(&report));
+        assert!(part_2_is_report_safe(&report, &SafetyPolicy::PART2));
     }
 
     #[test]
     fn part2_example_report5() {
         let report = [8u64, 6, 4, 4, 1];
-        assert!(part_2_is_report_safe(&report));
+        assert!(part_2_is_report_safe(&report, &SafetyPolicy::PART2));
     }
 
     #[test]
     fn part2_example_report6() {
         let report = [1u64, 3, 6, 7, 9];
-        assert!(part_2_is_report_safe(&report));
+        assert!(part_2_is_report_safe(&report, &SafetyPolicy::PART2));
     }
 
     #[test]
-    fn part2_example() {
+    fn part2_clever_example() {
         let input = [
             vec![7u64, 6, 4, 2, 1],
             vec![1u64, 2, 7, 8, 9],
@@ -316,7 +691,14 @@ mod tests {
             vec![8u64, 6, 4, 4, 1],
             vec![1u64, 3, 6, 7, 9],
         ];
-        assert_eq!(part2(&input), 4);
+        assert_eq!(part2_clever(&input), 4);
+        let wider = SafetyPolicy {
+            max_step: 5,
+            ..SafetyPolicy::PART2
+        };
+        assert_eq!(count_safe(&input, &wider), 6);
+        assert_eq!(part2_clever_with(&input, &wider), 6);
+        assert_eq!(part2_brute_with(&input, &wider), 6);
     }
 
     #[test]
@@ -339,7 +721,7 @@ mod tests {
     }
 
     #[test]
-    fn part2_bruter_example() {
+    fn part2_example() {
         let input = [
             vec![7u64, 6, 4, 2, 1],
             vec![1u64, 2, 7, 8, 9],
@@ -348,12 +730,200 @@ mod tests {
             vec![8u64, 6, 4, 4, 1],
             vec![1u64, 3, 6, 7, 9],
         ];
-        assert_eq!(part2_bruter(&input[..1]), 1);
-        assert_eq!(part2_bruter(&input[1..2]), 0);
-        assert_eq!(part2_bruter(&input[2..3]), 0);
-        assert_eq!(part2_bruter(&input[3..4]), 1);
-        assert_eq!(part2_bruter(&input[4..5]), 1);
-        assert_eq!(part2_bruter(&input[5..]), 1);
-        assert_eq!(part2_bruter(&input), 4);
+        assert_eq!(part2(&input[..1]), 1);
+        assert_eq!(part2(&input[1..2]), 0);
+        assert_eq!(part2(&input[2..3]), 0);
+        assert_eq!(part2(&input[3..4]), 1);
+        assert_eq!(part2(&input[4..5]), 1);
+        assert_eq!(part2(&input[5..]), 1);
+        assert_eq!(part2(&input), 4);
+    }
+
+    #[test]
+    fn policy_variants() {
+        let input = parse(indoc! {"
+            7 6 4 2 1
+            1 2 7 8 9
+            9 7 6 2 1
+            1 3 2 4 5
+            8 6 4 4 1
+            1 3 6 7 9
+        "})
+        .unwrap();
+        assert_eq!(count_safe(&input, &SafetyPolicy::PART1), 2);
+        assert_eq!(count_safe(&input, &SafetyPolicy::PART2), 4);
+
+        let wider = SafetyPolicy {
+            max_step: 5,
+            ..SafetyPolicy::PART1
+        };
+        assert!(wider.is_safe(&[1, 2, 7, 8, 9]));
+        assert!(!wider.is_safe(&[9, 7, 6, 2, 1, 1]));
+
+        let flat_ok = SafetyPolicy {
+            min_step: 0,
+            strictly_monotonic: false,
+            ..SafetyPolicy::PART1
+        };
+        assert!(flat_ok.is_safe(&[8, 6, 4, 4, 1]));
+        assert!(!flat_ok.is_safe(&[1, 3, 3, 2]));
+        // a step of 0 is still not allowed by a strict policy
+        let strict = SafetyPolicy {
+            strictly_monotonic: true,
+            ..flat_ok
+        };
+        assert!(!strict.is_safe(&[8, 6, 4, 4, 1]));
+
+        let one_turn = SafetyPolicy {
+            max_direction_changes: 1,
+            ..SafetyPolicy::PART1
+        };
+        assert!(one_turn.is_safe(&[1, 3, 2, 1]));
+        assert!(!one_turn.is_safe(&[1, 3, 2, 4]));
+
+        let two_removals = SafetyPolicy {
+            max_removals: 2,
+            ..SafetyPolicy::PART1
+        };
+        assert!(two_removals.is_safe(&[1, 2, 7, 8, 3, 4]));
+        assert!(!SafetyPolicy::PART2.is_safe(&[1, 2, 7, 8, 3, 4]));
+        assert_eq!(count_safe(&input, &two_removals), 6);
+    }
+
+    #[test]
+    fn dampen_returns_removals() {
+        let policy = SafetyPolicy::PART2;
+        assert_eq!(policy.dampen(&[7, 6, 4, 2, 1]), Some(vec![]));
+        assert_eq!(policy.dampen(&[1, 2, 7, 8, 9]), None);
+        assert_eq!(policy.dampen(&[1, 4, 2, 3]), Some(vec![1]));
+        // either of the equal levels will do
+        assert_eq!(policy.dampen(&[8, 6, 4, 4, 1]).unwrap().len(), 1);
+        // removing the first or the last level needs no step across the gap
+        assert_eq!(policy.dampen(&[9, 1, 2, 3]), Some(vec![0]));
+        assert_eq!(policy.dampen(&[1, 2, 3, 9]), Some(vec![3]));
+
+        let policy = SafetyPolicy {
+            max_removals: 3,
+            ..SafetyPolicy::PART1
+        };
+        assert_eq!(policy.dampen(&[1, 2, 7, 8, 9]), Some(vec![0, 1]));
+        assert_eq!(policy.dampen(&[1, 9, 8, 2, 3, 4]), Some(vec![1, 2]));
+        assert_eq!(policy.dampen(&[5, 5, 5]).unwrap().len(), 2);
+        assert_eq!(policy.dampen(&[]), Some(vec![]));
+    }
+
+    #[test]
+    fn dampen_agrees_with_brute_force() {
+        let strict = [0, 1, 2, 3].map(|max_removals| SafetyPolicy {
+            max_removals,
+            ..SafetyPolicy::PART1
+        });
+        let lenient = [0, 1, 2].map(|max_removals| SafetyPolicy {
+            min_step: 0,
+            max_step: 4,
+            strictly_monotonic: false,
+            max_direction_changes: 1,
+            max_removals,
+        });
+        let wider = [(1, 4), (2, 5)].map(|(min_step, max_step)| SafetyPolicy {
+            min_step,
+            max_step,
+            ..SafetyPolicy::PART2
+        });
+        for seed in 0..10 {
+            let input =
+                crate::inputs::generate_with(2, seed, 100, crate::params::Params::EXAMPLE).unwrap();
+            let reports = parse(input.trim_end()).unwrap();
+            for policy in strict.iter().chain(&lenient).chain(&wider) {
+                for report in &reports {
+                    let removals = policy.dampen(report);
+                    assert_eq!(
+                        removals.is_some(),
+                        policy.is_safe_by_brute_force(report),
+                        "{report:?} by {policy:?}"
+                    );
+                    if let Some(removals) = removals {
+                        let dampened = report
+                            .iter()
+                            .enumerate()
+                            .filter(|(i, _)| !removals.contains(i))
+                            .map(|(_, &level)| level)
+                            .collect_vec();
+                        assert!(policy.follows_rules(&dampened), "{report:?} by {policy:?}");
+                        let fewer = SafetyPolicy {
+                            max_removals: removals.len().saturating_sub(1),
+                            ..*policy
+                        };
+                        assert!(
+                            removals.is_empty() || !fewer.is_safe_by_brute_force(report),
+                            "{report:?} by {policy:?}"
+                        );
+                    }
+                }
+                assert_eq!(
+                    part2_brute_with(&reports, policy),
+                    count_safe(&reports, policy),
+                    "{policy:?}"
+                );
+            }
+            assert_eq!(part2(&reports), part2_brute(&reports));
+            assert_eq!(part2(&reports), part2_bruter(&reports));
+        }
+    }
+
+    #[test]
+    fn analyse_example() {
+        let input = parse(indoc! {"
+            7 6 4 2 1
+            1 2 7 8 9
+            9 7 6 2 1
+            1 3 2 4 5
+            8 6 4 4 1
+            1 3 6 7 9
+        "})
+        .unwrap();
+        let analysis = analyse(&input, &SafetyPolicy::PART1);
+        let diagnoses = &analysis.diagnoses;
+        assert!(diagnoses[0].is_safe());
+        assert_eq!(
+            diagnoses[1].first_violation,
+            Some(Violation {
+                index: 1,
+                levels: (2, 7),
+                rule: Rule::Jump
+            })
+        );
+        assert!(!diagnoses[1].is_fixable());
+        assert_eq!(
+            diagnoses[3].first_violation.map(|v| (v.index, v.rule)),
+            Some((1, Rule::DirectionChange))
+        );
+        assert_eq!(diagnoses[3].fixing_removals, [1, 2]);
+        assert_eq!(
+            diagnoses[4].first_violation.map(|v| (v.index, v.rule)),
+            Some((2, Rule::Flat))
+        );
+        assert_eq!(diagnoses[4].fixing_removals, [2, 3]);
+        assert_eq!(
+            diagnoses[3].to_string(),
+            "unsafe: 3 2 at levels 1 and 2 is direction change; fixed by removing level 1 or 2"
+        );
+
+        assert_eq!((analysis.n_safe(), analysis.n_fixable()), (2, 2));
+        assert_eq!(analysis.n_first_broken(Rule::Jump), 2);
+        assert_eq!(
+            analysis.n_safe() + analysis.n_fixable(),
+            part2(&input) as usize
+        );
+        assert_eq!(
+            analysis.to_string(),
+            indoc! {"
+                6 reports: 2 safe, 4 unsafe, 2 of which one removal fixes
+                first violation   reports  fixable
+                flat                    1        1
+                jump too large          2        0
+                direction change        1        1
+            "}
+        );
     }
 }
@@ -1,65 +1,66 @@
+//! Day 20: Race Condition
+
 use aoc_runner_derive::{aoc, aoc_generator};
 use rustc_hash::FxHashMap;
 
+use crate::{
+    grid::{Grid, Point},
+    params::{self, Day20Params},
+    parsing::ParseError,
+    search::{self, Predecessors},
+};
+
+/// The race track, with its start and end
+pub type Input = RaceTrack;
+
+/// Parses the race track of `#`, `.`, and one `S` and `E` each
 #[aoc_generator(day20)]
-fn parse(input: &str) -> RaceTrack {
+pub fn parse(input: &str) -> Result<Input, ParseError> {
     let input = input.trim().replace("\r\n", "\n");
 
-    let mut data = Vec::new();
-    let mut width = 0;
-    let mut height = 0;
     let mut start = None;
     let mut end = None;
+    let track = Grid::from_char_map(
+        &input,
+        "'#', '.', or the one and only 'S' and 'E'",
+        |point, c| match c {
+            '#' => Some(Location::Wall),
+            '.' => Some(Location::Empty),
+            'S' if start.is_none() => {
+                start = Some(point);
+                Some(Location::Empty)
+            }
+            'E' if end.is_none() => {
+                end = Some(point);
+                Some(Location::Empty)
+            }
+            _ => None,
+        },
+    )?;
+    let start = start.ok_or_else(|| ParseError::at_end(&input, "a start ('S')"))?;
+    let end = end.ok_or_else(|| ParseError::at_end(&input, "an end ('E')"))?;
 
-    for (y, line) in input.lines().enumerate() {
-        height = y + 1;
-        width = line.len();
-        for (x, c) in line.chars().enumerate() {
-            let location = match c {
-                '#' => Location::Wall,
-                '.' => Location::Empty,
-                'S' => {
-                    start = Some(Point { x, y });
-                    Location::Empty
-                }
-                'E' => {
-                    end = Some(Point { x, y });
-                    Location::Empty
-                }
-                _ => panic!("invalid character: {}", c),
-            };
-            data.push(location);
-        }
-    }
-    debug_assert_eq!(data.len(), width * height);
-    let start = start.expect("start not found");
-    let end = end.expect("end not found");
-
-    RaceTrack {
-        data,
-        width,
-        height,
-        start,
-        end,
-    }
+    Ok(RaceTrack { track, start, end })
 }
 
+/// The number of cheats of up to 2 picoseconds that save enough time
 #[aoc(day20, part1)]
-fn part1(race_track: &RaceTrack) -> u64 {
-    let min_gain = match race_track.width {
-        15 => 1,  // example
-        _ => 100, // real input
-    };
-    count_cheats(race_track, 2, min_gain)
+pub fn part1(race_track: &RaceTrack) -> u64 {
+    part1_with(race_track, params::current().day20)
+}
+
+pub fn part1_with(race_track: &RaceTrack, params: Day20Params) -> u64 {
+    count_cheats(race_track, 2, params.part1_min_gain)
 }
 
+/// The number of cheats of up to 20 picoseconds that save enough time
 #[aoc(day20, part2)]
-fn part2(race_track: &RaceTrack) -> u64 {
-    let min_gain = match race_track.width {
-        15 => 50, // example
-        _ => 100, // real input
-    };
-    count_cheats(race_track, 20, min_gain)
+pub fn part2(race_track: &RaceTrack) -> u64 {
+    part2_with(race_track, params::current().day20)
+}
+
+pub fn part2_with(race_track: &RaceTrack, params: Day20Params) -> u64 {
+    count_cheats(race_track, 20, params.part2_min_gain)
 }
 
 fn count_cheats(race_track: &RaceTrack, cheat_duration: usize, min_gain: isize) -> u64 {
@@ -68,7 +69,7 @@ fn count_cheats(race_track: &RaceTrack, cheat_duration: usize, min_gain: isize)
         .iter()
         .flat_map(|pd1| distances.iter().map(move |pd2| (pd1, pd2)))
         // ensure that we count each pair only once (and skip the pair with the same point)
-        .filter(|((&p1, _), (&p2, _))| race_track.flat_index(p1) > race_track.flat_index(p2))
+        .filter(|((p1, _), (p2, _))| p1 > p2)
         .filter(|((&p1, &d1), (&p2, &d2))| {
             let distance = p1.manhattan_distance(&p2);
             let gain: isize = d1.abs_diff(d2) as isize - distance as isize;
@@ -77,130 +78,39 @@ fn count_cheats(race_track: &RaceTrack, cheat_duration: usize, min_gain: isize)
         .count() as u64
 }
 
-/// Traverses an assumed-to-be linear path from start to end, returning the distances of each point
+/// The distances of each point on the track from the start (up to the end)
 ///
 /// The path is assumed to be linear, with only one possible path from start to end.
 /// This is given for all inputs I have seen, (and stated in the problem description).
-/// It allows us to do a super simple traversal without keeping track of an open set or closed set,
-/// or allow for backtracking. (IoW no need for Dijkstra or BFS or DFS more generally)
-fn distances_from_start(race_track: &RaceTrack) -> FxHashMap<Point, usize> {
-    let mut distances = FxHashMap::default();
-    let mut at = race_track.start;
-    let mut prev = race_track.start;
-    let mut distance = 0;
-    distances.insert(at, distance);
-    while at != race_track.end {
-        for neighbour in at.neighbours() {
-            if neighbour != prev && race_track.get(neighbour) == Some(Location::Empty) {
-                distance += 1;
-                distances.insert(neighbour, distance);
-                prev = at;
-                at = neighbour;
-                break;
-            }
-        }
-    }
-    distances
+/// It would allow for a simpler traversal without an open set or closed set,
+/// but a plain BFS does the job just as well.
+fn distances_from_start(race_track: &RaceTrack) -> FxHashMap<Point, u64> {
+    let track = &race_track.track;
+    search::bfs(
+        race_track.start,
+        |&at| {
+            track
+                .neighbours(at)
+                .filter(|&neighbour| track[neighbour] == Location::Empty)
+        },
+        |&at| at == race_track.end,
+        Predecessors::None,
+    )
+    .into_distances()
 }
-struct RaceTrack {
-    data: Vec<Location>,
-    width: usize,
-    height: usize,
-    start: Point,
-    end: Point,
-}
-
-impl RaceTrack {
-    fn get(&self, Point { x, y }: Point) -> Option<Location> {
-        if x < self.width && y < self.height {
-            Some(self.data[self.flat_index(Point { x, y })])
-        } else {
-            None
-        }
-    }
 
-    fn flat_index(&self, Point { x, y }: Point) -> usize {
-        y * self.width + x
-    }
+pub struct RaceTrack {
+    pub track: Grid<Location>,
+    pub start: Point,
+    pub end: Point,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Location {
+pub enum Location {
     Wall,
     Empty,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Point {
-    x: usize,
-    y: usize,
-}
-
-impl Point {
-    fn checked_add(&self, vector: &Vector) -> Option<Point> {
-        let x = self.x as isize + vector.dx;
-        let y = self.y as isize + vector.dy;
-        if x >= 0 && y >= 0 {
-            Some(Point {
-                x: x as usize,
-                y: y as usize,
-            })
-        } else {
-            None
-        }
-    }
-
-    fn neighbours(&self) -> Vec<Point> {
-        let mut neighbours = vec![];
-        for direction in Direction::all().into_iter() {
-            if let Some(pt) = self.checked_add(&direction.vector()) {
-                neighbours.push(pt);
-            }
-        }
-        neighbours
-    }
-
-    fn manhattan_distance(&self, other: &Point) -> usize {
-        let dx = self.x.abs_diff(other.x);
-        let dy = self.y.abs_diff(other.y);
-        dx + dy
-    }
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Direction {
-    North,
-    East,
-    South,
-    West,
-}
-
-impl Direction {
-    fn all() -> [Direction; 4] {
-        [
-            Direction::North,
-            Direction::East,
-            Direction::South,
-            Direction::West,
-        ]
-    }
-
-    fn vector(&self) -> Vector {
-        match self {
-            Direction::North => Vector { dx: 0, dy: -1 },
-            Direction::East => Vector { dx: 1, dy: 0 },
-            Direction::South => Vector { dx: 0, dy: 1 },
-            Direction::West => Vector { dx: -1, dy: 0 },
-        }
-    }
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct Vector {
-    dx: isize,
-    dy: isize,
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -227,13 +137,16 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse(EXAMPLE)), 44);
+        assert_eq!(
+            part1_with(&parse(EXAMPLE).unwrap(), Day20Params::EXAMPLE),
+            44
+        );
     }
 
     #[test]
     fn part2_example() {
         assert_eq!(
-            part2(&parse(EXAMPLE)),
+            part2_with(&parse(EXAMPLE).unwrap(), Day20Params::EXAMPLE),
             32 + 31 + 29 + 39 + 25 + 23 + 20 + 19 + 12 + 14 + 12 + 22 + 4 + 3
         );
     }
@@ -1,36 +1,71 @@
+//! Day 21: Keypad Conundrum
+
 use std::collections::VecDeque;
 use std::fmt::Display;
+use std::hash::Hash;
 use std::iter;
 
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
 use memoize::memoize;
-use rustc_hash::{FxHashMap, FxHashSet};
+use rustc_hash::FxHashMap;
+
+use crate::{
+    params::{self, Day21Params},
+    parsing::ParseError,
+    search::{self, Predecessors},
+};
+
+/// The five door codes, each as its characters and its numeric part
+pub type Input = [([char; 4], u64); 5];
 
+/// Parses the five door codes like `029A`
 #[aoc_generator(day21)]
-fn parse(input: &str) -> [([char; 4], u64); 5] {
+pub fn parse(input: &str) -> Result<Input, ParseError> {
     let input = input.trim().replace("\r\n", "\n");
     let mut parsed = [([' '; 4], 0); 5];
-    for (i, line) in input.lines().enumerate() {
+    let mut lines = input.lines();
+    for code in parsed.iter_mut() {
+        let line = lines
+            .next()
+            .ok_or_else(|| ParseError::at_end(&input, "five door codes"))?;
+        let digits = line
+            .strip_suffix('A')
+            .filter(|digits| digits.len() == 3 && digits.bytes().all(|b| b.is_ascii_digit()));
+        let Some(digits) = digits else {
+            return Err(ParseError::at(
+                &input,
+                line,
+                "a door code of three digits and an 'A'",
+            ));
+        };
         for (j, c) in line.chars().enumerate() {
-            parsed[i].0[j] = c;
+            code.0[j] = c;
         }
-        parsed[i].1 = line[..3].parse().unwrap();
+        code.1 = digits.parse().unwrap();
+    }
+    if let Some(extra) = lines.next() {
+        return Err(ParseError::at(&input, extra, "only five door codes"));
     }
 
-    parsed
+    Ok(parsed)
 }
 
+/// The sum of the complexities of the codes with two robots on direction pads
 #[aoc(day21, part1)]
-fn part1(codes: &[([char; 4], u64); 5]) -> u64 {
-    const N_DIR_PAD_ROBOTS: usize = 2;
+pub fn part1(codes: &[([char; 4], u64); 5]) -> u64 {
+    part1_with(codes, params::current().day21)
+}
+
+pub fn part1_with(codes: &[([char; 4], u64); 5], params: Day21Params) -> u64 {
+    let n_dir_pad_robots = params.part1_robots;
     let mut path_len_cache = FxHashMap::default();
     let mut total_complexity = 0;
     for (code_seq, code_val) in codes {
-        let mut pad_state = PadState::new(N_DIR_PAD_ROBOTS);
+        let mut pad_state = PadState::new(n_dir_pad_robots);
         let mut code_len = 0;
         for &code in code_seq {
-            let target_state = PadState::from_char(code, N_DIR_PAD_ROBOTS);
+            let target_state = PadState::from_char(code, n_dir_pad_robots);
             let len =
                 len_shortest_path_between_states(pad_state, &target_state, &mut path_len_cache);
             code_len += len;
@@ -41,9 +76,14 @@ fn part1(codes: &[([char; 4], u64); 5]) -> u64 {
     total_complexity
 }
 
+/// The sum of the complexities of the codes with more robots on direction pads
 #[aoc(day21, part2)]
-fn part2(input: &[([char; 4], u64); 5]) -> u64 {
-    const N_DIR_PAD_ROBOTS: usize = 5;
+pub fn part2(input: &[([char; 4], u64); 5]) -> u64 {
+    part2_with(input, params::current().day21)
+}
+
+pub fn part2_with(input: &[([char; 4], u64); 5], params: Day21Params) -> u64 {
+    let n_dir_pad_robots = params.part2_robots;
 
     // 1) Find shortest path (not its length) between all immediately connected pairs
     // of a NumPad and its nearest DirPad, in terms of the button presses required from the direction
@@ -56,37 +96,14 @@ fn part2(input: &[([char; 4], u64); 5]) -> u64 {
         // TODO: Optimisation: since we visit every state every time, just store the results for the different `to`s
         // in a map, and then use that map to find the shortest path to the `to`s.
         for to in NumPad::all() {
-            let mut todo = VecDeque::new();
-            todo.push_back((
+            let shortest_path = first_shortest_presses(
                 PadState {
                     num: from,
                     dir: vec![DirPad::A; 3],
                 },
-                vec![],
-            ));
-            let mut visited = FxHashSet::default();
-            let mut candidates = Vec::new();
-            while let Some((state, path)) = todo.pop_front() {
-                if !visited.insert(state.clone()) {
-                    continue;
-                }
-                if state.num == to {
-                    candidates.push((state.clone(), path.clone()));
-                }
-                for direction in DirPad::all() {
-                    if let Some(next_state) = state.clone().press(direction) {
-                        let mut new_path = path.clone();
-                        new_path.push(direction);
-                        todo.push_back((next_state, new_path));
-                    }
-                }
-            }
-            let shortest_path = candidates
-                .iter()
-                .min_by_key(|(_, path)| path.len())
-                .unwrap()
-                .1
-                .clone();
+                |state| state.num == to,
+                PadState::press,
+            );
             let mut path_one_level_up = Vec::new();
             let mut state = PadState {
                 num: from,
@@ -136,31 +153,11 @@ fn part2(input: &[([char; 4], u64); 5]) -> u64 {
             .into_iter()
             .map(|d| PressableDirPad::from_dirpad(d, true))
         {
-            let mut todo = VecDeque::new();
-            todo.push_back((DirPadStack::with_lowest(from, 3), vec![]));
-            let mut visited = FxHashSet::default();
-            let mut candidates = Vec::new();
-            while let Some((state, path)) = todo.pop_front() {
-                if !visited.insert(state.clone()) {
-                    continue;
-                }
-                if state.lowest == to {
-                    candidates.push((state.clone(), path.clone()));
-                }
-                for direction in DirPad::all() {
-                    if let Some(next_state) = state.clone().press(direction) {
-                        let mut new_path = path.clone();
-                        new_path.push(direction);
-                        todo.push_back((next_state, new_path));
-                    }
-                }
-            }
-            let shortest_path = candidates
-                .iter()
-                .min_by_key(|(_, path)| path.len())
-                .unwrap()
-                .1
-                .clone();
+            let shortest_path = first_shortest_presses(
+                DirPadStack::with_lowest(from, 3),
+                |state| state.lowest == to,
+                DirPadStack::press,
+            );
             let mut path_one_level_up = Vec::new();
             let mut state = DirPadStack::with_lowest(from, 3);
             for dir in shortest_path.iter() {
@@ -197,19 +194,19 @@ fn part2(input: &[([char; 4], u64); 5]) -> u64 {
     let mut total_complexity = 0;
     for (code_seq, code_val) in input {
         let mut code_complexity = 0;
-        let mut pad_state = PadState::new(N_DIR_PAD_ROBOTS);
+        let mut pad_state = PadState::new(n_dir_pad_robots);
         for &code in code_seq {
-            let target_state = PadState::from_char(code, N_DIR_PAD_ROBOTS);
+            let target_state = PadState::from_char(code, n_dir_pad_robots);
             let mut path = shortest_paths_numpad[&(pad_state.num, target_state.num)].clone();
             let deep_len = length_of_path_n_robots_deep(
                 path.clone(),
-                N_DIR_PAD_ROBOTS,
+                n_dir_pad_robots,
                 &shortest_paths_dirpad,
             );
-            if N_DIR_PAD_ROBOTS < 6 {
-                for _ in 0..N_DIR_PAD_ROBOTS {
+            if n_dir_pad_robots < 6 {
+                for _ in 0..n_dir_pad_robots {
                     let new_path = iter::once(DirPad::A)
-                        .chain(path.into_iter())
+                        .chain(path)
                         .tuple_windows()
                         .flat_map(|(from_dir, to_dir)| {
                             shortest_paths_dirpad[&(from_dir, to_dir)].clone()
@@ -231,6 +228,42 @@ fn part2(input: &[([char; 4], u64); 5]) -> u64 {
     total_complexity
 }
 
+/// The button presses (on the highest direction pad) of the first shortest path found
+/// from `start` to a state satisfying `is_goal`, trying the directions in `DirPad::all()` order
+fn first_shortest_presses<S>(
+    start: S,
+    is_goal: impl FnMut(&S) -> bool,
+    press: impl Fn(S, DirPad) -> Option<S>,
+) -> Vec<DirPad>
+where
+    S: Clone + Eq + Hash,
+{
+    let result = search::bfs(
+        start,
+        |state| {
+            DirPad::all()
+                .into_iter()
+                .filter_map(|direction| press(state.clone(), direction))
+                .collect::<Vec<_>>()
+        },
+        is_goal,
+        Predecessors::First,
+    );
+    let goal = result.goals().first().expect("goal is unreachable");
+    result
+        .path_to(goal)
+        .unwrap()
+        .into_iter()
+        .tuple_windows()
+        .map(|(from, to)| {
+            DirPad::all()
+                .into_iter()
+                .find(|&direction| press(from.clone(), direction).as_ref() == Some(&to))
+                .unwrap()
+        })
+        .collect()
+}
+
 #[memoize(Ignore: shortest_paths_dirpad)]
 fn length_of_path_n_robots_deep(
     path: Vec<DirPad>,
@@ -674,6 +707,19 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse(EXAMPLE)), 126384);
+        assert_eq!(
+            part1_with(&parse(EXAMPLE).unwrap(), Day21Params::EXAMPLE),
+            126384
+        );
+    }
+
+    #[test]
+    fn parse_malformed_codes() {
+        let error = parse("029A\n980A\n179A\n456A\n").unwrap_err();
+        assert_eq!(error.expected, "five door codes");
+        let error = parse("029A\n980A\n17A\n456A\n379A\n").unwrap_err();
+        assert_eq!((error.line, error.column), (3, 1));
+        let error = parse("029A\n980A\n00é\n456A\n379A\n").unwrap_err();
+        assert_eq!((error.line, error.column), (3, 1));
     }
 }
@@ -1,79 +1,260 @@
+//! Day 3: Mull It Over
+
+use std::io::{self, BufRead, BufReader, Read};
+
 use aoc_runner_derive::{aoc, aoc_generator};
-use regex::{Regex, RegexSet};
-
-const MUL_PATTERN: &str = r"mul\((?<l>\d+),(?<r>\d+)\)";
-const DO_PATTERN: &str = r"do\(\)";
-const DONT_PATTERN: &str = r"don't\(\)";
-
-#[aoc_generator(day3, part1)]
-fn parse(input: &str) -> Vec<(u64, u64)> {
-    let re = Regex::new(MUL_PATTERN).expect("failed to compile regex");
-    re.captures_iter(input)
-        .map(|caps| {
-            caps.extract::<2>()
-                .1
-                .map(|substr| substr.parse::<u64>().expect("expected numbers"))
-        })
-        .map(|arr| (arr[0], arr[1]))
-        .collect()
-}
 
-#[aoc(day3, part1)]
-fn part1(input: &[(u64, u64)]) -> u64 {
-    input.iter().map(|(a, b)| a * b).sum()
-}
+use crate::parsing::ParseError;
 
-#[derive(Debug, PartialEq)]
-enum Instruction {
+/// An instruction of the corrupted program
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
     Mul(u64, u64),
     Do,
     Dont,
 }
 
-#[aoc_generator(day3, part2)]
-fn parse_pt2(input: &str) -> Vec<Instruction> {
-    let mul_regex = Regex::new(MUL_PATTERN).expect("failed to compile MUL_PATTERN");
-    let regex_set = RegexSet::new([MUL_PATTERN, DO_PATTERN, DONT_PATTERN])
-        .expect("patterns in set did not compile");
-    let union_pattern = format!("{MUL_PATTERN}|{DO_PATTERN}|{DONT_PATTERN}");
-    let union_regex = Regex::new(&union_pattern).expect("failed to compile the union pattern");
-    union_regex
-        .find_iter(input)
-        .map(|match_| {
-            let matched_pattern_idx = regex_set
-                .matches(&input[match_.range()])
-                .iter()
-                .next()
-                .expect("expected one match, found none");
-            match matched_pattern_idx {
-                0 => {
-                    let captures = mul_regex
-                        .captures(&input[match_.range()])
-                        .expect("match should also capture");
-                    let l = captures
-                        .name("l")
-                        .expect("MUL_PATTERN captured without 'l'")
-                        .as_str()
-                        .parse::<u64>()
-                        .expect("expected a number for 'l'");
-                    let r = captures
-                        .name("r")
-                        .expect("MUL_PATTERN captured without 'r'")
-                        .as_str()
-                        .parse::<u64>()
-                        .expect("expected a number for 'r'");
-                    Instruction::Mul(l, r)
+/// The instructions of the corrupted program, in order
+pub type Input = Vec<Instruction>;
+
+/// An instruction, and where in the corrupted memory it was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token {
+    pub instruction: Instruction,
+    /// The byte offset of the first byte of the instruction
+    pub offset: usize,
+    /// The length of the instruction in bytes
+    pub len: usize,
+}
+
+impl Token {
+    /// The byte offset just past the instruction
+    pub fn end(&self) -> usize {
+        self.offset + self.len
+    }
+}
+
+/// How much of an instruction the lexer has seen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LexState {
+    Idle,
+    /// Within `mul(`, with this many bytes of it matched
+    Mul(usize),
+    Left {
+        value: u64,
+        digits: u32,
+    },
+    Right {
+        left: u64,
+        value: u64,
+        digits: u32,
+    },
+    /// Within `don't()`, with this many bytes of it matched
+    Dont(usize),
+    /// `do(`, which is not on the way to `don't()`
+    DoCall,
+}
+
+const MUL: &[u8] = b"mul(";
+const DONT: &[u8] = b"don't()";
+const MAX_DIGITS: u32 = 3;
+
+/// Picks the `mul(a,b)`, `do()` and `don't()` instructions out of corrupted memory, byte by byte
+///
+/// The operands of `mul` have 1 to 3 digits, anything else is corrupted.
+/// The bytes can come in chunks of any size, so the memory never has to be in one piece.
+#[derive(Debug, Clone)]
+pub struct Lexer {
+    state: LexState,
+    /// The offset of the first byte of the instruction in progress
+    start: usize,
+    /// The offset of the next byte
+    offset: usize,
+}
+
+impl Lexer {
+    pub fn new() -> Self {
+        Self {
+            state: LexState::Idle,
+            start: 0,
+            offset: 0,
+        }
+    }
+
+    /// Takes the next byte, returning the instruction it completes, if any
+    pub fn push(&mut self, byte: u8) -> Option<Token> {
+        let offset = self.offset;
+        self.offset += 1;
+        let (state, instruction) = match self.advance(byte) {
+            Some(advanced) => advanced,
+            // no instruction has an `m` or a `d` after its first byte,
+            // so a broken one can only restart with the byte that broke it
+            None => {
+                self.start = offset;
+                match byte {
+                    b'm' => (LexState::Mul(1), None),
+                    b'd' => (LexState::Dont(1), None),
+                    _ => (LexState::Idle, None),
                 }
-                1 => Instruction::Do,
-                2 => Instruction::Dont,
-                3.. => panic!("unknown pattern matched, with index {matched_pattern_idx}"),
             }
+        };
+        self.state = state;
+        instruction.map(|instruction| Token {
+            instruction,
+            offset: self.start,
+            len: self.offset - self.start,
         })
-        .collect()
+    }
+
+    /// Takes the next chunk of bytes, returning the instructions it completes
+    pub fn feed<'a>(&'a mut self, chunk: &'a [u8]) -> impl Iterator<Item = Token> + 'a {
+        chunk.iter().filter_map(|&byte| self.push(byte))
+    }
+
+    /// The state after `byte`, and the instruction it completes, if it continues one at all
+    fn advance(&self, byte: u8) -> Option<(LexState, Option<Instruction>)> {
+        let digit = |value: u64| value * 10 + u64::from(byte - b'0');
+        let advanced = match (self.state, byte) {
+            (LexState::Mul(matched), _) if byte == MUL[matched] => match matched + 1 {
+                4 => (
+                    LexState::Left {
+                        value: 0,
+                        digits: 0,
+                    },
+                    None,
+                ),
+                matched => (LexState::Mul(matched), None),
+            },
+            (LexState::Left { value, digits }, b'0'..=b'9') if digits < MAX_DIGITS => (
+                LexState::Left {
+                    value: digit(value),
+                    digits: digits + 1,
+                },
+                None,
+            ),
+            (LexState::Left { value, digits }, b',') if digits > 0 => (
+                LexState::Right {
+                    left: value,
+                    value: 0,
+                    digits: 0,
+                },
+                None,
+            ),
+            (
+                LexState::Right {
+                    left,
+                    value,
+                    digits,
+                },
+                b'0'..=b'9',
+            ) if digits < MAX_DIGITS => (
+                LexState::Right {
+                    left,
+                    value: digit(value),
+                    digits: digits + 1,
+                },
+                None,
+            ),
+            (
+                LexState::Right {
+                    left,
+                    value,
+                    digits,
+                },
+                b')',
+            ) if digits > 0 => (LexState::Idle, Some(Instruction::Mul(left, value))),
+            (LexState::Dont(2), b'(') => (LexState::DoCall, None),
+            (LexState::DoCall, b')') => (LexState::Idle, Some(Instruction::Do)),
+            (LexState::Dont(matched), _) if byte == DONT[matched] => match matched + 1 {
+                7 => (LexState::Idle, Some(Instruction::Dont)),
+                matched => (LexState::Dont(matched), None),
+            },
+            _ => return None,
+        };
+        Some(advanced)
+    }
+}
+
+impl Default for Lexer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// All instructions in `memory`, in order
+pub fn lex(memory: &[u8]) -> Vec<Token> {
+    Lexer::new().feed(memory).collect()
+}
+
+/// The instructions read from `reader`, lexed a buffer at a time as they are needed
+pub fn lex_reader<R: Read>(reader: R) -> ReaderTokens<R> {
+    ReaderTokens {
+        reader: BufReader::new(reader),
+        lexer: Lexer::new(),
+    }
+}
+
+/// See [`lex_reader`]
+#[derive(Debug)]
+pub struct ReaderTokens<R> {
+    reader: BufReader<R>,
+    lexer: Lexer,
+}
+
+impl<R: Read> Iterator for ReaderTokens<R> {
+    type Item = io::Result<Token>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let buffer = match self.reader.fill_buf() {
+                Ok([]) => return None,
+                Ok(buffer) => buffer,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => return Some(Err(error)),
+            };
+            let mut consumed = 0;
+            let mut token = None;
+            for &byte in buffer {
+                consumed += 1;
+                token = self.lexer.push(byte);
+                if token.is_some() {
+                    break;
+                }
+            }
+            self.reader.consume(consumed);
+            if let Some(token) = token {
+                return Some(Ok(token));
+            }
+        }
+    }
+}
+
+/// Picks the `mul(a,b)`, `do()` and `don't()` instructions out of the corrupted memory
+///
+/// Never fails: whatever is not an instruction is corrupted memory.
+#[aoc_generator(day3)]
+pub fn parse(input: &str) -> Result<Input, ParseError> {
+    Ok(lex(input.as_bytes())
+        .into_iter()
+        .map(|token| token.instruction)
+        .collect())
 }
 
+/// The sum of the products of all `mul` instructions
+#[aoc(day3, part1)]
+pub fn part1(input: &[Instruction]) -> u64 {
+    input
+        .iter()
+        .map(|instruction| match instruction {
+            Instruction::Mul(l, r) => l * r,
+            Instruction::Do | Instruction::Dont => 0,
+        })
+        .sum()
+}
+
+/// The sum of the products of the `mul` instructions enabled by `do()` and `don't()`
 #[aoc(day3, part2)]
-fn part2(input: &[Instruction]) -> u64 {
+pub fn part2(input: &[Instruction]) -> u64 {
     let mut enabled = true;
     let mut total = 0;
     for instruction in input {
@@ -86,6 +267,501 @@ fn part2(input: &[Instruction]) -> u64 {
     total
 }
 
+/// What an instruction does when it is called
+#[derive(Debug, Clone, Copy)]
+pub enum Semantics {
+    /// Computes a value from the arguments. A call standing alone adds its value to the total
+    /// (if enabled), and a call can be the argument of another call.
+    Value(fn(&[i64]) -> i64),
+    /// Changes the state, enabled or not. Such calls only ever stand alone.
+    Effect(fn(&mut State, &[i64])),
+}
+
+/// An instruction the corrupted memory may call, like `mul(a,b)`
+#[derive(Debug, Clone)]
+pub struct InstructionDef {
+    pub name: String,
+    pub arity: usize,
+    pub semantics: Semantics,
+}
+
+/// The instructions to look for in the corrupted memory
+#[derive(Debug, Clone, Default)]
+pub struct InstructionSet {
+    defs: Vec<InstructionDef>,
+    nesting: bool,
+}
+
+/// An argument of a call: a number of 1 to 3 digits, or (with nesting) a call of a value instruction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Arg {
+    Number(i64),
+    Call(Call),
+}
+
+/// A call found in the corrupted memory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Call {
+    pub name: String,
+    pub args: Vec<Arg>,
+    /// The byte offset of the first byte of the call
+    pub offset: usize,
+    /// The length of the call in bytes, including any nested calls
+    pub len: usize,
+}
+
+impl Call {
+    /// The byte offset just past the call
+    pub fn end(&self) -> usize {
+        self.offset + self.len
+    }
+}
+
+impl InstructionSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `mul(a,b)`, `do()` and `don't()`, as in the puzzle (so without nesting)
+    pub fn puzzle() -> Self {
+        let mut set = Self::new();
+        set.declare(
+            "mul",
+            2,
+            Semantics::Value(|args| args[0].wrapping_mul(args[1])),
+        )
+        .declare("do", 0, Semantics::Effect(|state, _| state.enabled = true))
+        .declare(
+            "don't",
+            0,
+            Semantics::Effect(|state, _| state.enabled = false),
+        );
+        set
+    }
+
+    /// The puzzle's instructions, plus `add(a,b)`, `sub(a,b)` and `reset()` (of the total),
+    /// with nesting
+    pub fn extended() -> Self {
+        let mut set = Self::puzzle();
+        set.allow_nesting(true)
+            .declare(
+                "add",
+                2,
+                Semantics::Value(|args| args[0].wrapping_add(args[1])),
+            )
+            .declare(
+                "sub",
+                2,
+                Semantics::Value(|args| args[0].wrapping_sub(args[1])),
+            )
+            .declare("reset", 0, Semantics::Effect(|state, _| state.total = 0));
+        set
+    }
+
+    /// Declares an instruction, replacing any other of the same name
+    pub fn declare(
+        &mut self,
+        name: impl Into<String>,
+        arity: usize,
+        semantics: Semantics,
+    ) -> &mut Self {
+        let def = InstructionDef {
+            name: name.into(),
+            arity,
+            semantics,
+        };
+        assert!(!def.name.is_empty(), "an instruction without a name");
+        self.defs.retain(|other| other.name != def.name);
+        self.defs.push(def);
+        // so that e.g. `don't` is tried before `do`
+        self.defs
+            .sort_by_key(|def| std::cmp::Reverse(def.name.len()));
+        self
+    }
+
+    /// Whether calls of value instructions may be arguments, like in `mul(mul(1,2),3)`
+    ///
+    /// Without nesting, that is a corrupted `mul(` followed by a `mul(1,2)`.
+    pub fn allow_nesting(&mut self, nesting: bool) -> &mut Self {
+        self.nesting = nesting;
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&InstructionDef> {
+        self.defs.iter().find(|def| def.name == name)
+    }
+
+    /// All calls standing alone in `memory`, in order, leftmost first
+    pub fn parse(&self, memory: &str) -> Vec<Call> {
+        let bytes = memory.as_bytes();
+        let mut parser = Parser {
+            set: self,
+            bytes,
+            corrupted: vec![false; bytes.len()],
+        };
+        let mut calls = Vec::new();
+        let mut offset = 0;
+        while offset < bytes.len() {
+            match parser.parse_call(offset, 0) {
+                Ok(call) => {
+                    offset = call.end();
+                    calls.push(call);
+                }
+                Err(_) => offset += 1,
+            }
+        }
+        calls
+    }
+}
+
+/// How deeply calls may nest as arguments; a call nested deeper counts as corrupted
+pub const MAX_NESTING: usize = 64;
+
+/// Why there is no call at some offset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Failure {
+    Corrupted,
+    /// It would nest deeper than [`MAX_NESTING`], though a shallower call there might not
+    TooDeep,
+}
+
+/// The state of [`InstructionSet::parse`]
+struct Parser<'a> {
+    set: &'a InstructionSet,
+    bytes: &'a [u8],
+    /// The offsets known to hold no call of a value instruction, at any depth, so that
+    /// a long run of unclosed calls is only parsed once as arguments
+    corrupted: Vec<bool>,
+}
+
+impl Parser<'_> {
+    /// The call starting at `offset`, nested as the argument of `depth` others,
+    /// which must have a value if it is nested at all
+    fn parse_call(&mut self, offset: usize, depth: usize) -> Result<Call, Failure> {
+        if depth > MAX_NESTING {
+            return Err(Failure::TooDeep);
+        }
+        let set = self.set;
+        let mut failure = Failure::Corrupted;
+        for def in &set.defs {
+            let is_value = matches!(def.semantics, Semantics::Value(_));
+            if !self.bytes[offset..].starts_with(def.name.as_bytes())
+                || (is_value && self.corrupted[offset])
+                || (depth > 0 && !is_value)
+            {
+                continue;
+            }
+            match self.parse_args(def, offset, depth) {
+                Ok(call) => return Ok(call),
+                Err(Failure::TooDeep) => failure = Failure::TooDeep,
+                Err(Failure::Corrupted) => {}
+            }
+        }
+        if depth > 0 && failure == Failure::Corrupted {
+            self.corrupted[offset] = true;
+        }
+        Err(failure)
+    }
+
+    /// The call of `def` starting at `offset`, with its name already matched
+    fn parse_args(
+        &mut self,
+        def: &InstructionDef,
+        offset: usize,
+        depth: usize,
+    ) -> Result<Call, Failure> {
+        let bytes = self.bytes;
+        let expect = |at: usize, byte| {
+            if bytes.get(at) == Some(&byte) {
+                Ok(at + 1)
+            } else {
+                Err(Failure::Corrupted)
+            }
+        };
+        let mut at = expect(offset + def.name.len(), b'(')?;
+        let mut args = Vec::new();
+        for i in 0..def.arity {
+            if i > 0 {
+                at = expect(at, b',')?;
+            }
+            let (arg, len) = self.parse_arg(at, depth)?;
+            at += len;
+            args.push(arg);
+        }
+        let end = expect(at, b')')?;
+        Ok(Call {
+            name: def.name.clone(),
+            args,
+            offset,
+            len: end - offset,
+        })
+    }
+
+    /// The argument starting at `offset` of a call nested `depth` deep, and its length
+    fn parse_arg(&mut self, offset: usize, depth: usize) -> Result<(Arg, usize), Failure> {
+        let digits = self.bytes[offset..]
+            .iter()
+            .take_while(|byte| byte.is_ascii_digit())
+            .count();
+        match digits {
+            0 if self.set.nesting => {
+                let call = self.parse_call(offset, depth + 1)?;
+                let len = call.len;
+                Ok((Arg::Call(call), len))
+            }
+            1..=3 => {
+                let number = self.bytes[offset..offset + digits]
+                    .iter()
+                    .fold(0, |number, digit| number * 10 + i64::from(digit - b'0'));
+                Ok((Arg::Number(number), digits))
+            }
+            _ => Err(Failure::Corrupted),
+        }
+    }
+}
+
+/// What the interpreter keeps track of
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct State {
+    /// Whether calls of value instructions count towards the total
+    pub enabled: bool,
+    pub total: i64,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            total: 0,
+        }
+    }
+}
+
+/// Called after each call, with its value (for value instructions) and the state after it
+pub type Hook<'a> = Box<dyn FnMut(&Call, Option<i64>, &State) + 'a>;
+
+/// Runs the calls of an [`InstructionSet`]
+pub struct Interpreter<'a> {
+    set: &'a InstructionSet,
+    state: State,
+    hooks: Vec<Hook<'a>>,
+}
+
+impl<'a> Interpreter<'a> {
+    pub fn new(set: &'a InstructionSet) -> Self {
+        Self {
+            set,
+            state: State::default(),
+            hooks: Vec::new(),
+        }
+    }
+
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    /// Calls `hook` after every call this interpreter executes
+    pub fn on_step(&mut self, hook: impl FnMut(&Call, Option<i64>, &State) + 'a) -> &mut Self {
+        self.hooks.push(Box::new(hook));
+        self
+    }
+
+    /// Executes a call standing alone
+    ///
+    /// Panics if the call is not of an instruction of the set, with its arity.
+    pub fn execute(&mut self, call: &Call) {
+        let args = call
+            .args
+            .iter()
+            .map(|arg| self.evaluate(arg))
+            .collect::<Vec<_>>();
+        let value = match self.def(call).semantics {
+            Semantics::Value(compute) => {
+                let value = compute(&args);
+                if self.state.enabled {
+                    self.state.total = self.state.total.wrapping_add(value);
+                }
+                Some(value)
+            }
+            Semantics::Effect(apply) => {
+                apply(&mut self.state, &args);
+                None
+            }
+        };
+        for hook in &mut self.hooks {
+            hook(call, value, &self.state);
+        }
+    }
+
+    /// Executes all calls in `memory`, returning the total afterwards
+    pub fn run(&mut self, memory: &str) -> i64 {
+        for call in self.set.parse(memory) {
+            self.execute(&call);
+        }
+        self.state.total
+    }
+
+    fn def(&self, call: &Call) -> &'a InstructionDef {
+        let set = self.set;
+        let def = set
+            .get(&call.name)
+            .unwrap_or_else(|| panic!("no instruction '{}' in the set", call.name));
+        assert_eq!(
+            def.arity,
+            call.args.len(),
+            "a call of '{}' with the wrong arity",
+            call.name
+        );
+        def
+    }
+
+    fn evaluate(&self, arg: &Arg) -> i64 {
+        match arg {
+            Arg::Number(number) => *number,
+            Arg::Call(call) => {
+                let args = call
+                    .args
+                    .iter()
+                    .map(|arg| self.evaluate(arg))
+                    .collect::<Vec<_>>();
+                match self.def(call).semantics {
+                    Semantics::Value(compute) => compute(&args),
+                    Semantics::Effect(_) => {
+                        panic!("a call of '{}' nested in another call", call.name)
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A recognised instruction, and what it did to the total of part 2
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Annotation {
+    pub token: Token,
+    /// Whether `mul` was enabled by the `do()` and `don't()` before it (or itself, for those)
+    pub enabled: bool,
+    /// What it added to the total
+    pub contribution: u64,
+}
+
+/// The instructions in `memory`, with whether they were enabled and what they contributed
+pub fn annotate(memory: &str) -> Vec<Annotation> {
+    let mut enabled = true;
+    lex(memory.as_bytes())
+        .into_iter()
+        .map(|token| {
+            let contribution = match token.instruction {
+                Instruction::Do => {
+                    enabled = true;
+                    0
+                }
+                Instruction::Dont => {
+                    enabled = false;
+                    0
+                }
+                Instruction::Mul(l, r) => enabled as u64 * l * r,
+            };
+            Annotation {
+                token,
+                enabled,
+                contribution,
+            }
+        })
+        .collect()
+}
+
+/// `memory`, as it was, with the instructions in colour for a terminal
+///
+/// Enabled `mul`s are green and followed by their contribution, disabled ones are struck out,
+/// `do()` is cyan, `don't()` magenta, and the corrupted rest grey. The total follows on a line
+/// of its own.
+pub fn render_ansi(memory: &str) -> String {
+    const RESET: &str = "\x1b[0m";
+    render(
+        memory,
+        |corrupted| format!("\x1b[90m{corrupted}{RESET}"),
+        |annotation, text| match annotation.token.instruction {
+            Instruction::Mul(..) if annotation.enabled => format!(
+                "\x1b[1;32m{text}{RESET}\x1b[2;32m⟨+{}⟩{RESET}",
+                annotation.contribution
+            ),
+            Instruction::Mul(..) => format!("\x1b[2;9m{text}{RESET}"),
+            Instruction::Do => format!("\x1b[36m{text}{RESET}"),
+            Instruction::Dont => format!("\x1b[35m{text}{RESET}"),
+        },
+        |total| format!("\ntotal: {total}\n"),
+    )
+}
+
+/// `memory`, as it was, in a `<pre>` with a `<span>` around each instruction
+///
+/// The spans have the classes `mul`, `do` or `dont`, plus `enabled` or `disabled`,
+/// and the contribution as their title, for a style sheet to pick up.
+pub fn render_html(memory: &str) -> String {
+    let mut html = String::from("<pre class=\"day3\">");
+    html.push_str(&render(
+        memory,
+        escape_html,
+        |annotation, text| {
+            let class = match annotation.token.instruction {
+                Instruction::Mul(..) => "mul",
+                Instruction::Do => "do",
+                Instruction::Dont => "dont",
+            };
+            let state = if annotation.enabled {
+                "enabled"
+            } else {
+                "disabled"
+            };
+            format!(
+                "<span class=\"{class} {state}\" title=\"+{}\">{}</span>",
+                annotation.contribution,
+                escape_html(text)
+            )
+        },
+        |total| format!("</pre>\n<p class=\"day3-total\">total: {total}</p>\n"),
+    ));
+    html
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// `memory`, with the corrupted parts and the instructions marked up, followed by the total
+fn render(
+    memory: &str,
+    corrupted: impl Fn(&str) -> String,
+    instruction: impl Fn(&Annotation, &str) -> String,
+    total: impl Fn(u64) -> String,
+) -> String {
+    let mut rendered = String::with_capacity(memory.len() * 2);
+    let mut offset = 0;
+    let mut sum = 0;
+    let push_corrupted = |rendered: &mut String, text: &str| {
+        if !text.is_empty() {
+            rendered.push_str(&corrupted(text));
+        }
+    };
+    for annotation in annotate(memory) {
+        let token = annotation.token;
+        push_corrupted(&mut rendered, &memory[offset..token.offset]);
+        rendered.push_str(&instruction(
+            &annotation,
+            &memory[token.offset..token.end()],
+        ));
+        offset = token.end();
+        sum += annotation.contribution;
+    }
+    push_corrupted(&mut rendered, &memory[offset..]);
+    rendered.push_str(&total(sum));
+    rendered
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -98,32 +774,37 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse(fixture_input_example())), 161);
+        assert_eq!(part1(&parse(fixture_input_example()).unwrap()), 161);
     }
 
     #[test]
     fn part1_parse_example() {
         assert_eq!(
-            parse(fixture_input_example()),
-            [(2, 4), (5, 5), (11, 8), (8, 5)]
+            parse(fixture_input_example()).unwrap(),
+            [
+                Instruction::Mul(2, 4),
+                Instruction::Mul(5, 5),
+                Instruction::Mul(11, 8),
+                Instruction::Mul(8, 5),
+            ]
         );
     }
 
     fn part2_fixture_input_example() -> &'static str {
-        const INPUT: &'static str =
+        const INPUT: &str =
             "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
         INPUT
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse_pt2(part2_fixture_input_example())), 48);
+        assert_eq!(part2(&parse(part2_fixture_input_example()).unwrap()), 48);
     }
 
     #[test]
     fn part2_parse_example() {
         assert_eq!(
-            parse_pt2(part2_fixture_input_example()),
+            parse(part2_fixture_input_example()).unwrap(),
             [
                 Instruction::Mul(2, 4),
                 Instruction::Dont,
@@ -137,11 +818,254 @@ mod tests {
 
     #[test]
     fn part2_parse_just_do() {
-        assert_eq!(parse_pt2("do()"), [Instruction::Do])
+        assert_eq!(parse("do()").unwrap(), [Instruction::Do])
     }
 
     #[test]
     fn part2_parse_just_dont() {
-        assert_eq!(parse_pt2("don't()"), [Instruction::Dont])
+        assert_eq!(parse("don't()").unwrap(), [Instruction::Dont])
+    }
+
+    #[test]
+    fn operands_have_one_to_three_digits() {
+        let input = "mul(2,4)mul(99999999999999999999,1)mul(1234,5)mul(123,456)mul(,1)mul(007,7)";
+        assert_eq!(
+            parse(input).unwrap(),
+            [
+                Instruction::Mul(2, 4),
+                Instruction::Mul(123, 456),
+                Instruction::Mul(7, 7)
+            ]
+        );
+    }
+
+    #[test]
+    fn lex_offsets() {
+        let input = part2_fixture_input_example();
+        let tokens = lex(input.as_bytes());
+        assert_eq!(tokens.len(), 6);
+        assert_eq!(
+            tokens[1],
+            Token {
+                instruction: Instruction::Dont,
+                offset: 20,
+                len: 7
+            }
+        );
+        for token in tokens {
+            let text = &input[token.offset..token.end()];
+            assert!(text.ends_with(')'));
+            assert_eq!(parse(text).unwrap(), [token.instruction]);
+        }
+    }
+
+    #[test]
+    fn lex_restarts_within_broken_instructions() {
+        assert_eq!(
+            parse("mumul(1,2)mul(3,mul(4,5)dodon't()do(do()don'tdo()").unwrap(),
+            [
+                Instruction::Mul(1, 2),
+                Instruction::Mul(4, 5),
+                Instruction::Dont,
+                Instruction::Do,
+                Instruction::Do,
+            ]
+        );
+        let tokens = lex(b"mumul(1,2)");
+        assert_eq!((tokens[0].offset, tokens[0].len), (2, 8));
+    }
+
+    /// Hands out the bytes in chunks of `chunk_len`
+    struct Chunked<'a> {
+        bytes: &'a [u8],
+        chunk_len: usize,
+    }
+
+    impl Read for Chunked<'_> {
+        fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+            let len = self.chunk_len.min(buffer.len()).min(self.bytes.len());
+            buffer[..len].copy_from_slice(&self.bytes[..len]);
+            self.bytes = &self.bytes[len..];
+            Ok(len)
+        }
+    }
+
+    #[test]
+    fn lex_chunked_reader() {
+        let input = part2_fixture_input_example().as_bytes();
+        let expected = lex(input);
+        for chunk_len in 1..=input.len() {
+            let tokens = lex_reader(Chunked {
+                bytes: input,
+                chunk_len,
+            })
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+            assert_eq!(tokens, expected, "in chunks of {chunk_len}");
+        }
+
+        let mut lexer = Lexer::new();
+        let (head, tail) = input.split_at(25);
+        let mut tokens = lexer.feed(head).collect::<Vec<_>>();
+        tokens.extend(lexer.feed(tail));
+        assert_eq!(tokens, expected);
+    }
+
+    #[test]
+    fn interpreter_runs_the_puzzle() {
+        let set = InstructionSet::puzzle();
+        assert_eq!(Interpreter::new(&set).run(fixture_input_example()), 161);
+        assert_eq!(
+            Interpreter::new(&set).run(part2_fixture_input_example()),
+            48
+        );
+        for seed in 0..5 {
+            let memory =
+                crate::inputs::generate_with(3, seed, 50, crate::params::Params::EXAMPLE).unwrap();
+            assert_eq!(
+                Interpreter::new(&set).run(&memory),
+                part2(&parse(&memory).unwrap()) as i64
+            );
+        }
+    }
+
+    #[test]
+    fn interpreter_nested_and_extended() {
+        let set = InstructionSet::extended();
+        let calls = set.parse("xadd(mul(2,3),4)]sub(1,2)mul(do(),1)reset(");
+        assert_eq!(calls.len(), 3);
+        assert_eq!((calls[0].offset, calls[0].len), (1, 15));
+        assert_eq!(
+            calls[0].args,
+            [
+                Arg::Call(Call {
+                    name: "mul".to_string(),
+                    args: vec![Arg::Number(2), Arg::Number(3)],
+                    offset: 5,
+                    len: 8,
+                }),
+                Arg::Number(4)
+            ]
+        );
+        // a nested do() is not a value, so that mul is corrupted, but the do() is not
+        assert_eq!(calls[2].name, "do");
+
+        let memory = "add(mul(2,3),4)sub(1,2)reset()mul(mul(1,2),mul(3,4))don't()add(1,1)";
+        assert_eq!(Interpreter::new(&set).run(memory), 24);
+        // without add, its mul stands alone, and without nesting, so do the innermost muls
+        assert_eq!(
+            Interpreter::new(&InstructionSet::puzzle()).run(memory),
+            6 + 2 + 12
+        );
+    }
+
+    #[test]
+    fn parse_deeply_nested_calls() {
+        let set = InstructionSet::extended();
+        // unclosed calls are parsed once as arguments, not again from each of their offsets
+        let memory = format!("{}mul(2,3)", "mul(".repeat(100_000));
+        let calls = set.parse(&memory);
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].offset, 400_000);
+        // a call inside a corrupted one still stands alone
+        let calls = set.parse("mul(add(1,2)x");
+        assert_eq!((calls.len(), calls[0].offset), (1, 4));
+
+        let nested = |depth| format!("{}1{}", "neg(".repeat(depth + 1), ")".repeat(depth + 1));
+        let mut set = InstructionSet::extended();
+        set.declare("neg", 1, Semantics::Value(|args| -args[0]));
+        assert_eq!(set.parse(&nested(MAX_NESTING))[0].offset, 0);
+        assert_eq!(set.parse(&nested(MAX_NESTING + 1))[0].offset, 4);
+    }
+
+    #[test]
+    fn interpreter_custom_instructions_and_hooks() {
+        let mut set = InstructionSet::puzzle();
+        set.allow_nesting(true)
+            .declare("neg", 1, Semantics::Value(|args| -args[0]))
+            .declare(
+                "toggle",
+                0,
+                Semantics::Effect(|state, _| state.enabled ^= true),
+            )
+            .declare(
+                "mul",
+                3,
+                Semantics::Value(|args| args[0] * args[1] * args[2]),
+            );
+        assert_eq!(set.get("mul").unwrap().arity, 3);
+
+        let mut steps = Vec::new();
+        let total = {
+            let mut interpreter = Interpreter::new(&set);
+            interpreter.on_step(|call, value, state| {
+                steps.push((call.name.clone(), value, state.enabled))
+            });
+            interpreter.run("neg(mul(1,2,3))mul(2,4)toggle()neg(5)toggle()mul(1,1,1)")
+        };
+        assert_eq!(total, -5);
+        assert_eq!(
+            steps,
+            [
+                ("neg".to_string(), Some(-6), true),
+                ("toggle".to_string(), None, false),
+                ("neg".to_string(), Some(-5), false),
+                ("toggle".to_string(), None, true),
+                ("mul".to_string(), Some(1), true),
+            ]
+        );
+    }
+
+    #[test]
+    fn annotate_example() {
+        let memory = part2_fixture_input_example();
+        let annotations = annotate(memory);
+        assert_eq!(
+            annotations
+                .iter()
+                .map(|annotation| (annotation.enabled, annotation.contribution))
+                .collect::<Vec<_>>(),
+            [
+                (true, 8),
+                (false, 0),
+                (false, 0),
+                (false, 0),
+                (true, 0),
+                (true, 40)
+            ]
+        );
+        assert_eq!(
+            annotations.iter().map(|a| a.contribution).sum::<u64>(),
+            part2(&parse(memory).unwrap())
+        );
+    }
+
+    #[test]
+    fn render_example() {
+        let memory = "x<mul(2,4)don't()mul(5,5)";
+        assert_eq!(
+            render_ansi(memory),
+            "\x1b[90mx<\x1b[0m\x1b[1;32mmul(2,4)\x1b[0m\x1b[2;32m⟨+8⟩\x1b[0m\
+             \x1b[35mdon't()\x1b[0m\x1b[2;9mmul(5,5)\x1b[0m\ntotal: 8\n"
+        );
+        assert_eq!(
+            render_html(memory),
+            "<pre class=\"day3\">x&lt;<span class=\"mul enabled\" title=\"+8\">mul(2,4)</span>\
+             <span class=\"dont disabled\" title=\"+0\">don't()</span>\
+             <span class=\"mul disabled\" title=\"+0\">mul(5,5)</span></pre>\n\
+             <p class=\"day3-total\">total: 8</p>\n"
+        );
+
+        // without the markup, the memory is all there
+        let mut plain = render_ansi(part2_fixture_input_example());
+        while let Some(start) = plain.find('\x1b') {
+            let end = start + plain[start..].find('m').unwrap();
+            plain.replace_range(start..=end, "");
+        }
+        let plain = plain.replace("⟨+8⟩", "").replace("⟨+40⟩", "");
+        assert_eq!(
+            plain,
+            format!("{}\ntotal: 48\n", part2_fixture_input_example())
+        );
     }
 }
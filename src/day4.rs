@@ -1,195 +1,334 @@
+//! Day 4: Ceres Search
+
 use aoc_runner_derive::{aoc, aoc_generator};
-#[aoc_generator(day4, part1)]
-fn parse(input: &str) -> String {
-    input.trim().to_string()
+
+use crate::{
+    grid::{Diagonal, Grid, Point, Vector},
+    parsing::ParseError,
+};
+
+/// The word search as a matrix of characters, `(x, y)` = `(column, row)`
+pub type CharMat = Grid<char>;
+
+/// The word search
+pub type Input = CharMat;
+
+/// Parses the word search, a rectangle of characters
+#[aoc_generator(day4)]
+pub fn parse(input: &str) -> Result<Input, ParseError> {
+    Grid::from_char_map(input.trim(), "any character", |_, c| Some(c))
 }
 
-const XMAS: &str = "XMAS";
-const SAMX: &str = "SAMX";
+/// The eight directions a word can run in, clockwise from north
+pub const DIRECTIONS: [Vector; 8] = [
+    Vector::new(0, -1),
+    Vector::new(1, -1),
+    Vector::new(1, 0),
+    Vector::new(1, 1),
+    Vector::new(0, 1),
+    Vector::new(-1, 1),
+    Vector::new(-1, 0),
+    Vector::new(-1, -1),
+];
 
-fn count_non_whitespace(s: &str) -> usize {
-    s.chars().filter(|c| !c.is_whitespace()).count()
+/// Finds words in a word search, in any of the eight directions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordSearch {
+    words: Vec<Vec<char>>,
+    wrap_around: bool,
 }
 
-#[aoc(day4, part1)]
-fn part1(input: &str) -> u64 {
-    let mut occurances = input.matches(XMAS).count() as u64;
-    occurances += input.matches(SAMX).count() as u64;
-
-    let lines: Vec<&str> = input.lines().collect();
-    let n_cols = lines[0].len();
-    let n_rows = lines.len();
-
-    let mut transposed = String::with_capacity(input.len());
-    for col in 0..n_cols {
-        for &line_ in lines.iter() {
-            transposed.push_str(&line_[col..col + 1]);
-        }
-        if col < n_cols - 1 {
-            transposed.push('\n');
+/// A word found in the word search
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Hit {
+    /// The index of the word in the list searched for
+    pub word: usize,
+    /// The cell of the first letter
+    pub start: Point,
+    /// The direction from each letter to the next
+    pub direction: Vector,
+}
+
+impl WordSearch {
+    pub fn new<S: AsRef<str>>(words: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            words: words
+                .into_iter()
+                .map(|word| word.as_ref().chars().collect())
+                .collect(),
+            wrap_around: false,
         }
     }
-    assert_eq!(
-        count_non_whitespace(&transposed),
-        count_non_whitespace(input)
-    );
-    let transposed = transposed;
-    occurances += transposed.matches(XMAS).count() as u64;
-    occurances += transposed.matches(SAMX).count() as u64;
-
-    let mut diagonals = String::with_capacity(input.len() + n_cols);
-    for offset in (0..n_rows).rev() {
-        for i in 0..n_cols {
-            let row_idx = i + offset;
-            if row_idx >= n_rows {
-                break;
-            }
-            diagonals.push_str(&lines[row_idx][i..i + 1]);
+
+    /// Lets words run off one edge and continue on the opposite one
+    pub fn with_wrap_around(self, wrap_around: bool) -> Self {
+        Self {
+            wrap_around,
+            ..self
         }
-        diagonals.push('\n');
     }
-    for offset in 1..n_cols {
-        for (i, &line) in lines.iter().enumerate() {
-            let col_idx = i + offset;
-            if col_idx >= n_cols || i > n_rows - 1 {
-                break;
+
+    /// Every occurrence of every word, by start cell (row-major), word and direction
+    ///
+    /// A word that reads the same from both ends is found twice, once each way,
+    /// and a word of one letter eight times.
+    pub fn find(&self, grid: &CharMat) -> Vec<Hit> {
+        let mut hits = Vec::new();
+        for (start, &letter) in grid.enumerate() {
+            for (word, letters) in self.words.iter().enumerate() {
+                if letters.first() != Some(&letter) {
+                    continue;
+                }
+                for direction in DIRECTIONS {
+                    let hit = Hit {
+                        word,
+                        start,
+                        direction,
+                    };
+                    let mut cells = self.cells(grid, &hit);
+                    if letters
+                        .iter()
+                        .all(|letter| cells.next().is_some_and(|cell| grid[cell] == *letter))
+                    {
+                        hits.push(hit);
+                    }
+                }
             }
-            diagonals.push_str(&line[col_idx..col_idx + 1]);
-        }
-        if offset < n_cols - 1 {
-            diagonals.push('\n');
         }
+        hits
+    }
+
+    /// The number of occurrences of all words
+    pub fn count(&self, grid: &CharMat) -> usize {
+        self.find(grid).len()
     }
-    assert_eq!(
-        count_non_whitespace(&diagonals),
-        count_non_whitespace(input)
-    );
-    let diagonals = diagonals;
-    occurances += diagonals.matches(XMAS).count() as u64;
-    occurances += diagonals.matches(SAMX).count() as u64;
-
-    let mut orthogonals = String::with_capacity(input.len() + n_cols);
-    for offset in (0..n_rows).rev() {
-        for i in 0..n_cols {
-            let row_idx = i + offset;
-            if row_idx >= n_rows {
-                break;
+
+    /// The cells of the letters of `hit`
+    pub fn hit_cells<'a>(
+        &'a self,
+        grid: &'a CharMat,
+        hit: &Hit,
+    ) -> impl Iterator<Item = Point> + 'a {
+        self.cells(grid, hit).take(self.words[hit.word].len())
+    }
+
+    /// `grid` with every letter that is not part of a hit replaced by `.`
+    pub fn highlight(&self, grid: &CharMat, hits: &[Hit]) -> CharMat {
+        let mut highlighted = grid.map(|_| '.');
+        for hit in hits {
+            for cell in self.hit_cells(grid, hit) {
+                highlighted[cell] = grid[cell];
             }
-            let rev_i = n_cols - 1 - i;
-            orthogonals.push_str(&lines[row_idx][rev_i..rev_i + 1]);
         }
-        orthogonals.push('\n');
+        highlighted
     }
-    for offset in 1..n_cols {
-        for (i, &line) in lines.iter().enumerate() {
-            let col_idx = i + offset;
-            if col_idx >= n_cols || i > n_rows - 1 {
-                break;
+
+    /// The cells from the start of `hit` on in its direction, as far as the grid allows
+    fn cells<'a>(&self, grid: &'a CharMat, hit: &Hit) -> impl Iterator<Item = Point> + 'a {
+        let (wrap_around, direction) = (self.wrap_around, hit.direction);
+        std::iter::successors(Some(hit.start), move |&cell| {
+            if wrap_around {
+                Some(grid.step_wrapping(cell, direction))
+            } else {
+                grid.step(cell, direction)
             }
-            let rev_col_idx = n_cols - 1 - col_idx;
-            orthogonals.push_str(&line[rev_col_idx..rev_col_idx + 1]);
-        }
-        if offset < n_cols - 1 {
-            orthogonals.push('\n');
-        }
+        })
     }
-    assert_eq!(
-        count_non_whitespace(&orthogonals),
-        count_non_whitespace(input)
-    );
-    let orthogonals = orthogonals;
-    occurances += orthogonals.matches(XMAS).count() as u64;
-    occurances += orthogonals.matches(SAMX).count() as u64;
-
-    occurances
 }
 
-struct CharMat {
-    n_rows: usize,
-    n_cols: usize,
-    buffer: String,
+/// The number of times XMAS appears in the word search, in any of the eight directions
+#[aoc(day4, part1)]
+pub fn part1(input: &CharMat) -> u64 {
+    WordSearch::new(["XMAS"]).count(input) as u64
 }
 
-impl CharMat {
-    fn from_str(source: &str) -> CharMat {
-        let mut lines = source.lines();
-        let line_0 = lines.next().expect("got empty source");
-        let n_cols = line_0.trim().len(); // assumes ASCII, assumes no ws inside lines
-        let n_rows = 1 + lines.count();
-        let res = CharMat {
-            n_rows,
-            n_cols,
-            buffer: source.split_ascii_whitespace().collect(),
-        };
-        assert_eq!(res.n_rows * res.n_cols, res.buffer.len());
-        res
-    }
+/// A small pattern of letters to look for in the word search, in any orientation
+///
+/// Written one row per line (or separated by `/`), with `.` matching any letter,
+/// like `M.S/.A./M.S` for an X-MAS. Hits are reported by where the anchor cell
+/// of the pattern (its centre, unless chosen otherwise) ends up. Orientations that only
+/// differ in where the anchor is are distinct, so a symmetric pattern is best anchored
+/// on its centre of symmetry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    /// The distinct rotations and reflections of the pattern, as given first
+    variants: Vec<Variant>,
+}
 
-    fn get(&self, row: usize, col: usize) -> &str {
-        let idx = self.flat_idx(col, row);
-        match idx {
-            // silly default to avoid standard option handling
-            None => "",
-            // assumes ASCII, silly default to avoid standard option handling
-            Some(idx) => self.buffer.get(idx..idx + 1).unwrap_or(""),
-        }
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Variant {
+    cells: Grid<Option<char>>,
+    anchor: Point,
+}
+
+/// A template found in the word search
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TemplateHit {
+    /// The cell the anchor of the template matched
+    pub anchor: Point,
+    /// The cell the top left corner of the (rotated or reflected) template matched
+    pub origin: Point,
+    /// Which rotation or reflection matched, see [`Template::variant`]
+    pub variant: usize,
+}
+
+impl Template {
+    pub const WILDCARD: char = '.';
+
+    /// Parses a pattern with its centre cell as the anchor (for an even size, the one after the middle)
+    pub fn parse(pattern: &str) -> Result<Self, ParseError> {
+        let width = pattern
+            .trim()
+            .split(['/', '\n'])
+            .next()
+            .unwrap_or("")
+            .chars()
+            .count();
+        let height = pattern.trim().split(['/', '\n']).count();
+        Self::parse_anchored(pattern, Point::new(width / 2, height / 2))
     }
 
-    fn flat_idx(&self, row: usize, col: usize) -> Option<usize> {
-        if col >= self.n_cols {
-            return None;
+    /// Parses a pattern, with the cell at `anchor` in it as the anchor
+    ///
+    /// Fails if the pattern is empty, or `anchor` is outside it.
+    pub fn parse_anchored(pattern: &str, anchor: Point) -> Result<Self, ParseError> {
+        if pattern.trim().is_empty() {
+            return Err(ParseError::at_end(pattern, "a letter or a wildcard"));
         }
-        if row >= self.n_rows {
-            return None;
+        let cells = Grid::from_char_map(
+            pattern.trim().replace('/', "\n").as_str(),
+            "a letter or a wildcard",
+            |_, c| Some((c != Self::WILDCARD).then_some(c)),
+        )?;
+        if !cells.contains(anchor) {
+            return Err(ParseError::at_end(
+                pattern,
+                format!("the anchor {anchor} inside the pattern"),
+            ));
         }
-        Some(col * self.n_rows + row)
+        let mut variant = Variant { cells, anchor };
+        let mut variants = Vec::with_capacity(8);
+        for _ in 0..2 {
+            for _ in 0..4 {
+                if !variants.contains(&variant) {
+                    variants.push(variant.clone());
+                }
+                variant = variant.rotated();
+            }
+            variant = variant.reflected();
+        }
+        Ok(Self { variants })
     }
 
-    fn deep_idx(&self, flat_idx: usize) -> (usize, usize) {
-        assert!(flat_idx < self.buffer.len());
-        let row = flat_idx / self.n_cols;
-        let col = flat_idx % self.n_cols;
-        debug_assert!(row < self.n_rows);
-        (row, col)
+    /// The number of distinct rotations and reflections
+    pub fn n_variants(&self) -> usize {
+        self.variants.len()
     }
 
-    // clean solution would return iterator with lifetime to avoid alloc
-    fn match_indices(&self, pat: &str) -> Vec<(usize, usize)> {
-        self.buffer
-            .match_indices(pat)
-            .map(|(match_idx, _)| self.deep_idx(match_idx))
-            .collect()
+    /// A rotation or reflection, with wildcards as `.`
+    pub fn variant(&self, index: usize) -> Grid<char> {
+        self.variants[index]
+            .cells
+            .map(|cell| cell.unwrap_or(Self::WILDCARD))
+    }
+
+    /// Every place where any variant matches, by anchor (row-major) and variant
+    pub fn find(&self, grid: &CharMat) -> Vec<TemplateHit> {
+        let mut hits = Vec::new();
+        for (index, variant) in self.variants.iter().enumerate() {
+            let (width, height) = (variant.cells.width(), variant.cells.height());
+            if width > grid.width() || height > grid.height() {
+                continue;
+            }
+            for y in 0..=grid.height() - height {
+                for x in 0..=grid.width() - width {
+                    let origin = Point::new(x, y);
+                    let at = |offset: Point| Point::new(x + offset.x, y + offset.y);
+                    let matches = variant
+                        .cells
+                        .enumerate()
+                        .all(|(offset, cell)| cell.is_none_or(|letter| grid[at(offset)] == letter));
+                    if matches {
+                        hits.push(TemplateHit {
+                            anchor: at(variant.anchor),
+                            origin,
+                            variant: index,
+                        });
+                    }
+                }
+            }
+        }
+        hits.sort_by_key(|hit| (hit.anchor.y, hit.anchor.x, hit.variant));
+        hits
     }
 }
 
-#[aoc_generator(day4, part2)]
-fn parse_part2(input: &str) -> CharMat {
-    CharMat::from_str(input.trim())
+impl Variant {
+    /// Turned by a quarter, clockwise
+    fn rotated(&self) -> Self {
+        let height = self.cells.height();
+        let turn = |point: Point| Point::new(height - 1 - point.y, point.x);
+        let mut cells = Grid::new(height, self.cells.width(), None);
+        for (point, &cell) in self.cells.enumerate() {
+            cells[turn(point)] = cell;
+        }
+        Self {
+            cells,
+            anchor: turn(self.anchor),
+        }
+    }
+
+    /// Mirrored left to right
+    fn reflected(&self) -> Self {
+        let width = self.cells.width();
+        let mirror = |point: Point| Point::new(width - 1 - point.x, point.y);
+        let mut cells = self.cells.clone();
+        for (point, &cell) in self.cells.enumerate() {
+            cells[mirror(point)] = cell;
+        }
+        Self {
+            cells,
+            anchor: mirror(self.anchor),
+        }
+    }
 }
 
+/// The number of X-MAS: two MAS crossing diagonally on their A
 #[aoc(day4, part2)]
-fn part2(input: &CharMat) -> u64 {
-    let a_matches = input.match_indices("A");
-    a_matches
-        .iter()
-        .filter(|(r, c)| {
-            r > &0_usize
-                && c > &0_usize
-                && ((input.get(r - 1, c - 1) == "M" && input.get(r + 1, c + 1) == "S")
-                    || (input.get(r - 1, c - 1) == "S" && input.get(r + 1, c + 1) == "M"))
-                && ((input.get(r - 1, c + 1) == "M" && input.get(r + 1, c - 1) == "S")
-                    || (input.get(r - 1, c + 1) == "S" && input.get(r + 1, c - 1) == "M"))
+pub fn part2(input: &CharMat) -> u64 {
+    input
+        .enumerate()
+        .filter(|(_, &c)| c == 'A')
+        .filter(|&(a, _)| {
+            let corner = |diagonal: Diagonal| input.step(a, diagonal.vector()).map(|p| input[p]);
+            is_m_and_s(corner(Diagonal::NW), corner(Diagonal::SE))
+                && is_m_and_s(corner(Diagonal::NE), corner(Diagonal::SW))
         })
         .count() as u64
 }
 
+/// The same, by matching the X-MAS as a template in all orientations
+#[aoc(day4, part2, template)]
+fn part2_template(input: &CharMat) -> u64 {
+    let x_mas = Template::parse_anchored("M.S/.A./M.S", Point::new(1, 1))
+        .expect("the X-MAS template is a 3x3 square");
+    x_mas.find(input).len() as u64
+}
+
+fn is_m_and_s(one_end: Option<char>, other_end: Option<char>) -> bool {
+    matches!(
+        (one_end, other_end),
+        (Some('M'), Some('S')) | (Some('S'), Some('M'))
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use indoc::indoc;
 
     use super::*;
 
-    const PART_1_EXAMPLE: &'static str = indoc! {"
+    const PART_1_EXAMPLE: &str = indoc! {"
         MMMSXXMASM
         MSAMXMSMSA
         AMXSXMAAMM
@@ -202,7 +341,7 @@ mod tests {
         MXMXAXMASX
     "};
 
-    const PART_1_SMALL_EXAMPLE: &'static str = indoc! {"
+    const PART_1_SMALL_EXAMPLE: &str = indoc! {"
         ..X...
         .SAMX.
         .A..A.
@@ -212,17 +351,43 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse(PART_1_EXAMPLE)), 18);
+        assert_eq!(part1(&parse(PART_1_EXAMPLE).unwrap()), 18);
     }
 
     #[test]
     fn part1_small_example() {
-        assert_eq!(part1(&parse(PART_1_SMALL_EXAMPLE)), 4);
+        assert_eq!(part1(&parse(PART_1_SMALL_EXAMPLE).unwrap()), 4);
+    }
+
+    #[test]
+    fn parse_ragged_word_search() {
+        let error = parse("XMAS\nSAM\n").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 1));
+        let error = parse("XMAS\nSAMÄS\n").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 1));
+    }
+
+    #[test]
+    fn part1_non_ascii() {
+        assert_eq!(part1(&parse("ÄXMAS\nSAMXÖ\n").unwrap()), 2);
+    }
+
+    #[test]
+    fn part1_non_square() {
+        let wide = parse("XMASAMX.\n.M......\n..A.....\n...S....\n").unwrap();
+        assert_eq!(part1(&wide), 3);
+        let tall = Grid::from_vec(
+            wide.height(),
+            wide.width(),
+            wide.columns().flatten().copied().collect(),
+        );
+        assert_eq!((tall.width(), tall.height()), (4, 8));
+        assert_eq!(part1(&tall), 3);
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse_part2(PART_1_EXAMPLE)), 9);
+        assert_eq!(part2(&parse(PART_1_EXAMPLE).unwrap()), 9);
     }
 
     #[test]
@@ -232,7 +397,7 @@ mod tests {
             .A.
             S.S
         "};
-        assert_eq!(part2(&parse_part2(&input)), 1);
+        assert_eq!(part2(&parse(input).unwrap()), 1);
     }
     #[test]
     fn part2_xmas2() {
@@ -241,7 +406,7 @@ mod tests {
             .A.
             M.S
         "};
-        assert_eq!(part2(&parse_part2(&input)), 1);
+        assert_eq!(part2(&parse(input).unwrap()), 1);
     }
 
     #[test]
@@ -251,7 +416,7 @@ mod tests {
             .A.
             S.M
         "};
-        assert_eq!(part2(&parse_part2(&input)), 1);
+        assert_eq!(part2(&parse(input).unwrap()), 1);
     }
     #[test]
     fn part2_xmas4() {
@@ -260,6 +425,118 @@ mod tests {
             .A.
             M.M
         "};
-        assert_eq!(part2(&parse_part2(&input)), 1);
+        assert_eq!(part2(&parse(input).unwrap()), 1);
+    }
+
+    #[test]
+    fn word_search_hits() {
+        let grid = parse(PART_1_SMALL_EXAMPLE).unwrap();
+        let search = WordSearch::new(["XMAS"]);
+        let hits = search.find(&grid);
+        assert_eq!(
+            hits[0],
+            Hit {
+                word: 0,
+                start: Point::new(2, 0),
+                direction: Vector::new(1, 1)
+            }
+        );
+        assert_eq!(
+            search.highlight(&grid, &hits).to_string(),
+            PART_1_SMALL_EXAMPLE
+        );
+        assert_eq!(
+            search.hit_cells(&grid, &hits[0]).collect::<Vec<_>>(),
+            [
+                Point::new(2, 0),
+                Point::new(3, 1),
+                Point::new(4, 2),
+                Point::new(5, 3)
+            ]
+        );
+    }
+
+    #[test]
+    fn word_search_many_words() {
+        let grid = parse(PART_1_EXAMPLE).unwrap();
+        let search = WordSearch::new(["XMAS", "MAS", "SAMX"]);
+        let hits = search.find(&grid);
+        let count = |word| hits.iter().filter(|hit| hit.word == word).count();
+        // SAMX is XMAS backwards, so found as often, just from the other end
+        assert_eq!((count(0), count(2)), (18, 18));
+        assert_eq!(count(1), WordSearch::new(["MAS"]).count(&grid));
+        assert_eq!(
+            WordSearch::new(["A"]).count(&grid),
+            8 * grid.iter().filter(|&&c| c == 'A').count()
+        );
+    }
+
+    #[test]
+    fn word_search_wrap_around() {
+        let grid = parse(indoc! {"
+            ASXM
+            ....
+        "})
+        .unwrap();
+        assert_eq!(WordSearch::new(["XMAS"]).count(&grid), 0);
+        let wrapping = WordSearch::new(["XMAS"]).with_wrap_around(true);
+        let hits = wrapping.find(&grid);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(wrapping.highlight(&grid, &hits).to_string(), "ASXM\n....\n");
+    }
+
+    #[test]
+    fn template_variants() {
+        let x_mas = Template::parse("M.S/.A./M.S").unwrap();
+        assert_eq!(x_mas.n_variants(), 4);
+        assert_eq!(x_mas.variant(1).to_string(), "M.M\n.A.\nS.S\n");
+        // straight words only come in the orthogonal directions
+        assert_eq!(Template::parse("XMAS").unwrap().n_variants(), 4);
+        let corner = Template::parse(indoc! {"
+            XM
+            .A
+        "})
+        .unwrap();
+        assert_eq!(corner.n_variants(), 8);
+        assert_eq!(Template::parse("A.A/.A./A.A").unwrap().n_variants(), 1);
+        assert!(Template::parse("M.S/.A/M.S").is_err());
+        assert!(Template::parse("").is_err());
+        assert!(Template::parse(" \n ").is_err());
+        let error = Template::parse_anchored("XMAS", Point::new(4, 0)).unwrap_err();
+        assert_eq!(error.expected, "the anchor 4,0 inside the pattern");
+    }
+
+    #[test]
+    fn template_hits() {
+        let grid = parse(PART_1_EXAMPLE).unwrap();
+        assert_eq!(part2_template(&grid), part2(&grid));
+        let x_mas = Template::parse_anchored("M.S/.A./M.S", Point::new(1, 1)).unwrap();
+        let hits = x_mas.find(&grid);
+        assert_eq!(
+            hits[0],
+            TemplateHit {
+                anchor: Point::new(2, 1),
+                origin: Point::new(1, 0),
+                variant: 0
+            }
+        );
+        assert!(hits.iter().all(|hit| grid[hit.anchor] == 'A'));
+
+        // the anchor follows the template around
+        let grid = parse("...\nXMA\n..S\n").unwrap();
+        let corner = Template::parse_anchored("XMA/..S", Point::new(0, 0)).unwrap();
+        let hits = corner.find(&grid);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(
+            (hits[0].anchor, hits[0].origin),
+            (Point::new(0, 1), Point::new(0, 1))
+        );
+        let grid = parse("S..\nAMX\n...\n").unwrap();
+        let hits = corner.find(&grid);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(
+            (hits[0].anchor, hits[0].origin),
+            (Point::new(2, 1), Point::new(0, 0))
+        );
     }
 }
@@ -1,9 +1,26 @@
-use std::collections::{HashMap, HashSet};
+//! Day 5: Print Queue
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
 
 use aoc_runner_derive::{aoc, aoc_generator};
+use itertools::Itertools;
+
+use crate::{
+    parsing::{parse_token, split_once, ParseError},
+    table::Table,
+};
+
+/// The page ordering rules `(before, after)`, and the updates as lists of page numbers
+pub type Input = (Vec<(u64, u64)>, Vec<Vec<u64>>);
 
+/// Parses the rules like `47|53`, a blank line, and the updates like `75,47,61`
 #[aoc_generator(day5)]
-fn parse(input: &str) -> (Vec<(u64, u64)>, Vec<Vec<u64>>) {
+pub fn parse(input: &str) -> Result<Input, ParseError> {
     let mut rules = Vec::with_capacity(input.len() / 2);
     let mut in_rules = true;
     let mut updates = Vec::new();
@@ -13,25 +30,24 @@ fn parse(input: &str) -> (Vec<(u64, u64)>, Vec<Vec<u64>>) {
             continue;
         }
         if in_rules {
-            let mut num_iter = line
-                .split("|")
-                .map(|c| c.parse::<u64>().expect("not a number"));
-            let first = num_iter.next().unwrap();
-            let second = num_iter.next().unwrap();
+            let (first, second) = split_once(input, line, "|", "a rule like 47|53")?;
+            let first = parse_token(input, first, "a page number")?;
+            let second = parse_token(input, second, "a page number")?;
             rules.push((first, second));
         } else {
             let pages: Vec<u64> = line
                 .split(",")
-                .map(|c| c.parse::<u64>().expect("not a number"))
-                .collect();
+                .map(|c| parse_token(input, c, "a page number"))
+                .collect::<Result<_, _>>()?;
             updates.push(pages);
         }
     }
-    (rules, updates)
+    Ok((rules, updates))
 }
 
+/// The sum of the middle page numbers of the updates that are in the right order
 #[aoc(day5, part1)]
-fn part1((rules, updates): &(Vec<(u64, u64)>, Vec<Vec<u64>>)) -> u64 {
+pub fn part1((rules, updates): &(Vec<(u64, u64)>, Vec<Vec<u64>>)) -> u64 {
     updates
         .iter()
         .filter(|&update| part_1_check_update(update, rules))
@@ -83,6 +99,8 @@ fn part_1_sorting_check_update(update: &[u64], rules: &[(u64, u64)]) -> bool {
     sort_update(update, rules) == update
 }
 
+/// Sorts `update` with the rules between its pages as the comparison, rebuilding them
+/// (indexed by page number, so for small page numbers only) for every update
 fn sort_update(update: &[u64], rules: &[(u64, u64)]) -> Vec<u64> {
     let relevant_rules = rules
         .iter()
@@ -103,12 +121,13 @@ fn sort_update(update: &[u64], rules: &[(u64, u64)]) -> Vec<u64> {
     sorted
 }
 
+/// The sum of the middle page numbers of the updates that are not, after ordering them
+///
+/// Like [`part1`] and the `sorting` variants, this is the original solution, which scans all
+/// rules for every update. They are kept as the baselines for the indexed variants:
+/// `generate 5 --seed 1 --size 200 | profile 5 -i - --repeat 200` compares them all.
 #[aoc(day5, part2)]
-fn part2((rules, updates): &(Vec<(u64, u64)>, Vec<Vec<u64>>)) -> u64 {
-    let mut rules_by_pre = HashMap::with_capacity(rules.len());
-    for (pre, post) in rules {
-        rules_by_pre.entry(pre).or_insert_with(Vec::new).push(post);
-    }
+pub fn part2((rules, updates): &(Vec<(u64, u64)>, Vec<Vec<u64>>)) -> u64 {
     updates
         .iter()
         .filter(|&update| !part_1_check_update(update, rules))
@@ -180,10 +199,6 @@ fn part2_fix_update(update: &[u64], rules: &[(u64, u64)]) -> Vec<u64> {
 
 #[aoc(day5, part2, sorting)]
 fn part2_sorting((rules, updates): &(Vec<(u64, u64)>, Vec<Vec<u64>>)) -> u64 {
-    let mut rules_by_pre = HashMap::with_capacity(rules.len());
-    for (pre, post) in rules {
-        rules_by_pre.entry(pre).or_insert_with(Vec::new).push(post);
-    }
     updates
         .iter()
         .filter(|&update| !part_1_check_update(update, rules))
@@ -191,6 +206,508 @@ fn part2_sorting((rules, updates): &(Vec<(u64, u64)>, Vec<Vec<u64>>)) -> u64 {
         .map(|update| update[update.len() / 2])
         .sum()
 }
+
+/// The page ordering rules as a graph, with an edge from each page to those that must follow it
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PrecedenceGraph {
+    successors: HashMap<u64, HashSet<u64>>,
+}
+
+/// The pages of an update in an order that follows the rules
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageOrder {
+    pub pages: Vec<u64>,
+    /// Whether this is the only order that follows the rules
+    pub unique: bool,
+}
+
+/// Pages that the rules require to come before themselves
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    /// Each page must come before the next, and the last before the first
+    pub pages: Vec<u64>,
+}
+
+impl Display for Cycle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let rules = self
+            .pages
+            .iter()
+            .zip(self.pages.iter().cycle().skip(1))
+            .map(|(before, after)| format!("{before}|{after}"))
+            .join(", ");
+        write!(f, "the rules {rules} form a cycle")
+    }
+}
+
+impl Error for Cycle {}
+
+impl PrecedenceGraph {
+    pub fn new(rules: &[(u64, u64)]) -> Self {
+        let mut successors: HashMap<u64, HashSet<u64>> = HashMap::new();
+        for &(before, after) in rules {
+            successors.entry(before).or_default().insert(after);
+        }
+        Self { successors }
+    }
+
+    /// Whether a rule says `before` comes before `after`
+    pub fn precedes(&self, before: u64, after: u64) -> bool {
+        self.successors
+            .get(&before)
+            .is_some_and(|successors| successors.contains(&after))
+    }
+
+    /// Every page any rule mentions, in ascending order
+    pub fn pages(&self) -> Vec<u64> {
+        self.successors
+            .iter()
+            .flat_map(|(&before, successors)| successors.iter().copied().chain([before]))
+            .sorted_unstable()
+            .dedup()
+            .collect()
+    }
+
+    /// Orders the (distinct) `pages` by the rules among them, with Kahn's algorithm
+    ///
+    /// Pages free to go next go in the order they were given, so pages that are in order
+    /// stay that way. Fails with one of the cycles if the rules cannot all be followed.
+    pub fn sort(&self, pages: &[u64]) -> Result<PageOrder, Cycle> {
+        let successors = self.local_successors(pages);
+        let mut n_predecessors = vec![0; pages.len()];
+        for &successor in successors.iter().flatten() {
+            n_predecessors[successor] += 1;
+        }
+        let mut ready: BinaryHeap<Reverse<usize>> = (0..pages.len())
+            .filter(|&page| n_predecessors[page] == 0)
+            .map(Reverse)
+            .collect();
+        let mut sorted = Vec::with_capacity(pages.len());
+        let mut unique = true;
+        while let Some(Reverse(page)) = ready.pop() {
+            unique &= ready.is_empty();
+            sorted.push(pages[page]);
+            for &successor in &successors[page] {
+                n_predecessors[successor] -= 1;
+                if n_predecessors[successor] == 0 {
+                    ready.push(Reverse(successor));
+                }
+            }
+        }
+        if sorted.len() < pages.len() {
+            return Err(self.find_cycle(pages, &successors, &n_predecessors));
+        }
+        Ok(PageOrder {
+            pages: sorted,
+            unique,
+        })
+    }
+
+    /// Every rule `update` breaks, and the fewest moves fixing it
+    pub fn audit(&self, update: &[u64]) -> Result<Audit, Cycle> {
+        let violations = update
+            .iter()
+            .enumerate()
+            .tuple_combinations()
+            .filter(|&((_, &earlier), (_, &later))| self.precedes(later, earlier))
+            .map(
+                |((earlier_at, &earlier), (later_at, &later))| RuleViolation {
+                    rule: (later, earlier),
+                    positions: (later_at, earlier_at),
+                },
+            )
+            .sorted_by_key(|violation| (violation.positions.1, violation.positions.0))
+            .collect_vec();
+        let fixed = self.sort(update)?.pages;
+        let target: HashMap<u64, usize> = fixed.iter().enumerate().map(|(i, &p)| (p, i)).collect();
+        let targets = update.iter().map(|page| target[page]).collect_vec();
+        let staying = longest_increasing_subsequence(&targets);
+        let moves = (0..update.len())
+            .filter(|position| !staying.contains(position))
+            .map(|position| Move {
+                page: update[position],
+                from: position,
+                to: targets[position],
+            })
+            .collect();
+        Ok(Audit {
+            update: update.to_vec(),
+            violations,
+            fixed,
+            moves,
+        })
+    }
+
+    /// For each of `pages`, the indices of those among them that must follow it
+    fn local_successors(&self, pages: &[u64]) -> Vec<Vec<usize>> {
+        let index: HashMap<u64, usize> = pages.iter().enumerate().map(|(i, &p)| (p, i)).collect();
+        pages
+            .iter()
+            .map(|page| {
+                self.successors
+                    .get(page)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|successor| index.get(successor).copied())
+                    .sorted_unstable()
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// A cycle among the pages Kahn's algorithm left with predecessors
+    fn find_cycle(
+        &self,
+        pages: &[u64],
+        successors: &[Vec<usize>],
+        n_predecessors: &[usize],
+    ) -> Cycle {
+        // every page left over has a predecessor left over, so walking backwards
+        // from any of them has to come back around eventually
+        let mut predecessor = vec![None; pages.len()];
+        for (page, successors) in successors.iter().enumerate() {
+            if n_predecessors[page] > 0 {
+                for &successor in successors {
+                    predecessor[successor].get_or_insert(page);
+                }
+            }
+        }
+        let mut page = (0..pages.len())
+            .find(|&page| n_predecessors[page] > 0)
+            .expect("Kahn's algorithm stopped early without any page left over");
+        let mut seen = vec![false; pages.len()];
+        while !seen[page] {
+            seen[page] = true;
+            page = predecessor[page].expect("a left over page without predecessors");
+        }
+        let mut cycle = vec![pages[page]];
+        let mut current = predecessor[page].unwrap();
+        while current != page {
+            cycle.push(pages[current]);
+            current = predecessor[current].unwrap();
+        }
+        cycle.reverse();
+        // start from the smallest page, for a stable report
+        let smallest = cycle.iter().position_min().unwrap();
+        cycle.rotate_left(smallest);
+        Cycle { pages: cycle }
+    }
+}
+
+/// A rule an update breaks, by having its second page before its first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RuleViolation {
+    pub rule: (u64, u64),
+    /// Where the first and the second page of the rule are in the update
+    pub positions: (usize, usize),
+}
+
+/// Taking a page out of an update and putting it back elsewhere
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    pub page: u64,
+    /// Where the page was in the update
+    pub from: usize,
+    /// Where the page is in the fixed update
+    pub to: usize,
+}
+
+/// What is wrong with an update, and how to fix it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Audit {
+    pub update: Vec<u64>,
+    /// Every rule the update breaks, by the position of its second page, then of its first
+    pub violations: Vec<RuleViolation>,
+    /// The update in the order [`PrecedenceGraph::sort`] puts it
+    pub fixed: Vec<u64>,
+    /// The fewest pages to move to get from the update to `fixed`, by position in the update
+    ///
+    /// The others keep their order. If `fixed` is the only order following the rules,
+    /// as in the puzzle, no other order can be reached with fewer moves either.
+    pub moves: Vec<Move>,
+}
+
+impl Audit {
+    pub fn is_ordered(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// The update after making the moves: the pages that stay keep their places relative to each
+    /// other, and the moved ones go where they belong, from the front
+    pub fn apply_moves(&self) -> Vec<u64> {
+        let mut pages = self
+            .update
+            .iter()
+            .enumerate()
+            .filter(|(position, _)| !self.moves.iter().any(|m| m.from == *position))
+            .map(|(_, &page)| page)
+            .collect_vec();
+        for page_move in self.moves.iter().sorted_by_key(|m| m.to) {
+            pages.insert(page_move.to, page_move.page);
+        }
+        pages
+    }
+}
+
+/// The positions of a longest strictly increasing subsequence of `values`, in O(n log n)
+fn longest_increasing_subsequence(values: &[usize]) -> Vec<usize> {
+    // the position of the last value of the best subsequence of each length so far
+    let mut tails: Vec<usize> = Vec::new();
+    let mut previous = vec![None; values.len()];
+    for (position, &value) in values.iter().enumerate() {
+        let length = tails.partition_point(|&tail| values[tail] < value);
+        previous[position] = length.checked_sub(1).map(|length| tails[length]);
+        if length == tails.len() {
+            tails.push(position);
+        } else {
+            tails[length] = position;
+        }
+    }
+    let mut subsequence =
+        std::iter::successors(tails.last().copied(), |&position| previous[position]).collect_vec();
+    subsequence.reverse();
+    subsequence
+}
+
+/// The audits of all updates, with statistics over them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditReport {
+    pub audits: Vec<Audit>,
+}
+
+impl AuditReport {
+    /// Audits every update by the rules
+    pub fn new((rules, updates): &Input) -> Result<Self, Cycle> {
+        let graph = PrecedenceGraph::new(rules);
+        Ok(Self {
+            audits: updates
+                .iter()
+                .map(|update| graph.audit(update))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    pub fn n_ordered(&self) -> usize {
+        self.audits
+            .iter()
+            .filter(|audit| audit.is_ordered())
+            .count()
+    }
+
+    pub fn n_moves(&self) -> usize {
+        self.audits.iter().map(|audit| audit.moves.len()).sum()
+    }
+
+    /// The rules that are broken at all, with how often, most often first
+    pub fn violation_counts(&self) -> Vec<((u64, u64), usize)> {
+        self.audits
+            .iter()
+            .flat_map(|audit| &audit.violations)
+            .map(|violation| violation.rule)
+            .counts()
+            .into_iter()
+            .sorted_by_key(|&(rule, count)| (Reverse(count), rule))
+            .collect()
+    }
+
+    /// The `n` most often broken rules, with how often
+    pub fn to_table(&self, n: usize) -> Table {
+        let mut table = Table::new(["rule", "violations"]);
+        for ((before, after), count) in self.violation_counts().into_iter().take(n) {
+            table.push_row([format!("{before}|{after}"), count.to_string()]);
+        }
+        table
+    }
+}
+
+/// A summary, and the ten most often broken rules
+impl Display for AuditReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let n_updates = self.audits.len();
+        let n_ordered = self.n_ordered();
+        writeln!(
+            f,
+            "{n_updates} updates: {n_ordered} in order, {} not, fixed by moving {} pages",
+            n_updates - n_ordered,
+            self.n_moves()
+        )?;
+        write!(f, "{}", self.to_table(10))
+    }
+}
+
+/// The rules as a dense adjacency matrix of bitsets, for quick questions about whole updates
+///
+/// Pages are numbered densely as they first appear in a rule, so their numbers can be anything.
+/// Rules can be added and removed at any time. Built once for all updates, this makes the
+/// `bitset` variants an order of magnitude faster than the baselines, see [`part2`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuleIndex {
+    /// The dense index of each page
+    index: HashMap<u64, usize>,
+    /// Which pages each page must come before, one bit per dense index
+    before: Vec<Vec<u64>>,
+    n_rules: usize,
+}
+
+impl RuleIndex {
+    pub fn new(rules: &[(u64, u64)]) -> Self {
+        let mut index = Self::default();
+        for &(before, after) in rules {
+            index.add(before, after);
+        }
+        index
+    }
+
+    /// Adds the rule that `before` comes before `after`, returning whether it is new
+    pub fn add(&mut self, before: u64, after: u64) -> bool {
+        let (before, after) = (self.intern(before), self.intern(after));
+        let (word, bit) = (after / 64, 1 << (after % 64));
+        let is_new = self.before[before][word] & bit == 0;
+        self.before[before][word] |= bit;
+        self.n_rules += is_new as usize;
+        is_new
+    }
+
+    /// Removes the rule that `before` comes before `after`, returning whether there was one
+    pub fn remove(&mut self, before: u64, after: u64) -> bool {
+        let (Some(&before), Some(&after)) = (self.index.get(&before), self.index.get(&after))
+        else {
+            return false;
+        };
+        let (word, bit) = (after / 64, 1 << (after % 64));
+        let was_there = self.before[before][word] & bit != 0;
+        self.before[before][word] &= !bit;
+        self.n_rules -= was_there as usize;
+        was_there
+    }
+
+    /// Whether a rule says `before` comes before `after`
+    pub fn contains(&self, before: u64, after: u64) -> bool {
+        match (self.index.get(&before), self.index.get(&after)) {
+            (Some(&before), Some(&after)) => self.precedes(before, after),
+            _ => false,
+        }
+    }
+
+    /// The number of rules
+    pub fn len(&self) -> usize {
+        self.n_rules
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n_rules == 0
+    }
+
+    /// Whether `update` breaks none of the rules, in O(len²)
+    pub fn is_valid(&self, update: &[u64]) -> bool {
+        let pages = self.dense(update);
+        pages.iter().enumerate().all(|(i, &earlier)| {
+            pages[i + 1..].iter().all(
+                |&later| !matches!((earlier, later), (Some(e), Some(l)) if self.precedes(l, e)),
+            )
+        })
+    }
+
+    /// `update` ordered by the rules, in O(len²), or `None` if the rules among its pages
+    /// form a cycle (see [`PrecedenceGraph::sort`] for which)
+    ///
+    /// Like [`PrecedenceGraph::sort`], pages free to go next go in the order they were given.
+    pub fn fix(&self, update: &[u64]) -> Option<Vec<u64>> {
+        let pages = self.dense(update);
+        let must_precede = |earlier: usize, later: usize| match (pages[earlier], pages[later]) {
+            (Some(earlier), Some(later)) => self.precedes(earlier, later),
+            _ => false,
+        };
+        let mut n_predecessors = (0..pages.len())
+            .map(|later| {
+                (0..pages.len())
+                    .filter(|&earlier| must_precede(earlier, later))
+                    .count()
+            })
+            .collect_vec();
+        let mut placed = vec![false; pages.len()];
+        let mut fixed = Vec::with_capacity(pages.len());
+        for _ in 0..pages.len() {
+            let next = (0..pages.len()).find(|&page| !placed[page] && n_predecessors[page] == 0)?;
+            placed[next] = true;
+            fixed.push(update[next]);
+            for (later, n_predecessors) in n_predecessors.iter_mut().enumerate() {
+                if must_precede(next, later) {
+                    *n_predecessors -= 1;
+                }
+            }
+        }
+        Some(fixed)
+    }
+
+    fn intern(&mut self, page: u64) -> usize {
+        if let Some(&index) = self.index.get(&page) {
+            return index;
+        }
+        let index = self.index.len();
+        self.index.insert(page, index);
+        let n_words = (index + 1).div_ceil(64);
+        if n_words > self.before.first().map_or(0, Vec::len) {
+            for row in &mut self.before {
+                row.resize(n_words, 0);
+            }
+        }
+        self.before.push(vec![0; n_words]);
+        index
+    }
+
+    fn dense(&self, update: &[u64]) -> Vec<Option<usize>> {
+        update
+            .iter()
+            .map(|page| self.index.get(page).copied())
+            .collect()
+    }
+
+    fn precedes(&self, before: usize, after: usize) -> bool {
+        self.before[before][after / 64] & (1 << (after % 64)) != 0
+    }
+}
+
+#[aoc(day5, part1, bitset)]
+fn part1_bitset((rules, updates): &(Vec<(u64, u64)>, Vec<Vec<u64>>)) -> u64 {
+    let index = RuleIndex::new(rules);
+    updates
+        .iter()
+        .filter(|update| index.is_valid(update))
+        .map(|update| update[update.len() / 2])
+        .sum()
+}
+
+#[aoc(day5, part2, bitset)]
+fn part2_bitset((rules, updates): &(Vec<(u64, u64)>, Vec<Vec<u64>>)) -> u64 {
+    let index = RuleIndex::new(rules);
+    updates
+        .iter()
+        .filter(|update| !index.is_valid(update))
+        .map(|update| {
+            let fixed = index
+                .fix(update)
+                .unwrap_or_else(|| panic!("the rules for {update:?} form a cycle"));
+            fixed[fixed.len() / 2]
+        })
+        .sum()
+}
+
+#[aoc(day5, part2, topological)]
+fn part2_topological((rules, updates): &(Vec<(u64, u64)>, Vec<Vec<u64>>)) -> u64 {
+    let graph = PrecedenceGraph::new(rules);
+    updates
+        .iter()
+        .filter_map(|update| {
+            let sorted = graph
+                .sort(update)
+                .unwrap_or_else(|cycle| panic!("cannot order {update:?}: {cycle}"))
+                .pages;
+            (sorted != *update).then(|| sorted[sorted.len() / 2])
+        })
+        .sum()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -260,31 +777,211 @@ mod tests {
             vec![61, 13, 29],
             vec![97, 13, 75, 29, 47],
         ];
-        let (rules, updates) = parse(PART_1_EXAMPLE);
+        let (rules, updates) = parse(PART_1_EXAMPLE).unwrap();
         assert_eq!(rules, expected_rules);
         assert_eq!(updates, expected_updates);
     }
 
+    #[test]
+    fn parse_malformed_rule_and_update() {
+        let error = parse("47|53\n97-13\n\n75,47,61\n").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 1));
+        assert_eq!(error.snippet, "97-13");
+
+        let error = parse("47|53\n\n75,47,,61\n").unwrap_err();
+        assert_eq!((error.line, error.column), (3, 7));
+        assert_eq!(error.expected, "a page number");
+    }
+
     #[test]
     fn part1_example() {
-        let input = parse(PART_1_EXAMPLE);
+        let input = parse(PART_1_EXAMPLE).unwrap();
         assert_eq!(part1(&input), 143);
     }
 
     #[test]
     fn part1_example_sorting() {
-        let input = parse(PART_1_EXAMPLE);
+        let input = parse(PART_1_EXAMPLE).unwrap();
         assert_eq!(part1_sorting(&input), 143);
     }
 
     #[test]
     fn part2_example() {
-        let input = parse(PART_1_EXAMPLE);
+        let input = parse(PART_1_EXAMPLE).unwrap();
         assert_eq!(part2(&input), 123);
     }
     #[test]
     fn part2_example_sorting() {
-        let input = parse(PART_1_EXAMPLE);
+        let input = parse(PART_1_EXAMPLE).unwrap();
         assert_eq!(part2_sorting(&input), 123);
     }
+
+    #[test]
+    fn part2_example_topological() {
+        let input = parse(PART_1_EXAMPLE).unwrap();
+        assert_eq!(part2_topological(&input), 123);
+    }
+
+    #[test]
+    fn precedence_graph_sort() {
+        let (rules, _) = parse(PART_1_EXAMPLE).unwrap();
+        let graph = PrecedenceGraph::new(&rules);
+        assert!(graph.precedes(47, 53));
+        assert!(!graph.precedes(53, 47));
+        assert_eq!(graph.pages(), [13, 29, 47, 53, 61, 75, 97]);
+
+        let ordering = graph.sort(&[97, 13, 75, 29, 47]).unwrap();
+        assert_eq!(ordering.pages, [97, 75, 47, 29, 13]);
+        assert!(ordering.unique);
+        // the example rules order all their pages completely
+        assert!(graph.sort(&graph.pages()).unwrap().unique);
+
+        // without rules between them, pages stay as they are, but could go either way
+        let ordering = graph.sort(&[61, 47, 5]).unwrap();
+        assert_eq!(ordering.pages, [47, 61, 5]);
+        assert!(!ordering.unique);
+        assert_eq!(graph.sort(&[]).unwrap().pages, []);
+    }
+
+    #[test]
+    fn precedence_graph_cycle() {
+        let graph = PrecedenceGraph::new(&[(1, 2), (2, 3), (3, 4), (4, 2), (5, 1)]);
+        let cycle = graph.sort(&[5, 4, 3, 2, 1]).unwrap_err();
+        assert_eq!(cycle.pages, [2, 3, 4]);
+        assert_eq!(cycle.to_string(), "the rules 2|3, 3|4, 4|2 form a cycle");
+        // the cycle only matters if all of it is in the update
+        assert_eq!(graph.sort(&[4, 3, 1, 5]).unwrap().pages, [3, 4, 5, 1]);
+
+        let graph = PrecedenceGraph::new(&[(7, 7)]);
+        assert_eq!(graph.sort(&[7]).unwrap_err().pages, [7]);
+    }
+
+    #[test]
+    fn audit_update() {
+        let (rules, _) = parse(PART_1_EXAMPLE).unwrap();
+        let graph = PrecedenceGraph::new(&rules);
+        let audit = graph.audit(&[75, 97, 47, 61, 53]).unwrap();
+        assert_eq!(
+            audit.violations,
+            [RuleViolation {
+                rule: (97, 75),
+                positions: (1, 0)
+            }]
+        );
+        assert_eq!(audit.fixed, [97, 75, 47, 61, 53]);
+        // moving either of the two will do
+        assert_eq!(audit.moves.len(), 1);
+        assert_eq!(audit.apply_moves(), audit.fixed);
+
+        let audit = graph.audit(&[97, 13, 75, 29, 47]).unwrap();
+        assert_eq!(audit.violations.len(), 4);
+        assert!(audit.violations.iter().all(|violation| {
+            let (first, second) = violation.positions;
+            first > second && audit.update[first] == violation.rule.0
+        }));
+        assert_eq!(audit.fixed, [97, 75, 47, 29, 13]);
+        // 97 and either 75 or 47 and 29 stay (both 3 long), 2 pages move
+        assert_eq!(audit.moves.len(), 2);
+        assert_eq!(audit.apply_moves(), audit.fixed);
+
+        let audit = graph.audit(&[75, 47, 61, 53, 29]).unwrap();
+        assert!(audit.is_ordered() && audit.moves.is_empty());
+    }
+
+    #[test]
+    fn audit_moves_are_minimal() {
+        // with all pages ordered, what stays is a longest run in the right order
+        let rules = (1..=6).tuple_combinations().collect_vec();
+        let graph = PrecedenceGraph::new(&rules);
+        for (update, n_moves) in [
+            (vec![1, 2, 3, 4, 5, 6], 0),
+            (vec![6, 1, 2, 3, 4, 5], 1),
+            (vec![2, 1, 4, 3, 6, 5], 3),
+            (vec![6, 5, 4, 3, 2, 1], 5),
+            (vec![3, 4, 5, 6, 1, 2], 2),
+        ] {
+            let audit = graph.audit(&update).unwrap();
+            assert_eq!(audit.moves.len(), n_moves, "{update:?}");
+            assert_eq!(audit.apply_moves(), [1, 2, 3, 4, 5, 6]);
+        }
+        assert!(graph.audit(&[1, 2]).is_ok());
+        assert!(PrecedenceGraph::new(&[(1, 2), (2, 1)])
+            .audit(&[1, 2])
+            .is_err());
+    }
+
+    #[test]
+    fn audit_report() {
+        let input = parse(PART_1_EXAMPLE).unwrap();
+        let report = AuditReport::new(&input).unwrap();
+        assert_eq!(report.n_ordered(), 3);
+        let counts = report.violation_counts();
+        assert_eq!(
+            counts.iter().map(|(_, count)| count).sum::<usize>(),
+            1 + 4 + 1
+        );
+        assert_eq!(counts[0], ((29, 13), 2));
+        assert_eq!(
+            report.to_string().lines().next().unwrap(),
+            "6 updates: 3 in order, 3 not, fixed by moving 4 pages"
+        );
+        assert_eq!(report.to_table(1).rows(), [["29|13", "2"]]);
+    }
+
+    #[test]
+    fn part1_and_part2_example_bitset() {
+        let input = parse(PART_1_EXAMPLE).unwrap();
+        assert_eq!(part1_bitset(&input), 143);
+        assert_eq!(part2_bitset(&input), 123);
+    }
+
+    #[test]
+    fn rule_index_add_and_remove() {
+        let mut index = RuleIndex::new(&[(47, 53), (97, 13)]);
+        assert_eq!(index.len(), 2);
+        assert!(index.contains(47, 53) && !index.contains(53, 47));
+        assert!(!index.contains(47, 12345));
+        assert!(index.is_valid(&[47, 53, 97, 13]));
+        assert!(!index.is_valid(&[53, 47]));
+        assert!(index.is_valid(&[53, 12345, 1]));
+
+        assert!(index.add(53, 47));
+        assert!(!index.add(53, 47));
+        assert_eq!(index.fix(&[47, 53]), None);
+        assert!(index.remove(47, 53));
+        assert!(!index.remove(47, 53));
+        assert!(!index.remove(1, 2));
+        assert_eq!(index.len(), 2);
+        assert!(index.is_valid(&[53, 47]));
+        assert_eq!(index.fix(&[13, 47, 97, 53]), Some(vec![97, 13, 53, 47]));
+
+        // more pages than fit one word of bits
+        let rules = (1000..1200).tuple_windows().collect_vec();
+        let mut index = RuleIndex::new(&rules);
+        assert_eq!(index.len(), 199);
+        assert!(index.contains(1198, 1199) && !index.contains(1199, 1198));
+        assert!(index.is_valid(&[1000, 1100, 1199]));
+        // only direct rules count, like in the puzzle
+        assert_eq!(index.fix(&[1101, 1199, 1100]), Some(vec![1199, 1100, 1101]));
+        index.remove(1000, 1001);
+        assert_eq!(index.fix(&[1001, 1000]), Some(vec![1001, 1000]));
+    }
+
+    #[test]
+    fn rule_index_agrees_with_the_graph() {
+        for seed in 0..5 {
+            let input =
+                crate::inputs::generate_with(5, seed, 100, crate::params::Params::EXAMPLE).unwrap();
+            let input = parse(&input).unwrap();
+            let (rules, updates) = &input;
+            let (index, graph) = (RuleIndex::new(rules), PrecedenceGraph::new(rules));
+            for update in updates {
+                let fixed = graph.sort(update).unwrap().pages;
+                assert_eq!(index.is_valid(update), fixed == *update);
+                assert_eq!(index.fix(update), Some(fixed));
+            }
+            assert_eq!(part1_bitset(&input), part1(&input));
+            assert_eq!(part2_bitset(&input), part2(&input));
+        }
+    }
 }
@@ -1,12 +1,16 @@
-use std::{
-    collections::HashSet,
-    ops::{Index, IndexMut},
-};
+//! Day 6: Guard Gallivant
+
+use std::collections::HashSet;
 
 use aoc_runner_derive::{aoc, aoc_generator};
 
+use crate::{
+    grid::{Direction, Grid, Point, Vector},
+    parsing::ParseError,
+};
+
 #[derive(PartialEq, Debug, Clone, Copy)]
-enum Location {
+pub enum Location {
     Clear,
     Obstacle,
 }
@@ -23,100 +27,44 @@ impl PartialEq<StopReason> for Location {
     }
 }
 
-struct MapLab {
-    height: usize,
-    width: usize,
-    buffer: Vec<Location>,
-}
-
-impl Index<&(usize, usize)> for MapLab {
-    type Output = Location;
-
-    fn index(&self, (x, y): &(usize, usize)) -> &Self::Output {
-        debug_assert_eq!(
-            self.height * self.width,
-            self.buffer.len(),
-            "inconsistent buffer size"
-        );
-        debug_assert!(*x < self.width, "x out of bounds");
-        debug_assert!(*y < self.height, "y out of bounds");
-        &self.buffer[y * self.width + x]
-    }
-}
+pub type MapLab = Grid<Location>;
 
 impl MapLab {
-    fn get(&self, (x, y): &(usize, usize)) -> Option<Location> {
-        if *x < self.width && *y < self.height {
-            Some(self[&(*x, *y)])
-        } else {
-            None
-        }
-    }
-
-    fn until_obstacle(
-        &self,
-        from_pos: &(usize, usize),
-        towards: Direction,
-    ) -> ((usize, usize), StopReason) {
-        debug_assert!(self.get(from_pos).is_some(), "from_pos out of bounds");
-        let step = match towards {
-            Direction::Up => (0, -1),
-            Direction::Down => (0, 1),
-            Direction::Left => (-1, 0),
-            Direction::Right => (1, 0),
-        };
-        let mut pos = *from_pos;
+    fn until_obstacle(&self, from_pos: Point, towards: Direction) -> (Point, StopReason) {
+        debug_assert!(self.contains(from_pos), "from_pos out of bounds");
+        let mut pos = from_pos;
         loop {
-            let next_pos = (
-                (pos.0 as isize + step.0) as usize,
-                (pos.1 as isize + step.1) as usize,
-            );
-            match self.get(&next_pos) {
-                Some(Location::Obstacle) => return (pos, StopReason::Obstacle),
-                Some(Location::Clear) => pos = next_pos,
-                None => return (pos, StopReason::EndOfMap),
+            let Some(next_pos) = self.step(pos, towards.vector()) else {
+                return (pos, StopReason::EndOfMap);
+            };
+            match self[next_pos] {
+                Location::Obstacle => return (pos, StopReason::Obstacle),
+                Location::Clear => pos = next_pos,
             }
         }
     }
 
-    fn with_obstacle_at(&self, pos: &(usize, usize)) -> MapLab {
-        let mut new_buffer = self.buffer.clone();
-        new_buffer[pos.1 * self.width + pos.0] = Location::Obstacle;
-        MapLab {
-            height: self.height,
-            width: self.width,
-            buffer: new_buffer,
-        }
+    fn with_obstacle_at(&self, pos: Point) -> MapLab {
+        let mut new_map = self.clone();
+        new_map[pos] = Location::Obstacle;
+        new_map
     }
 }
 
-#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
-}
-
+/// Where the guard is, and which way they are facing
 #[derive(Eq, PartialEq, Debug, Clone, Hash)]
-struct GuardState {
-    pos: (usize, usize),
-    facing: Direction,
+pub struct GuardState {
+    pub pos: Point,
+    pub facing: Direction,
 }
 
 impl GuardState {
     fn walk(&self, map_lab: &MapLab) -> (GuardState, StopReason) {
-        let (new_pos, stop_reason) = map_lab.until_obstacle(&self.pos, self.facing);
-        let new_facing = match self.facing {
-            Direction::Up => Direction::Right,
-            Direction::Right => Direction::Down,
-            Direction::Down => Direction::Left,
-            Direction::Left => Direction::Up,
-        };
+        let (new_pos, stop_reason) = map_lab.until_obstacle(self.pos, self.facing);
         (
             GuardState {
                 pos: new_pos,
-                facing: new_facing,
+                facing: self.facing.turn_right(),
             },
             stop_reason,
         )
@@ -124,86 +72,46 @@ impl GuardState {
 }
 
 struct BeenThereDoneThat {
-    height: usize,
-    width: usize,
-    buffer: Vec<bool>,
-}
-
-impl Index<&(usize, usize)> for BeenThereDoneThat {
-    type Output = bool;
-
-    fn index(&self, (x, y): &(usize, usize)) -> &Self::Output {
-        debug_assert_eq!(
-            self.height * self.width,
-            self.buffer.len(),
-            "inconsistent buffer size"
-        );
-        debug_assert!(*x < self.width, "x out of bounds");
-        debug_assert!(*y < self.height, "y out of bounds");
-        &self.buffer[y * self.width + x]
-    }
-}
-
-impl IndexMut<&(usize, usize)> for BeenThereDoneThat {
-    fn index_mut(&mut self, (x, y): &(usize, usize)) -> &mut Self::Output {
-        debug_assert_eq!(
-            self.height * self.width,
-            self.buffer.len(),
-            "inconsistent buffer size"
-        );
-        debug_assert!(*x < self.width, "x out of bounds");
-        debug_assert!(*y < self.height, "y out of bounds");
-        &mut self.buffer[y * self.width + x]
-    }
+    visited: Grid<bool>,
 }
 
 impl BeenThereDoneThat {
     fn with_dimensions(width: usize, height: usize) -> Self {
         BeenThereDoneThat {
-            height,
-            width,
-            buffer: vec![false; width * height],
+            visited: Grid::new(width, height, false),
         }
     }
 
-    fn visit(&mut self, (x, y): &(usize, usize)) {
-        self[&(*x, *y)] = true;
+    fn visit(&mut self, pos: Point) {
+        self.visited[pos] = true;
     }
 
-    fn visit_between(&mut self, from: &(usize, usize), to: &(usize, usize)) {
-        let step = (
-            (to.0 as isize - from.0 as isize).signum(),
-            (to.1 as isize - from.1 as isize).signum(),
-        );
+    fn visit_between(&mut self, from: Point, to: Point) {
+        let step = Vector {
+            dx: (to.x as isize - from.x as isize).signum(),
+            dy: (to.y as isize - from.y as isize).signum(),
+        };
         debug_assert!(
-            step.0 == 0 || step.1 == 0,
+            step.dx == 0 || step.dy == 0,
             "diagonal movement not supported"
         );
-        let mut pos = *from;
-        while pos != *to {
-            self.visit(&pos);
-            pos = (
-                (pos.0 as isize + step.0) as usize,
-                (pos.1 as isize + step.1) as usize,
-            );
+        let mut pos = from;
+        while pos != to {
+            self.visit(pos);
+            pos = pos + step;
         }
         self.visit(to);
     }
 
     fn total(&self) -> u64 {
-        self.buffer.iter().filter(|&&b| b).count() as u64
-    }
-
-    fn as_2d_idx(&self, idx: usize) -> (usize, usize) {
-        (idx % self.width, idx / self.width)
+        self.visited.iter().filter(|&&b| b).count() as u64
     }
 
-    fn iter_visited(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
-        self.buffer
-            .iter()
+    fn iter_visited(&self) -> impl Iterator<Item = Point> + '_ {
+        self.visited
             .enumerate()
             .filter(|&(_, b)| *b)
-            .map(|(idx, _)| self.as_2d_idx(idx))
+            .map(|(pos, _)| pos)
     }
 
     fn from_map_and_initial_state(
@@ -211,14 +119,14 @@ impl BeenThereDoneThat {
         initial_state: &GuardState,
     ) -> BeenThereDoneThat {
         let mut to_be_or_not_to_be =
-            BeenThereDoneThat::with_dimensions(map_lab.width, map_lab.height);
+            BeenThereDoneThat::with_dimensions(map_lab.width(), map_lab.height());
         let mut guard_state = initial_state.clone();
         let mut stop_reason = StopReason::Obstacle;
         // on adversarial maps, this could be an infinite loop
         // to guard against this case, we could stop when we reach the same state again
         while stop_reason != StopReason::EndOfMap {
             let (new_state, new_reason) = guard_state.walk(map_lab);
-            to_be_or_not_to_be.visit_between(&guard_state.pos, &new_state.pos);
+            to_be_or_not_to_be.visit_between(guard_state.pos, new_state.pos);
             guard_state = new_state;
             stop_reason = new_reason;
         }
@@ -226,66 +134,49 @@ impl BeenThereDoneThat {
     }
 }
 
+/// The map of the lab, and where the guard starts
+pub type Input = (MapLab, GuardState);
+
+/// Parses the map of `.` and `#`, with the guard as `^`, `>`, `v` or `<`
 #[aoc_generator(day6)]
-fn parse(input: &str) -> (MapLab, GuardState) {
+pub fn parse(input: &str) -> Result<Input, ParseError> {
     let input = input.trim();
-    let mut buffer = Vec::with_capacity(input.len());
-    let mut height = 0;
-    let mut width = 0;
     let mut guard = None;
-    for (y, line) in input.lines().enumerate() {
-        height = y + 1;
-        if y > 0 {
-            debug_assert_eq!(line.len(), width, "inconsistent line length");
-        }
-        width = line.len();
-        for (x, c) in line.chars().enumerate() {
-            let location = match c {
-                '.' => Location::Clear,
-                '#' => Location::Obstacle,
-                '^' | '>' | '<' | 'v' => {
-                    debug_assert!(guard.is_none(), "multiple guards found");
-                    guard = Some(GuardState {
-                        pos: (x, y),
-                        facing: match c {
-                            '^' => Direction::Up,
-                            '>' => Direction::Right,
-                            '<' => Direction::Left,
-                            'v' => Direction::Down,
-                            _ => unreachable!(),
-                        },
-                    });
-                    Location::Clear
-                }
-                _ => panic!("unexpected character: {}", c),
-            };
-            buffer.push(location);
-        }
-    }
-    let guard = guard.expect("guard not found");
-    (
-        MapLab {
-            height,
-            width,
-            buffer,
+    let map_lab = Grid::from_char_map(
+        input,
+        "'.', '#' or the one and only guard ('^', '>', 'v' or '<')",
+        |pos, c| match c {
+            '.' => Some(Location::Clear),
+            '#' => Some(Location::Obstacle),
+            '^' | '>' | '<' | 'v' if guard.is_none() => {
+                guard = Some(GuardState {
+                    pos,
+                    facing: Direction::from_arrow(c)?,
+                });
+                Some(Location::Clear)
+            }
+            _ => None,
         },
-        guard,
-    )
+    )?;
+    let guard = guard.ok_or_else(|| ParseError::at_end(input, "a guard somewhere on the map"))?;
+    Ok((map_lab, guard))
 }
 
+/// The number of distinct positions the guard visits before leaving the map
 #[aoc(day6, part1)]
-fn part1((map_lab, initial_state): &(MapLab, GuardState)) -> u64 {
+pub fn part1((map_lab, initial_state): &(MapLab, GuardState)) -> u64 {
     BeenThereDoneThat::from_map_and_initial_state(map_lab, initial_state).total()
 }
 
+/// The number of positions where a new obstruction would trap the guard in a loop
 #[aoc(day6, part2)]
-fn part2((map_lab, initial_state): &(MapLab, GuardState)) -> u64 {
+pub fn part2((map_lab, initial_state): &(MapLab, GuardState)) -> u64 {
     let visited = BeenThereDoneThat::from_map_and_initial_state(map_lab, initial_state);
     visited
         .iter_visited()
         .filter_map(|cand_pos| {
             if cand_pos != initial_state.pos {
-                Some(map_lab.with_obstacle_at(&cand_pos))
+                Some(map_lab.with_obstacle_at(cand_pos))
             } else {
                 None
             }
@@ -329,72 +220,77 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse(PART_1_EXAMPLE)), 41u64);
+        assert_eq!(part1(&parse(PART_1_EXAMPLE).unwrap()), 41u64);
     }
 
     #[test]
     fn part1_parse_example() {
-        let (map_lab, guard) = parse(PART_1_EXAMPLE);
-        assert_eq!(map_lab.height, 10);
-        assert_eq!(map_lab.width, 10);
-        assert_eq!(map_lab.buffer.len(), 100);
+        let (map_lab, guard) = parse(PART_1_EXAMPLE).unwrap();
+        assert_eq!(map_lab.height(), 10);
+        assert_eq!(map_lab.width(), 10);
+        assert_eq!(map_lab.len(), 100);
         assert_eq!(
-            map_lab
-                .buffer
-                .iter()
-                .filter(|&l| *l == Location::Obstacle)
-                .count(),
+            map_lab.iter().filter(|&l| *l == Location::Obstacle).count(),
             8
         );
-        assert_eq!(guard.pos.0, 4);
-        assert_eq!(guard.pos.1, 6);
-        assert_eq!(guard.facing, Direction::Up);
+        assert_eq!(guard.pos.x, 4);
+        assert_eq!(guard.pos.y, 6);
+        assert_eq!(guard.facing, Direction::North);
+    }
+
+    #[test]
+    fn parse_needs_exactly_one_guard() {
+        let error = parse("..#\n...\n").unwrap_err();
+        assert_eq!(error.expected, "a guard somewhere on the map");
+        let error = parse(".^#\n.>.\n").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 2));
+        assert_eq!(error.snippet, ">");
     }
 
     #[test]
     fn test_map_access_wide_line() {
-        let map_lab = MapLab {
-            height: 1,
-            width: 5,
-            buffer: vec![
+        let map_lab = MapLab::from_vec(
+            5,
+            1,
+            vec![
                 Location::Clear,
                 Location::Obstacle,
                 Location::Clear,
                 Location::Clear,
                 Location::Clear,
             ],
-        };
-        assert_eq!(map_lab.get(&(0, 0)), Some(Location::Clear));
-        assert_eq!(map_lab.get(&(1, 0)), Some(Location::Obstacle));
-        assert_eq!(map_lab.get(&(2, 0)), Some(Location::Clear));
-        assert_eq!(map_lab.get(&(3, 0)), Some(Location::Clear));
-        assert_eq!(map_lab.get(&(4, 0)), Some(Location::Clear));
-        assert_eq!(map_lab.get(&(5, 0)), None);
-        assert_eq!(map_lab.get(&(0, 1)), None);
-        assert_eq!(map_lab.get(&(1, 1)), None);
+        );
+        assert_eq!(map_lab.get(Point::new(0, 0)), Some(&Location::Clear));
+        assert_eq!(map_lab.get(Point::new(1, 0)), Some(&Location::Obstacle));
+        assert_eq!(map_lab.get(Point::new(2, 0)), Some(&Location::Clear));
+        assert_eq!(map_lab.get(Point::new(3, 0)), Some(&Location::Clear));
+        assert_eq!(map_lab.get(Point::new(4, 0)), Some(&Location::Clear));
+        assert_eq!(map_lab.get(Point::new(5, 0)), None);
+        assert_eq!(map_lab.get(Point::new(0, 1)), None);
+        assert_eq!(map_lab.get(Point::new(1, 1)), None);
     }
 
     #[test]
     fn test_map_access_tall_line() {
-        let map_lab = MapLab {
-            height: 3,
-            width: 1,
-            buffer: vec![Location::Obstacle, Location::Clear, Location::Obstacle],
-        };
-        assert_eq!(map_lab.get(&(0, 0)), Some(Location::Obstacle));
-        assert_eq!(map_lab.get(&(0, 1)), Some(Location::Clear));
-        assert_eq!(map_lab.get(&(0, 2)), Some(Location::Obstacle));
-        assert_eq!(map_lab.get(&(0, 3)), None);
-        assert_eq!(map_lab.get(&(1, 0)), None);
-        assert_eq!(map_lab.get(&(1, 1)), None);
+        let map_lab = MapLab::from_vec(
+            1,
+            3,
+            vec![Location::Obstacle, Location::Clear, Location::Obstacle],
+        );
+        assert_eq!(map_lab.get(Point::new(0, 0)), Some(&Location::Obstacle));
+        assert_eq!(map_lab.get(Point::new(0, 1)), Some(&Location::Clear));
+        assert_eq!(map_lab.get(Point::new(0, 2)), Some(&Location::Obstacle));
+        assert_eq!(map_lab.get(Point::new(0, 3)), None);
+        assert_eq!(map_lab.get(Point::new(1, 0)), None);
+        assert_eq!(map_lab.get(Point::new(1, 1)), None);
     }
 
     #[test]
     fn test_map_access_small_rectangle() {
-        let map_lab = MapLab {
-            height: 2,
-            width: 3,
-            buffer: vec![
+        let map_lab = MapLab::from_vec(
+            3,
+            2,
+            vec![
                 Location::Clear,
                 Location::Obstacle,
                 Location::Clear,
@@ -402,42 +298,38 @@ mod tests {
                 Location::Clear,
                 Location::Obstacle,
             ],
-        };
-        assert_eq!(map_lab.get(&(0, 0)), Some(Location::Clear));
-        assert_eq!(map_lab.get(&(1, 0)), Some(Location::Obstacle));
-        assert_eq!(map_lab.get(&(2, 0)), Some(Location::Clear));
-        assert_eq!(map_lab.get(&(0, 1)), Some(Location::Obstacle));
-        assert_eq!(map_lab.get(&(1, 1)), Some(Location::Clear));
-        assert_eq!(map_lab.get(&(2, 1)), Some(Location::Obstacle));
-        assert_eq!(map_lab.get(&(3, 0)), None);
-        assert_eq!(map_lab.get(&(0, 2)), None);
-        assert_eq!(map_lab.get(&(1, 2)), None);
-        assert_eq!(map_lab.get(&(3, 1)), None);
+        );
+        assert_eq!(map_lab.get(Point::new(0, 0)), Some(&Location::Clear));
+        assert_eq!(map_lab.get(Point::new(1, 0)), Some(&Location::Obstacle));
+        assert_eq!(map_lab.get(Point::new(2, 0)), Some(&Location::Clear));
+        assert_eq!(map_lab.get(Point::new(0, 1)), Some(&Location::Obstacle));
+        assert_eq!(map_lab.get(Point::new(1, 1)), Some(&Location::Clear));
+        assert_eq!(map_lab.get(Point::new(2, 1)), Some(&Location::Obstacle));
+        assert_eq!(map_lab.get(Point::new(3, 0)), None);
+        assert_eq!(map_lab.get(Point::new(0, 2)), None);
+        assert_eq!(map_lab.get(Point::new(1, 2)), None);
+        assert_eq!(map_lab.get(Point::new(3, 1)), None);
     }
 
     #[test]
     fn test_map_until_end_wide_line() {
-        let map_lab = MapLab {
-            height: 1,
-            width: 5,
-            buffer: vec![Location::Clear; 5],
-        };
+        let map_lab = MapLab::from_vec(5, 1, vec![Location::Clear; 5]);
         assert_eq!(
-            map_lab.until_obstacle(&(0, 0), Direction::Right),
-            ((4, 0), StopReason::EndOfMap)
+            map_lab.until_obstacle(Point::new(0, 0), Direction::East),
+            (Point::new(4, 0), StopReason::EndOfMap)
         );
         assert_eq!(
-            map_lab.until_obstacle(&(4, 0), Direction::Left),
-            ((0, 0), StopReason::EndOfMap)
+            map_lab.until_obstacle(Point::new(4, 0), Direction::West),
+            (Point::new(0, 0), StopReason::EndOfMap)
         );
     }
 
     #[test]
     fn test_map_until_obstacle_wide_line() {
-        let map_lab = MapLab {
-            height: 1,
-            width: 6,
-            buffer: vec![
+        let map_lab = MapLab::from_vec(
+            6,
+            1,
+            vec![
                 Location::Clear,
                 Location::Clear,
                 Location::Obstacle,
@@ -445,63 +337,59 @@ mod tests {
                 Location::Clear,
                 Location::Clear,
             ],
-        };
+        );
         assert_eq!(
-            map_lab.until_obstacle(&(4, 0), Direction::Left),
-            ((3, 0), StopReason::Obstacle)
+            map_lab.until_obstacle(Point::new(4, 0), Direction::West),
+            (Point::new(3, 0), StopReason::Obstacle)
         );
         assert_eq!(
-            map_lab.until_obstacle(&(0, 0), Direction::Right),
-            ((1, 0), StopReason::Obstacle)
+            map_lab.until_obstacle(Point::new(0, 0), Direction::East),
+            (Point::new(1, 0), StopReason::Obstacle)
         );
     }
 
     #[test]
     fn test_map_until_end_tall_line() {
-        let map_lab = MapLab {
-            height: 3,
-            width: 1,
-            buffer: vec![Location::Clear; 3],
-        };
+        let map_lab = MapLab::from_vec(1, 3, vec![Location::Clear; 3]);
         assert_eq!(
-            map_lab.until_obstacle(&(0, 1), Direction::Down),
-            ((0, 2), StopReason::EndOfMap)
+            map_lab.until_obstacle(Point::new(0, 1), Direction::South),
+            (Point::new(0, 2), StopReason::EndOfMap)
         );
         assert_eq!(
-            map_lab.until_obstacle(&(0, 1), Direction::Up),
-            ((0, 0), StopReason::EndOfMap)
+            map_lab.until_obstacle(Point::new(0, 1), Direction::North),
+            (Point::new(0, 0), StopReason::EndOfMap)
         );
     }
 
     #[test]
     fn test_map_until_obstacle_tall_line() {
-        let map_lab = MapLab {
-            height: 5,
-            width: 1,
-            buffer: vec![
+        let map_lab = MapLab::from_vec(
+            1,
+            5,
+            vec![
                 Location::Clear,
                 Location::Clear,
                 Location::Obstacle,
                 Location::Clear,
                 Location::Clear,
             ],
-        };
+        );
         assert_eq!(
-            map_lab.until_obstacle(&(0, 4), Direction::Up),
-            ((0, 3), StopReason::Obstacle)
+            map_lab.until_obstacle(Point::new(0, 4), Direction::North),
+            (Point::new(0, 3), StopReason::Obstacle)
         );
         assert_eq!(
-            map_lab.until_obstacle(&(0, 0), Direction::Down),
-            ((0, 1), StopReason::Obstacle)
+            map_lab.until_obstacle(Point::new(0, 0), Direction::South),
+            (Point::new(0, 1), StopReason::Obstacle)
         );
     }
 
     #[test]
     fn test_map_until_end_small_rectangle() {
-        let map_lab = MapLab {
-            height: 2,
-            width: 3,
-            buffer: vec![
+        let map_lab = MapLab::from_vec(
+            3,
+            2,
+            vec![
                 Location::Clear,
                 Location::Clear,
                 Location::Clear,
@@ -509,31 +397,31 @@ mod tests {
                 Location::Clear,
                 Location::Clear,
             ],
-        };
+        );
         assert_eq!(
-            map_lab.until_obstacle(&(0, 0), Direction::Right),
-            ((2, 0), StopReason::EndOfMap)
+            map_lab.until_obstacle(Point::new(0, 0), Direction::East),
+            (Point::new(2, 0), StopReason::EndOfMap)
         );
         assert_eq!(
-            map_lab.until_obstacle(&(2, 0), Direction::Left),
-            ((0, 0), StopReason::EndOfMap)
+            map_lab.until_obstacle(Point::new(2, 0), Direction::West),
+            (Point::new(0, 0), StopReason::EndOfMap)
         );
         assert_eq!(
-            map_lab.until_obstacle(&(0, 1), Direction::Right),
-            ((2, 1), StopReason::EndOfMap)
+            map_lab.until_obstacle(Point::new(0, 1), Direction::East),
+            (Point::new(2, 1), StopReason::EndOfMap)
         );
         assert_eq!(
-            map_lab.until_obstacle(&(2, 1), Direction::Left),
-            ((0, 1), StopReason::EndOfMap)
+            map_lab.until_obstacle(Point::new(2, 1), Direction::West),
+            (Point::new(0, 1), StopReason::EndOfMap)
         );
     }
 
     #[test]
     fn test_map_until_obstacle_small_rectangle() {
-        let map_lab = MapLab {
-            height: 3,
-            width: 4,
-            buffer: vec![
+        let map_lab = MapLab::from_vec(
+            4,
+            3,
+            vec![
                 Location::Clear,
                 Location::Clear,
                 Location::Obstacle,
@@ -547,35 +435,35 @@ mod tests {
                 Location::Clear,
                 Location::Obstacle,
             ],
-        };
+        );
         assert_eq!(
-            map_lab.until_obstacle(&(3, 0), Direction::Down),
-            ((3, 1), StopReason::Obstacle)
+            map_lab.until_obstacle(Point::new(3, 0), Direction::South),
+            (Point::new(3, 1), StopReason::Obstacle)
         );
         assert_eq!(
-            map_lab.until_obstacle(&(2, 2), Direction::Up),
-            ((2, 1), StopReason::Obstacle)
+            map_lab.until_obstacle(Point::new(2, 2), Direction::North),
+            (Point::new(2, 1), StopReason::Obstacle)
         );
         assert_eq!(
-            map_lab.until_obstacle(&(3, 1), Direction::Left),
-            ((1, 1), StopReason::Obstacle)
+            map_lab.until_obstacle(Point::new(3, 1), Direction::West),
+            (Point::new(1, 1), StopReason::Obstacle)
         );
         assert_eq!(
-            map_lab.until_obstacle(&(0, 2), Direction::Right),
-            ((2, 2), StopReason::Obstacle)
+            map_lab.until_obstacle(Point::new(0, 2), Direction::East),
+            (Point::new(2, 2), StopReason::Obstacle)
         );
         assert_eq!(
-            map_lab.until_obstacle(&(0, 0), Direction::Right),
-            ((1, 0), StopReason::Obstacle)
+            map_lab.until_obstacle(Point::new(0, 0), Direction::East),
+            (Point::new(1, 0), StopReason::Obstacle)
         );
         assert_eq!(
-            map_lab.until_obstacle(&(1, 1), Direction::Left),
-            ((1, 1), StopReason::Obstacle)
+            map_lab.until_obstacle(Point::new(1, 1), Direction::West),
+            (Point::new(1, 1), StopReason::Obstacle)
         );
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse(PART_1_EXAMPLE)), 6u64);
+        assert_eq!(part2(&parse(PART_1_EXAMPLE).unwrap()), 6u64);
     }
 }
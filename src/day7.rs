@@ -1,12 +1,17 @@
+//! Day 7: Bridge Repair
+
 use std::cmp::Ordering;
 
 use aoc_runner_derive::{aoc, aoc_generator};
 
+use crate::parsing::{parse_token, split_once, ParseError};
+
+/// A calibration equation, missing its operators
 #[derive(Debug, PartialEq)]
-struct CalibEq {
-    test_value: u64,
-    leftmost: u64,
-    other_operands: Vec<u64>,
+pub struct CalibEq {
+    pub test_value: u64,
+    pub leftmost: u64,
+    pub other_operands: Vec<u64>,
 }
 
 impl CalibEq {
@@ -22,20 +27,12 @@ impl CalibEq {
         }
     }
 
-    fn from_line(line: &str) -> CalibEq {
-        let mut part_it = line.split(':');
-        let test_value = part_it
-            .next()
-            .expect("no test value found (empty line?)")
-            .parse::<u64>()
-            .expect("test value is not a number");
-        let mut operands = part_it
-            .next_back()
-            .expect("no operands found")
-            .split_ascii_whitespace()
-            .map(|token| token.parse::<u64>().expect("NaN operand"));
-        debug_assert!(part_it.next().is_none());
-        CalibEq::from_operands(test_value, &mut operands)
+    fn from_line(input: &str, line: &str) -> Result<CalibEq, ParseError> {
+        let (test_value, operands) = parse_line(input, line)?;
+        Ok(CalibEq::from_operands(
+            test_value,
+            &mut operands.into_iter(),
+        ))
     }
 }
 
@@ -46,25 +43,27 @@ struct CalibEqCheckBackwards {
 }
 
 impl CalibEqCheckBackwards {
-    fn from_line(line: &str) -> CalibEqCheckBackwards {
-        let mut part_it = line.split(':');
-        let test_value = part_it
-            .next()
-            .expect("no test value found (empty line?)")
-            .parse::<u64>()
-            .expect("test value is not a number");
-        let operands = part_it
-            .next_back()
-            .expect("no operands found")
-            .split_ascii_whitespace()
-            .map(|token| token.parse::<u64>().expect("NaN operand"))
-            .collect();
-        debug_assert!(part_it.next().is_none());
-        CalibEqCheckBackwards {
+    fn from_line(input: &str, line: &str) -> Result<CalibEqCheckBackwards, ParseError> {
+        let (test_value, operands) = parse_line(input, line)?;
+        Ok(CalibEqCheckBackwards {
             test_value,
             operands,
-        }
+        })
+    }
+}
+
+/// Splits a line like `3267: 81 40 27` into the test value and the (non-empty) operands
+fn parse_line(input: &str, line: &str) -> Result<(u64, Vec<u64>), ParseError> {
+    let (test_value, operands) = split_once(input, line, ":", "a test value followed by ':'")?;
+    let test_value = parse_token(input, test_value, "a test value (a number)")?;
+    let operands: Vec<u64> = operands
+        .split_ascii_whitespace()
+        .map(|token| parse_token(input, token, "an operand (a number)"))
+        .collect::<Result<_, _>>()?;
+    if operands.is_empty() {
+        return Err(ParseError::after(input, line, "at least one operand"));
     }
+    Ok((test_value, operands))
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -93,7 +92,7 @@ impl Operator {
                 }
             }
             Operator::Mul => {
-                if target % operand != 0 {
+                if !target.is_multiple_of(operand) {
                     None
                 } else {
                     Some(target / operand)
@@ -197,24 +196,33 @@ fn is_possible_check_backwards(
     false
 }
 
+/// The calibration equations, each a test value and its operands
+pub type Input = Vec<CalibEq>;
+
+/// Parses one equation like `3267: 81 40 27` per line
 #[aoc_generator(day7, part2, check_fwd)]
 #[aoc_generator(day7, part1, check_fwd)]
-fn parse(input: &str) -> Vec<CalibEq> {
-    input.trim().lines().map(CalibEq::from_line).collect()
+pub fn parse(input: &str) -> Result<Input, ParseError> {
+    input
+        .trim()
+        .lines()
+        .map(|line| CalibEq::from_line(input, line))
+        .collect()
 }
 
 #[aoc_generator(day7, part2, check_backwards)]
 #[aoc_generator(day7, part1, check_backwards)]
-fn parse_check_backwards(input: &str) -> Vec<CalibEqCheckBackwards> {
+fn parse_check_backwards(input: &str) -> Result<Vec<CalibEqCheckBackwards>, ParseError> {
     input
         .trim()
         .lines()
-        .map(CalibEqCheckBackwards::from_line)
+        .map(|line| CalibEqCheckBackwards::from_line(input, line))
         .collect()
 }
 
+/// The sum of the test values that `+` and `*` can produce from the operands
 #[aoc(day7, part1, check_fwd)]
-fn part1(input: &[CalibEq]) -> u64 {
+pub fn part1(input: &[CalibEq]) -> u64 {
     input
         .iter()
         .filter_map(|calib_eq| {
@@ -250,8 +258,9 @@ fn part1_check_backwards(input: &[CalibEqCheckBackwards]) -> u64 {
         .sum()
 }
 
+/// The sum of the test values that `+`, `*` and `||` can produce from the operands
 #[aoc(day7, part2, check_fwd)]
-fn part2(input: &[CalibEq]) -> u64 {
+pub fn part2(input: &[CalibEq]) -> u64 {
     input
         .iter()
         .filter_map(|calib_eq| {
@@ -307,13 +316,13 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse(PART_1_EXAMPLE_INPUT)), 3749u64);
+        assert_eq!(part1(&parse(PART_1_EXAMPLE_INPUT).unwrap()), 3749u64);
     }
 
     #[test]
     fn part1_example_check_back() {
         assert_eq!(
-            part1_check_backwards(&parse_check_backwards(PART_1_EXAMPLE_INPUT)),
+            part1_check_backwards(&parse_check_backwards(PART_1_EXAMPLE_INPUT).unwrap()),
             3749u64
         );
     }
@@ -348,7 +357,7 @@ mod tests {
             },
         ];
         for (line, exp) in PART_1_EXAMPLE_INPUT.trim().lines().zip(expect) {
-            assert_eq!(parse(line), [exp]);
+            assert_eq!(parse(line).unwrap(), [exp]);
         }
     }
 
@@ -377,19 +386,31 @@ mod tests {
             },
         ];
         for (line, exp) in PART_1_EXAMPLE_INPUT.trim().lines().zip(expect) {
-            assert_eq!(parse_check_backwards(line), [exp]);
+            assert_eq!(parse_check_backwards(line).unwrap(), [exp]);
         }
     }
 
+    #[test]
+    fn parse_malformed_equations() {
+        let error = parse("190: 10 19\n3267 81 40 27\n").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 1));
+        let error = parse_check_backwards("190: 10 19\n83: 17 x\n").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 8));
+        let error = parse("190:\n").unwrap_err();
+        assert_eq!(error.expected, "at least one operand");
+        let error = parse("190: 10: 19\n").unwrap_err();
+        assert_eq!(error.snippet, "10:");
+    }
+
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse(PART_1_EXAMPLE_INPUT)), 11387u64);
+        assert_eq!(part2(&parse(PART_1_EXAMPLE_INPUT).unwrap()), 11387u64);
     }
 
     #[test]
     fn part2_example_check_back() {
         assert_eq!(
-            part2_check_backwards(&parse_check_backwards(PART_1_EXAMPLE_INPUT)),
+            part2_check_backwards(&parse_check_backwards(PART_1_EXAMPLE_INPUT).unwrap()),
             11387u64
         );
     }
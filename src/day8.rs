@@ -1,11 +1,15 @@
+//! Day 8: Resonant Collinearity
+
 use rustc_hash::{FxHashMap, FxHashSet};
 
 use aoc_runner_derive::{aoc, aoc_generator};
 
+use crate::parsing::ParseError;
+
 #[derive(Debug, PartialEq, Eq, Hash)]
-struct Point<Num> {
-    x: Num,
-    y: Num,
+pub struct Point<Num> {
+    pub x: Num,
+    pub y: Num,
 }
 
 #[derive(PartialEq, Eq)]
@@ -14,22 +18,43 @@ enum Part {
     Two,
 }
 
+/// Antenna positions by frequency
+pub type Antennas = FxHashMap<char, Vec<Point<usize>>>;
+
+/// The antennas by frequency, and the bottom-right corner of the map
+pub type Input = (Antennas, Point<usize>);
+
+/// Parses the map of `.` and antennas (letters and digits)
 #[aoc_generator(day8)]
-fn parse(input: &str) -> (FxHashMap<char, Vec<Point<usize>>>, Point<usize>) {
+pub fn parse(input: &str) -> Result<Input, ParseError> {
     let input = input.trim();
     let mut points = FxHashMap::default();
     let mut max_point = Point { x: 0, y: 0 };
     for (y, line) in input.lines().enumerate() {
         max_point.y = y;
+        let mut line_width = 0;
         for (x, character) in line.chars().enumerate() {
-            max_point.x = x;
+            line_width = x + 1;
             if character.is_alphanumeric() {
                 let entry = points.entry(character).or_insert_with(Vec::new);
                 entry.push(Point { x, y });
             }
         }
+        // the map is assumed to be rectangular, with the extent given by the first line
+        if y == 0 {
+            max_point.x = line_width.saturating_sub(1);
+        } else if line_width != max_point.x + 1 {
+            return Err(ParseError::at(
+                input,
+                line,
+                format!(
+                    "a line of the same width as the first ({})",
+                    max_point.x + 1
+                ),
+            ));
+        }
     }
-    (points, max_point)
+    Ok((points, max_point))
 }
 
 fn find_antinodes(
@@ -72,13 +97,15 @@ fn find_antinodes(
     points_with_antinodes
 }
 
+/// The number of distinct locations on the map with an antinode
 #[aoc(day8, part1)]
-fn part1((antennas, max_point): &(FxHashMap<char, Vec<Point<usize>>>, Point<usize>)) -> u64 {
+pub fn part1((antennas, max_point): &(FxHashMap<char, Vec<Point<usize>>>, Point<usize>)) -> u64 {
     find_antinodes(antennas, max_point, 1, Part::One).len() as u64
 }
 
+/// The number of distinct locations on the map with an antinode, counting resonant harmonics
 #[aoc(day8, part2)]
-fn part2((antennas, max_point): &(FxHashMap<char, Vec<Point<usize>>>, Point<usize>)) -> u64 {
+pub fn part2((antennas, max_point): &(FxHashMap<char, Vec<Point<usize>>>, Point<usize>)) -> u64 {
     find_antinodes(antennas, max_point, max_point.x.max(max_point.y), Part::Two).len() as u64
 }
 
@@ -105,12 +132,12 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse(PART_1_EXAMPLE)), 14);
+        assert_eq!(part1(&parse(PART_1_EXAMPLE).unwrap()), 14);
     }
 
     #[test]
     fn part1_parse_example() {
-        let (parsed, max_point) = parse(PART_1_EXAMPLE);
+        let (parsed, max_point) = parse(PART_1_EXAMPLE).unwrap();
         assert_eq!(parsed.len(), 2);
         assert_eq!(
             parsed[&'0'],
@@ -146,7 +173,7 @@ mod tests {
             ..........
             ..........
         "};
-        assert_eq!(part1(&parse(input)), 2);
+        assert_eq!(part1(&parse(input).unwrap()), 2);
     }
 
     #[test]
@@ -163,11 +190,18 @@ mod tests {
             ..........
             ..........
         "};
-        assert_eq!(part1(&parse(input)), 4);
+        assert_eq!(part1(&parse(input).unwrap()), 4);
+    }
+
+    #[test]
+    fn parse_ragged_map() {
+        let error = parse("..a.\n.a..\n..\n").unwrap_err();
+        assert_eq!((error.line, error.column), (3, 1));
+        assert_eq!(error.expected, "a line of the same width as the first (4)");
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse(PART_1_EXAMPLE)), 34);
+        assert_eq!(part2(&parse(PART_1_EXAMPLE).unwrap()), 34);
     }
 }
@@ -1,27 +1,38 @@
+//! Day 9: Disk Fragmenter
+
 use std::iter;
 
 use aoc_runner_derive::{aoc, aoc_generator};
 
+use crate::parsing::ParseError;
+
+/// The length of a file or of free space, in blocks
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum DenseDiskValue {
+pub enum DenseDiskValue {
     Empty(u8),
     Full(u8),
 }
 
+/// The disk map, alternating between files and free space
+pub type Input = Vec<DenseDiskValue>;
+
+/// Parses the dense disk map of single digits
 #[aoc_generator(day9)]
-fn parse(input: &str) -> Vec<DenseDiskValue> {
-    input
-        .trim()
-        .chars()
-        .map(|c| c.to_digit(10).expect("Invalid digit") as u8)
-        .enumerate()
-        .filter_map(|(idx, n_blocks)| match (idx % 2, n_blocks) {
-            (_, 0) => None,
-            (0, n_blocks) => Some(DenseDiskValue::Full(n_blocks)),
-            (1, n_blocks) => Some(DenseDiskValue::Empty(n_blocks)),
+pub fn parse(input: &str) -> Result<Input, ParseError> {
+    let input = input.trim();
+    let mut dense = Vec::with_capacity(input.len());
+    for (idx, (offset, c)) in input.char_indices().enumerate() {
+        let n_blocks = c.to_digit(10).ok_or_else(|| {
+            ParseError::at(input, &input[offset..offset + c.len_utf8()], "a digit")
+        })? as u8;
+        match (idx % 2, n_blocks) {
+            (_, 0) => (),
+            (0, n_blocks) => dense.push(DenseDiskValue::Full(n_blocks)),
+            (1, n_blocks) => dense.push(DenseDiskValue::Empty(n_blocks)),
             _ => unreachable!(),
-        })
-        .collect()
+        }
+    }
+    Ok(dense)
 }
 
 fn expand_dense_representation(input: &[DenseDiskValue]) -> Vec<Option<u64>> {
@@ -31,20 +42,20 @@ fn expand_dense_representation(input: &[DenseDiskValue]) -> Vec<Option<u64>> {
     for val in input {
         match val {
             DenseDiskValue::Full(n_blocks) => {
-                sparse_disk_map
-                    .extend(iter::repeat(Some(current_file_id)).take(*n_blocks as usize));
+                sparse_disk_map.extend(iter::repeat_n(Some(current_file_id), *n_blocks as usize));
                 current_file_id += 1;
             }
             DenseDiskValue::Empty(n_blocks) => {
-                sparse_disk_map.extend(iter::repeat(None).take(*n_blocks as usize));
+                sparse_disk_map.extend(iter::repeat_n(None, *n_blocks as usize));
             }
         }
     }
     sparse_disk_map
 }
 
+/// The filesystem checksum after compacting the disk one block at a time
 #[aoc(day9, part1)]
-fn part1(input: &[DenseDiskValue]) -> u64 {
+pub fn part1(input: &[DenseDiskValue]) -> u64 {
     let sparse_disk_map = expand_dense_representation(input);
 
     // this one walks the disk map from the start, to either copy existing file IDs,
@@ -93,8 +104,9 @@ fn part1(input: &[DenseDiskValue]) -> u64 {
         })
 }
 
+/// The filesystem checksum after compacting the disk one whole file at a time
 #[aoc(day9, part2)]
-fn part2(input: &[DenseDiskValue]) -> u64 {
+pub fn part2(input: &[DenseDiskValue]) -> u64 {
     let mut disk_map = expand_dense_representation(input);
     let max_file_id = disk_map.iter().filter_map(|&id_opt| id_opt).max().unwrap();
     let unique_file_ids = (0..(max_file_id + 1)).rev();
@@ -152,14 +164,14 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse(PART_1_EXAMPLE)), 1928u64);
+        assert_eq!(part1(&parse(PART_1_EXAMPLE).unwrap()), 1928u64);
     }
 
     #[test]
     fn part_1_example_parse() {
         use DenseDiskValue::*;
         assert_eq!(
-            parse(PART_1_EXAMPLE),
+            parse(PART_1_EXAMPLE).unwrap(),
             vec![
                 Full(2),
                 Empty(3),
@@ -184,8 +196,15 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_non_digit() {
+        let error = parse("23331x3121\n").unwrap_err();
+        assert_eq!((error.line, error.column), (1, 6));
+        assert_eq!(error.snippet, "x");
+    }
+
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse(PART_1_EXAMPLE)), 2858);
+        assert_eq!(part2(&parse(PART_1_EXAMPLE).unwrap()), 2858);
     }
 }
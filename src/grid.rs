@@ -0,0 +1,760 @@
+//! A dense, row-major 2D grid shared by the map-based puzzles,
+//! together with the points, vectors and directions used to walk it.
+//!
+//! Points are `(x, y)` with `x` growing eastwards (along a row)
+//! and `y` growing southwards (down the columns), matching how the puzzle maps are printed.
+
+use std::{
+    fmt::{self, Display, Formatter},
+    ops::{Add, Index, IndexMut, Mul},
+};
+
+use crate::parsing::ParseError;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    data: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T: Clone> Grid<T> {
+    /// A `width` x `height` grid with every cell set to `fill`
+    pub fn new(width: usize, height: usize, fill: T) -> Self {
+        Self {
+            data: vec![fill; width * height],
+            width,
+            height,
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    /// Wraps row-major `data` as a `width` x `height` grid
+    ///
+    /// Panics if the length of `data` does not match the dimensions.
+    pub fn from_vec(width: usize, height: usize, data: Vec<T>) -> Self {
+        assert_eq!(
+            data.len(),
+            width * height,
+            "data does not fit a {width}x{height} grid"
+        );
+        Self {
+            data,
+            width,
+            height,
+        }
+    }
+
+    /// Parses a character map (one line per row), mapping each character to a cell
+    ///
+    /// `to_cell` receives the point and character of each cell, and returns `None`
+    /// for characters it does not expect, which fails the parse with `expected`
+    /// as the explanation. Lines of inconsistent length fail the parse as well.
+    pub fn from_char_map<F>(input: &str, expected: &str, mut to_cell: F) -> Result<Self, ParseError>
+    where
+        F: FnMut(Point, char) -> Option<T>,
+    {
+        let mut data = Vec::with_capacity(input.len());
+        let mut width = None;
+        let mut height = 0;
+        for (y, line) in input.lines().enumerate() {
+            let mut line_width = 0;
+            for (x, (offset, c)) in line.char_indices().enumerate() {
+                let cell = to_cell(Point { x, y }, c).ok_or_else(|| {
+                    ParseError::at(input, &line[offset..offset + c.len_utf8()], expected)
+                })?;
+                data.push(cell);
+                line_width += 1;
+            }
+            match width {
+                None => width = Some(line_width),
+                Some(width) if width != line_width => {
+                    return Err(ParseError::at(
+                        input,
+                        line,
+                        format!("a line of the same width as the first ({width})"),
+                    ));
+                }
+                Some(_) => (),
+            }
+            height = y + 1;
+        }
+        Ok(Self {
+            data,
+            width: width.unwrap_or(0),
+            height,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The number of cells in the grid
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        point.x < self.width && point.y < self.height
+    }
+
+    pub fn flat_index(&self, point: Point) -> Option<usize> {
+        if self.contains(point) {
+            Some(point.y * self.width + point.x)
+        } else {
+            None
+        }
+    }
+
+    pub fn point_index(&self, flat_index: usize) -> Option<Point> {
+        if flat_index >= self.data.len() {
+            return None;
+        }
+        Some(Point {
+            x: flat_index % self.width,
+            y: flat_index / self.width,
+        })
+    }
+
+    pub fn get(&self, point: Point) -> Option<&T> {
+        self.flat_index(point).map(|idx| &self.data[idx])
+    }
+
+    pub fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+        self.flat_index(point).map(|idx| &mut self.data[idx])
+    }
+
+    /// Steps from `from` by `by`, if that stays inside the grid
+    pub fn step(&self, from: Point, by: Vector) -> Option<Point> {
+        from.checked_add(by).filter(|&to| self.contains(to))
+    }
+
+    /// Steps from `from` by `by`, coming back in on the opposite edge when leaving the grid
+    ///
+    /// Panics for an empty grid.
+    pub fn step_wrapping(&self, from: Point, by: Vector) -> Point {
+        assert!(!self.is_empty(), "cannot step around an empty grid");
+        let wrap = |coordinate: usize, by: isize, len: usize| {
+            (coordinate as isize + by).rem_euclid(len as isize) as usize
+        };
+        Point {
+            x: wrap(from.x, by.dx, self.width),
+            y: wrap(from.y, by.dy, self.height),
+        }
+    }
+
+    /// The in-bounds orthogonal (4-connected) neighbours of `point`
+    pub fn neighbours(&self, point: Point) -> impl Iterator<Item = Point> + '_ {
+        Direction::ALL
+            .into_iter()
+            .filter_map(move |direction| self.step(point, direction.vector()))
+    }
+
+    /// The in-bounds orthogonal and diagonal (8-connected) neighbours of `point`
+    pub fn neighbours8(&self, point: Point) -> impl Iterator<Item = Point> + '_ {
+        Direction::ALL
+            .into_iter()
+            .map(Direction::vector)
+            .chain(Diagonal::ALL.into_iter().map(Diagonal::vector))
+            .filter_map(move |vector| self.step(point, vector))
+    }
+
+    /// All cells in row-major order
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        self.data.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut T> + ExactSizeIterator {
+        self.data.iter_mut()
+    }
+
+    /// All points of the grid in row-major order
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let width = self.width;
+        (0..self.data.len()).map(move |idx| Point {
+            x: idx % width,
+            y: idx / width,
+        })
+    }
+
+    /// All cells in row-major order, together with their points
+    pub fn enumerate(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.points().zip(self.data.iter())
+    }
+
+    /// The first point (in row-major order) whose cell satisfies `predicate`
+    pub fn position<P>(&self, predicate: P) -> Option<Point>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        self.data
+            .iter()
+            .position(predicate)
+            .and_then(|idx| self.point_index(idx))
+    }
+
+    /// A grid of the same shape, with every cell mapped through `f`
+    pub fn map<U, F>(&self, f: F) -> Grid<U>
+    where
+        F: FnMut(&T) -> U,
+    {
+        Grid {
+            data: self.data.iter().map(f).collect(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    /// The cells of row `y`, from west to east
+    pub fn row(&self, y: usize) -> &[T] {
+        assert!(y < self.height, "row {y} out of bounds");
+        &self.data[y * self.width..(y + 1) * self.width]
+    }
+
+    /// The rows of the grid, from north to south
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[T]> {
+        // chunks_exact(0) panics, and an empty grid has no rows anyway
+        self.data.chunks_exact(self.width.max(1))
+    }
+
+    /// The cells of column `x`, from north to south
+    pub fn column(&self, x: usize) -> impl DoubleEndedIterator<Item = &T> {
+        assert!(x < self.width, "column {x} out of bounds");
+        self.data.iter().skip(x).step_by(self.width)
+    }
+
+    /// The columns of the grid, from west to east
+    pub fn columns(&self) -> impl Iterator<Item = impl DoubleEndedIterator<Item = &T>> {
+        (0..self.width).map(|x| self.column(x))
+    }
+
+    /// Walks from `from` (inclusive) in steps of `towards`, until leaving the grid
+    pub fn ray(&self, from: Point, towards: Vector) -> Ray<'_, T> {
+        Ray {
+            grid: self,
+            next: Some(from).filter(|&from| self.contains(from)),
+            towards,
+        }
+    }
+
+    /// Walks the diagonal through `from` (inclusive) towards `towards`, until leaving the grid
+    pub fn diagonal(&self, from: Point, towards: Diagonal) -> Ray<'_, T> {
+        self.ray(from, towards.vector())
+    }
+
+    /// The diagonals running south-east, from the one in the south-west corner
+    /// to the one in the north-east corner, each from its north-western end
+    pub fn diagonals(&self) -> impl Iterator<Item = Ray<'_, T>> {
+        let starts = (0..self.height)
+            .rev()
+            .map(|y| Point::new(0, y))
+            .chain((1..self.width).map(|x| Point::new(x, 0)));
+        starts
+            .filter(|_| !self.is_empty())
+            .map(|start| self.diagonal(start, Diagonal::SE))
+    }
+
+    /// The diagonals running south-west, from the one in the north-west corner
+    /// to the one in the south-east corner, each from its north-eastern end
+    pub fn anti_diagonals(&self) -> impl Iterator<Item = Ray<'_, T>> {
+        let last_x = self.width.saturating_sub(1);
+        let starts = (0..self.width)
+            .map(|x| Point::new(x, 0))
+            .chain((1..self.height).map(move |y| Point::new(last_x, y)));
+        starts
+            .filter(|_| !self.is_empty())
+            .map(|start| self.diagonal(start, Diagonal::SW))
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point) -> &Self::Output {
+        debug_assert!(point.x < self.width, "x out of bounds");
+        debug_assert!(point.y < self.height, "y out of bounds");
+        &self.data[point.y * self.width + point.x]
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, point: Point) -> &mut Self::Output {
+        debug_assert!(point.x < self.width, "x out of bounds");
+        debug_assert!(point.y < self.height, "y out of bounds");
+        &mut self.data[point.y * self.width + point.x]
+    }
+}
+
+/// Indexing by the flat (row-major) index, see [`Grid::flat_index`]
+impl<T> Index<usize> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, flat_index: usize) -> &Self::Output {
+        &self.data[flat_index]
+    }
+}
+
+impl<T> IndexMut<usize> for Grid<T> {
+    fn index_mut(&mut self, flat_index: usize) -> &mut Self::Output {
+        &mut self.data[flat_index]
+    }
+}
+
+/// Renders the grid one row per line, each cell by its own `Display`
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Iterator over the cells along a straight line through a grid, see [`Grid::ray`]
+pub struct Ray<'a, T> {
+    grid: &'a Grid<T>,
+    next: Option<Point>,
+    towards: Vector,
+}
+
+impl<'a, T> Iterator for Ray<'a, T> {
+    type Item = (Point, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let point = self.next?;
+        self.next = self.grid.step(point, self.towards);
+        Some((point, &self.grid[point]))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl Point {
+    pub const fn new(x: usize, y: usize) -> Self {
+        Self { x, y }
+    }
+
+    /// `self + by`, unless that would leave the first quadrant
+    pub fn checked_add(&self, by: Vector) -> Option<Point> {
+        Some(Point {
+            x: self.x.checked_add_signed(by.dx)?,
+            y: self.y.checked_add_signed(by.dy)?,
+        })
+    }
+
+    pub fn manhattan_distance(&self, other: &Point) -> usize {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+}
+
+impl Add<Vector> for Point {
+    type Output = Point;
+
+    fn add(self, Vector { dx, dy }: Vector) -> Self::Output {
+        let x = self.x as isize + dx;
+        let y = self.y as isize + dy;
+        debug_assert!(x >= 0, "x underflow");
+        debug_assert!(y >= 0, "y underflow");
+        Point {
+            x: x as usize,
+            y: y as usize,
+        }
+    }
+}
+
+impl Display for Point {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Vector {
+    pub dx: isize,
+    pub dy: isize,
+}
+
+impl Vector {
+    pub const fn new(dx: isize, dy: isize) -> Self {
+        Self { dx, dy }
+    }
+}
+
+impl Add for Vector {
+    type Output = Vector;
+
+    fn add(self, rhs: Vector) -> Self::Output {
+        Vector {
+            dx: self.dx + rhs.dx,
+            dy: self.dy + rhs.dy,
+        }
+    }
+}
+
+impl Mul<usize> for Vector {
+    type Output = Vector;
+
+    fn mul(self, rhs: usize) -> Self::Output {
+        Vector {
+            dx: self.dx * rhs as isize,
+            dy: self.dy * rhs as isize,
+        }
+    }
+}
+
+/// The four orthogonal directions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Direction {
+    /// Clockwise, starting from north
+    pub const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    pub fn vector(self) -> Vector {
+        match self {
+            Direction::North => Vector { dx: 0, dy: -1 },
+            Direction::East => Vector { dx: 1, dy: 0 },
+            Direction::South => Vector { dx: 0, dy: 1 },
+            Direction::West => Vector { dx: -1, dy: 0 },
+        }
+    }
+
+    pub fn turn_left(self) -> Self {
+        match self {
+            Direction::North => Direction::West,
+            Direction::East => Direction::North,
+            Direction::South => Direction::East,
+            Direction::West => Direction::South,
+        }
+    }
+
+    pub fn turn_right(self) -> Self {
+        match self {
+            Direction::North => Direction::East,
+            Direction::East => Direction::South,
+            Direction::South => Direction::West,
+            Direction::West => Direction::North,
+        }
+    }
+
+    pub fn reverse(self) -> Self {
+        self.turn_left().turn_left()
+    }
+
+    /// The arrow (`^`, `>`, `v`, `<`) pointing this way
+    pub fn arrow(self) -> char {
+        match self {
+            Direction::North => '^',
+            Direction::East => '>',
+            Direction::South => 'v',
+            Direction::West => '<',
+        }
+    }
+
+    pub fn from_arrow(arrow: char) -> Option<Self> {
+        match arrow {
+            '^' => Some(Direction::North),
+            '>' => Some(Direction::East),
+            'v' => Some(Direction::South),
+            '<' => Some(Direction::West),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.arrow())
+    }
+}
+
+/// The four diagonal directions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Diagonal {
+    NE,
+    SE,
+    SW,
+    NW,
+}
+
+impl Diagonal {
+    /// Clockwise, starting from north-east
+    pub const ALL: [Diagonal; 4] = [Diagonal::NE, Diagonal::SE, Diagonal::SW, Diagonal::NW];
+
+    pub fn vector(self) -> Vector {
+        match self {
+            Diagonal::NE => Vector { dx: 1, dy: -1 },
+            Diagonal::SE => Vector { dx: 1, dy: 1 },
+            Diagonal::SW => Vector { dx: -1, dy: 1 },
+            Diagonal::NW => Vector { dx: -1, dy: -1 },
+        }
+    }
+
+    /// The orthogonal direction just clockwise of this diagonal
+    pub fn clockwise(self) -> Direction {
+        match self {
+            Diagonal::NE => Direction::East,
+            Diagonal::SE => Direction::South,
+            Diagonal::SW => Direction::West,
+            Diagonal::NW => Direction::North,
+        }
+    }
+
+    /// The orthogonal direction just counter-clockwise of this diagonal
+    pub fn counter_clockwise(self) -> Direction {
+        match self {
+            Diagonal::NE => Direction::North,
+            Diagonal::SE => Direction::East,
+            Diagonal::SW => Direction::South,
+            Diagonal::NW => Direction::West,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    const WIDE: &str = indoc! {"
+        abcd
+        efgh
+        ijkl
+    "};
+
+    fn wide() -> Grid<char> {
+        Grid::from_char_map(WIDE, "any character", |_, c| Some(c)).unwrap()
+    }
+
+    #[test]
+    fn parse_char_map() {
+        let grid = wide();
+        assert_eq!(grid.width(), 4);
+        assert_eq!(grid.height(), 3);
+        assert_eq!(grid[Point::new(0, 0)], 'a');
+        assert_eq!(grid[Point::new(3, 0)], 'd');
+        assert_eq!(grid[Point::new(0, 2)], 'i');
+        assert_eq!(grid[Point::new(2, 1)], 'g');
+        assert_eq!(grid.get(Point::new(4, 0)), None);
+        assert_eq!(grid.get(Point::new(0, 3)), None);
+    }
+
+    #[test]
+    fn parse_ragged_char_map() {
+        let error = Grid::from_char_map("abc\nde\n", "any character", |_, c| Some(c)).unwrap_err();
+        assert_eq!((error.line, error.column), (2, 1));
+        assert_eq!(error.snippet, "de");
+    }
+
+    #[test]
+    fn parse_unexpected_char() {
+        let error =
+            Grid::from_char_map("..\n.#\n", "'.'", |_, c| (c == '.').then_some(())).unwrap_err();
+        assert_eq!((error.line, error.column), (2, 2));
+        assert_eq!(error.snippet, "#");
+        assert_eq!(error.expected, "'.'");
+    }
+
+    #[test]
+    fn index_round_trip_non_square() {
+        let grid = wide();
+        for (flat_index, point) in grid.points().enumerate() {
+            assert_eq!(grid.flat_index(point), Some(flat_index));
+            assert_eq!(grid.point_index(flat_index), Some(point));
+            assert_eq!(grid[point], grid[flat_index]);
+        }
+        assert_eq!(grid.point_index(grid.len()), None);
+    }
+
+    /// Grids of random shapes (including single rows and columns), each cell holding its point
+    fn random_grids() -> impl Iterator<Item = Grid<Point>> {
+        let mut rng = crate::inputs::Rng::new(4);
+        (0..50).map(move |_| {
+            let (width, height) = (rng.range(1..=12) as usize, rng.range(1..=12) as usize);
+            let points = (0..height)
+                .flat_map(|y| (0..width).map(move |x| Point::new(x, y)))
+                .collect();
+            Grid::from_vec(width, height, points)
+        })
+    }
+
+    #[test]
+    fn index_round_trip_random_shapes() {
+        for grid in random_grids() {
+            let (width, height) = (grid.width(), grid.height());
+            for (flat_index, &point) in grid.iter().enumerate() {
+                assert_eq!(grid.flat_index(point), Some(flat_index));
+                assert_eq!(grid.point_index(flat_index), Some(point));
+                assert_eq!(grid[point], point);
+                assert_eq!(grid.row(point.y)[point.x], point);
+                assert_eq!(grid.column(point.x).nth(point.y), Some(&point));
+            }
+            assert_eq!(grid.flat_index(Point::new(width, 0)), None);
+            assert_eq!(grid.flat_index(Point::new(0, height)), None);
+            assert_eq!(grid.point_index(width * height), None);
+            assert_eq!(grid.rows().count(), height);
+            assert_eq!(grid.columns().count(), width);
+        }
+    }
+
+    #[test]
+    fn diagonals_cover_random_shapes() {
+        for grid in random_grids() {
+            for (diagonals, step) in [
+                (grid.diagonals().collect::<Vec<_>>(), Diagonal::SE),
+                (grid.anti_diagonals().collect(), Diagonal::SW),
+            ] {
+                assert_eq!(diagonals.len(), grid.width() + grid.height() - 1);
+                let mut seen = Grid::new(grid.width(), grid.height(), 0);
+                for diagonal in diagonals {
+                    let cells = diagonal.map(|(_, &point)| point).collect::<Vec<_>>();
+                    for pair in cells.windows(2) {
+                        assert_eq!(pair[0] + step.vector(), pair[1]);
+                    }
+                    for point in cells {
+                        seen[point] += 1;
+                    }
+                }
+                assert!(seen.iter().all(|&n_seen| n_seen == 1));
+            }
+        }
+        let grid = wide();
+        let diagonals = grid
+            .anti_diagonals()
+            .map(|diagonal| diagonal.map(|(_, &c)| c).collect::<String>())
+            .collect::<Vec<_>>();
+        assert_eq!(diagonals, ["a", "be", "cfi", "dgj", "hk", "l"]);
+        assert_eq!(Grid::<char>::from_vec(0, 0, vec![]).diagonals().count(), 0);
+    }
+
+    #[test]
+    fn unicode_char_map_round_trip() {
+        let mut rng = crate::inputs::Rng::new(22);
+        for _ in 0..20 {
+            let (width, height) = (rng.range(1..=8) as usize, rng.range(1..=8) as usize);
+            let map = (0..height)
+                .map(|_| {
+                    (0..width)
+                        .map(|_| *rng.choose(&['x', 'Ä', 'ß', '€', '🎄', 'ж']))
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>();
+            let input = map.join("\n") + "\n";
+            let grid = Grid::from_char_map(&input, "any character", |_, c| Some(c)).unwrap();
+            assert_eq!((grid.width(), grid.height()), (width, height));
+            assert_eq!(grid.to_string(), input);
+            for (y, line) in map.iter().enumerate() {
+                assert_eq!(grid.row(y).iter().collect::<String>(), *line);
+            }
+        }
+    }
+
+    #[test]
+    fn neighbours_at_the_edges() {
+        let grid = wide();
+        let corner: Vec<Point> = grid.neighbours(Point::new(0, 0)).collect();
+        assert_eq!(corner, [Point::new(1, 0), Point::new(0, 1)]);
+        let edge: Vec<Point> = grid.neighbours(Point::new(3, 1)).collect();
+        assert_eq!(edge, [Point::new(3, 0), Point::new(3, 2), Point::new(2, 1)]);
+        assert_eq!(grid.neighbours8(Point::new(0, 0)).count(), 3);
+        assert_eq!(grid.neighbours8(Point::new(1, 1)).count(), 8);
+        assert_eq!(grid.neighbours8(Point::new(3, 2)).count(), 3);
+    }
+
+    #[test]
+    fn step_wrapping_around_the_edges() {
+        let grid = wide();
+        let corner = Point::new(0, 0);
+        assert_eq!(
+            grid.step_wrapping(corner, Diagonal::NW.vector()),
+            Point::new(3, 2)
+        );
+        assert_eq!(
+            grid.step_wrapping(corner, Vector::new(9, 4)),
+            Point::new(1, 1)
+        );
+        assert_eq!(
+            grid.step_wrapping(Point::new(1, 1), Direction::East.vector()),
+            grid.step(Point::new(1, 1), Direction::East.vector())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn rows_and_columns() {
+        let grid = wide();
+        assert_eq!(grid.row(1), ['e', 'f', 'g', 'h']);
+        assert_eq!(grid.rows().count(), 3);
+        assert_eq!(grid.column(2).collect::<String>(), "cgk");
+        assert_eq!(
+            grid.columns()
+                .map(|column| column.collect::<String>())
+                .collect::<Vec<_>>(),
+            ["aei", "bfj", "cgk", "dhl"]
+        );
+    }
+
+    #[test]
+    fn rays_stop_at_the_edge() {
+        let grid = wide();
+        let diagonal: String = grid
+            .diagonal(Point::new(0, 0), Diagonal::SE)
+            .map(|(_, &c)| c)
+            .collect();
+        assert_eq!(diagonal, "afk");
+        let anti_diagonal: String = grid
+            .diagonal(Point::new(3, 0), Diagonal::SW)
+            .map(|(_, &c)| c)
+            .collect();
+        assert_eq!(anti_diagonal, "dgj");
+        let west: Vec<Point> = grid
+            .ray(Point::new(2, 2), Direction::West.vector())
+            .map(|(point, _)| point)
+            .collect();
+        assert_eq!(west, [Point::new(2, 2), Point::new(1, 2), Point::new(0, 2)]);
+        assert_eq!(grid.ray(Point::new(4, 0), Vector::new(1, 0)).count(), 0);
+    }
+
+    #[test]
+    fn display_round_trip() {
+        assert_eq!(wide().to_string(), WIDE);
+    }
+
+    #[test]
+    fn turning() {
+        for direction in Direction::ALL {
+            assert_eq!(direction.turn_left().turn_right(), direction);
+            assert_eq!(direction.reverse().reverse(), direction);
+            assert_eq!(Direction::from_arrow(direction.arrow()), Some(direction));
+        }
+        assert_eq!(Direction::North.turn_right(), Direction::East);
+        assert_eq!(Direction::North.reverse(), Direction::South);
+    }
+}
@@ -0,0 +1,626 @@
+//! Random but valid puzzle inputs, for fuzzing the parsers and cross-checking the solvers.
+//!
+//! The inputs follow the format and the (stated and unstated) promises of the real inputs,
+//! so that every solver should be able to answer them: the guard on day 6 leaves the lab,
+//! the claw machines on day 13 have independent buttons, the program on day 17 has a quine,
+//! and so on. They are reproducible from the seed alone, by way of a small built-in [`Rng`].
+
+use std::ops::RangeInclusive;
+
+use itertools::Itertools;
+
+use crate::{
+    grid::{Direction, Grid, Point},
+    params::{self, Day14Params, Day18Params, Params},
+};
+
+/// A small, seeded pseudo-random number generator (SplitMix64)
+///
+/// Not for cryptography, but fast, and the same sequence for the same seed on every platform.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `range` (with a negligible bias towards the low end for huge ranges)
+    pub fn range(&mut self, range: RangeInclusive<u64>) -> u64 {
+        let (start, end) = range.into_inner();
+        assert!(start <= end, "empty range {start}..={end}");
+        match (end - start).checked_add(1) {
+            Some(span) => start + self.next_u64() % span,
+            None => self.next_u64(),
+        }
+    }
+
+    /// An index below `n`
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "nothing to choose from");
+        self.range(0..=n as u64 - 1) as usize
+    }
+
+    /// `true` with probability `p`
+    pub fn chance(&mut self, p: f64) -> bool {
+        ((self.next_u64() >> 11) as f64) < p * (1u64 << 53) as f64
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+/// A random input for `day`, or `None` if there is no solver for that day (yet)
+///
+/// `size` scales the input, roughly as the number of records (lines, machines, robots, ...)
+/// or as the side of a map, but inputs have a minimum size where the puzzle needs one.
+/// The inputs for days 14 and 18 fit the current [`params`], and on day 18 the number of
+/// bytes is given by those alone.
+pub fn generate(day: u8, seed: u64, size: usize) -> Option<String> {
+    generate_with(day, seed, size, params::current())
+}
+
+/// The same, for the given puzzle parameters
+pub fn generate_with(day: u8, seed: u64, size: usize, params: Params) -> Option<String> {
+    let rng = &mut Rng::new(seed);
+    let size = size.max(1);
+    let input = match day {
+        1 => day1(rng, size),
+        2 => day2(rng, size),
+        3 => day3(rng, size),
+        4 => day4(rng, size),
+        5 => day5(rng, size),
+        6 => day6(rng, size),
+        7 => day7(rng, size),
+        8 => day8(rng, size),
+        9 => day9(rng, size),
+        10 => day10(rng, size),
+        11 => day11(rng, size),
+        12 => day12(rng, size),
+        13 => day13(rng, size),
+        14 => day14(rng, size, params.day14),
+        15 => day15(rng, size),
+        16 => day16(rng, size),
+        17 => day17(rng),
+        18 => day18(rng, params.day18),
+        19 => day19(rng, size),
+        20 => day20(rng, size),
+        21 => day21(rng),
+        _ => return None,
+    };
+    Some(format!("{}\n", input.trim_end()))
+}
+
+fn day1(rng: &mut Rng, size: usize) -> String {
+    // draw some IDs from a shared pool, so that the similarity score is not just 0
+    let pool = (0..size).map(|_| rng.range(10000..=99999)).collect_vec();
+    let location_id = |rng: &mut Rng| {
+        if rng.chance(0.5) {
+            *rng.choose(&pool)
+        } else {
+            rng.range(10000..=99999)
+        }
+    };
+    (0..size)
+        .map(|_| format!("{}   {}", location_id(rng), location_id(rng)))
+        .join("\n")
+}
+
+fn day2(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let n_levels = rng.range(5..=8);
+            let increasing = rng.chance(0.5);
+            let mut level = rng.range(20..=80);
+            let mut report = vec![level];
+            for _ in 1..n_levels {
+                // mostly safe steps, but sometimes none, too large or the wrong way
+                let step = match rng.below(10) {
+                    0 => 0,
+                    1 => rng.range(4..=7),
+                    _ => rng.range(1..=3),
+                };
+                level = if increasing != rng.chance(0.05) {
+                    level + step
+                } else {
+                    level.saturating_sub(step)
+                };
+                report.push(level);
+            }
+            report.iter().join(" ")
+        })
+        .join("\n")
+}
+
+fn day3(rng: &mut Rng, size: usize) -> String {
+    const NOISE: &[&str] = &[
+        "mul(4*",
+        "mul ( 2 , 4 )",
+        "mul[3,7]",
+        "mul(12,)",
+        "mul(1234,5)",
+        "?(12,34)",
+        "do",
+        "don't",
+        "from()",
+        "what()",
+        "select(",
+        "where()",
+        "how()",
+        ")",
+        " ",
+        "+",
+        "%",
+        "&",
+        "#",
+        "!",
+        "@",
+        "^",
+        "'",
+    ];
+    let mut memory = String::new();
+    for _ in 0..size {
+        match rng.below(12) {
+            0 => memory.push_str("do()"),
+            1 => memory.push_str("don't()"),
+            2..=4 => memory.push_str(&format!(
+                "mul({},{})",
+                rng.range(1..=999),
+                rng.range(1..=999)
+            )),
+            5 if rng.chance(0.1) => memory.push('\n'),
+            _ => memory.push_str(rng.choose::<&str>(NOISE)),
+        }
+    }
+    memory
+}
+
+fn day4(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            (0..size)
+                .map(|_| *rng.choose(&['X', 'M', 'A', 'S']))
+                .collect::<String>()
+        })
+        .join("\n")
+}
+
+fn day5(rng: &mut Rng, size: usize) -> String {
+    // like the real inputs, there is a rule for every pair of pages, all from one hidden order
+    let mut order = (10..=99).collect_vec();
+    rng.shuffle(&mut order);
+    order.truncate((size + 4).min(49));
+    let mut rules = order.iter().tuple_combinations::<(_, _)>().collect_vec();
+    rng.shuffle(&mut rules);
+    let max_half = ((order.len() as u64 - 1) / 2).min(11);
+    let updates = (0..size)
+        .map(|_| {
+            let n_pages = 2 * rng.range(1..=max_half) as usize + 1;
+            let mut update = order.clone();
+            rng.shuffle(&mut update);
+            update.truncate(n_pages);
+            if rng.chance(0.5) {
+                update.sort_by_key(|page| order.iter().position(|p| p == page));
+            }
+            update.iter().join(",")
+        })
+        .join("\n");
+    let rules = rules
+        .iter()
+        .map(|(before, after)| format!("{before}|{after}"))
+        .join("\n");
+    format!("{rules}\n\n{updates}")
+}
+
+fn day6(rng: &mut Rng, size: usize) -> String {
+    // the guard must leave the lab, so keep drawing maps until they do
+    loop {
+        let mut lab = Grid::new(size, size, '.');
+        for cell in lab.iter_mut() {
+            if rng.chance(0.1) {
+                *cell = '#';
+            }
+        }
+        let guard = Point::new(rng.below(size), rng.below(size));
+        lab[guard] = '.';
+        let facing = *rng.choose(&Direction::ALL);
+        if guard_leaves(&lab, guard, facing) {
+            lab[guard] = facing.arrow();
+            return lab.to_string();
+        }
+    }
+}
+
+fn guard_leaves(lab: &Grid<char>, mut guard: Point, mut facing: Direction) -> bool {
+    let mut turns = Vec::new();
+    while let Some(ahead) = lab.step(guard, facing.vector()) {
+        if lab[ahead] != '#' {
+            guard = ahead;
+        } else if turns.contains(&(guard, facing)) {
+            return false;
+        } else {
+            turns.push((guard, facing));
+            facing = facing.turn_right();
+        }
+    }
+    true
+}
+
+fn day7(rng: &mut Rng, size: usize) -> String {
+    // at most 8 two-digit operands, so even concatenating all of them fits in a u64
+    (0..size)
+        .map(|_| {
+            let n_operands = rng.range(2..=8);
+            let operands = (0..n_operands).map(|_| rng.range(1..=99)).collect_vec();
+            let mut test_value = operands[1..]
+                .iter()
+                .fold(operands[0], |acc, &operand| match rng.below(3) {
+                    0 => acc + operand,
+                    1 => acc * operand,
+                    _ => acc * 10u64.pow(operand.ilog10() + 1) + operand,
+                });
+            // and about half of them (probably) cannot be calibrated
+            if rng.chance(0.5) {
+                test_value += rng.range(1..=9);
+            }
+            format!("{test_value}: {}", operands.iter().join(" "))
+        })
+        .join("\n")
+}
+
+fn day8(rng: &mut Rng, size: usize) -> String {
+    let mut frequencies = ('a'..='z').chain('A'..='Z').chain('0'..='9').collect_vec();
+    rng.shuffle(&mut frequencies);
+    frequencies.truncate((size / 4).max(1));
+    let mut city = Grid::new(size, size, '.');
+    for cell in city.iter_mut() {
+        if rng.chance(0.05) {
+            *cell = *rng.choose(&frequencies);
+        }
+    }
+    city.to_string()
+}
+
+fn day9(rng: &mut Rng, size: usize) -> String {
+    let mut disk_map = String::with_capacity(2 * size);
+    for file in 0..size {
+        disk_map.push_str(&rng.range(1..=9).to_string());
+        if file + 1 < size {
+            disk_map.push_str(&rng.range(0..=9).to_string());
+        }
+    }
+    disk_map
+}
+
+fn day10(rng: &mut Rng, size: usize) -> String {
+    // often one higher than a neighbour, so that there are hiking trails
+    let mut heights = Grid::new(size, size, 0);
+    for point in heights.points().collect_vec() {
+        let (x, y) = (point.x, point.y);
+        heights[point] = match rng.below(3) {
+            0 if x > 0 => (heights[Point::new(x - 1, y)] + 1) % 10,
+            1 if y > 0 => (heights[Point::new(x, y - 1)] + 1) % 10,
+            _ => rng.range(0..=9),
+        };
+    }
+    heights.to_string()
+}
+
+fn day11(rng: &mut Rng, size: usize) -> String {
+    (0..size).map(|_| rng.range(0..=999_999)).join(" ")
+}
+
+fn day12(rng: &mut Rng, size: usize) -> String {
+    // mostly the same plant as a neighbour, so that there are regions larger than one plot
+    let mut garden = Grid::new(size, size, 'A');
+    for point in garden.points().collect_vec() {
+        let (x, y) = (point.x, point.y);
+        garden[point] = match rng.below(5) {
+            0 | 1 if x > 0 => garden[Point::new(x - 1, y)],
+            2 | 3 if y > 0 => garden[Point::new(x, y - 1)],
+            _ => *rng.choose(&['A', 'B', 'C', 'D', 'E', 'F']),
+        };
+    }
+    garden.to_string()
+}
+
+fn day13(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let (a, b) = loop {
+                let a = [rng.range(10..=99), rng.range(10..=99)];
+                let b = [rng.range(10..=99), rng.range(10..=99)];
+                if a[0] * b[1] != a[1] * b[0] {
+                    break (a, b);
+                }
+            };
+            let prize = if rng.chance(0.5) {
+                let (presses_a, presses_b) = (rng.range(0..=100), rng.range(0..=100));
+                [
+                    presses_a * a[0] + presses_b * b[0],
+                    presses_a * a[1] + presses_b * b[1],
+                ]
+            } else {
+                [rng.range(1000..=20000), rng.range(1000..=20000)]
+            };
+            format!(
+                "Button A: X+{}, Y+{}\nButton B: X+{}, Y+{}\nPrize: X={}, Y={}",
+                a[0], a[1], b[0], b[1], prize[0], prize[1]
+            )
+        })
+        .join("\n\n")
+}
+
+fn day14(rng: &mut Rng, size: usize, params: Day14Params) -> String {
+    let Day14Params { width, height } = params;
+    let (width, height) = (width as u64, height as u64);
+    (0..size)
+        .map(|_| {
+            let (px, py) = (rng.range(0..=width - 1), rng.range(0..=height - 1));
+            let vx = rng.range(0..=2 * width - 2) as i64 - (width as i64 - 1);
+            let vy = rng.range(0..=2 * height - 2) as i64 - (height as i64 - 1);
+            format!("p={px},{py} v={vx},{vy}")
+        })
+        .join("\n")
+}
+
+fn day15(rng: &mut Rng, size: usize) -> String {
+    // walled in, like the real warehouses
+    let side = size + 2;
+    let mut warehouse = Grid::new(side, side, '#');
+    for point in warehouse.points().collect_vec() {
+        if (1..=size).contains(&point.x) && (1..=size).contains(&point.y) {
+            warehouse[point] = match rng.below(20) {
+                0 => '#',
+                1..=6 => 'O',
+                _ => '.',
+            };
+        }
+    }
+    let robot = Point::new(
+        rng.range(1..=size as u64) as usize,
+        rng.range(1..=size as u64) as usize,
+    );
+    warehouse[robot] = '@';
+    let moves = (0..10 * size)
+        .map(|_| rng.choose(&Direction::ALL).arrow())
+        .chunks(70)
+        .into_iter()
+        .map(|line| line.collect::<String>())
+        .join("\n");
+    format!("{warehouse}\n{moves}")
+}
+
+fn day16(rng: &mut Rng, size: usize) -> String {
+    // a maze carved out by a random depth-first search, with some extra shortcuts,
+    // from the bottom left to the top right
+    let cells = size.max(2);
+    let side = 2 * cells + 1;
+    let mut maze = Grid::new(side, side, '#');
+    let at = |cell: Point| Point::new(2 * cell.x + 1, 2 * cell.y + 1);
+    let mut stack = vec![Point::new(0, 0)];
+    maze[at(stack[0])] = '.';
+    while let Some(&cell) = stack.last() {
+        let unvisited = Direction::ALL
+            .into_iter()
+            .filter_map(|direction| cell.checked_add(direction.vector()))
+            .filter(|next| next.x < cells && next.y < cells && maze[at(*next)] == '#')
+            .collect_vec();
+        if unvisited.is_empty() {
+            stack.pop();
+            continue;
+        }
+        let next = *rng.choose(&unvisited);
+        maze[Point::new(cell.x + next.x + 1, cell.y + next.y + 1)] = '.';
+        maze[at(next)] = '.';
+        stack.push(next);
+    }
+    for point in maze.points().collect_vec() {
+        let inside = (1..side - 1).contains(&point.x) && (1..side - 1).contains(&point.y);
+        if inside && (point.x + point.y) % 2 == 1 && rng.chance(0.1) {
+            maze[point] = '.';
+        }
+    }
+    maze[Point::new(1, side - 2)] = 'S';
+    maze[Point::new(side - 2, 1)] = 'E';
+    maze.to_string()
+}
+
+fn day17(rng: &mut Rng) -> String {
+    // the shape of the real programs, with the constants for which there is a quine
+    const QUINE_CONSTANTS: [(u8, u8); 4] = [(0, 5), (1, 4), (1, 5), (7, 7)];
+    let (first, second) = *rng.choose(&QUINE_CONSTANTS);
+    let register_a = rng.range(1..=1 << 30);
+    format!(
+        "Register A: {register_a}\nRegister B: 0\nRegister C: 0\n\n\
+         Program: 2,4,1,{first},7,5,1,{second},4,0,0,3,5,5,3,0"
+    )
+}
+
+fn day18(rng: &mut Rng, params: Day18Params) -> String {
+    // every byte but the start and the exit falls eventually, but the first ones
+    // stay clear of a random path to the exit
+    let Day18Params {
+        width,
+        height,
+        n_fallen,
+    } = params;
+    let end = Point::new(width - 1, height - 1);
+    let mut steps = vec![Direction::East; width - 1];
+    steps.extend(vec![Direction::South; height - 1]);
+    rng.shuffle(&mut steps);
+    let path = steps
+        .iter()
+        .scan(Point::new(0, 0), |point, direction| {
+            *point = point.checked_add(direction.vector())?;
+            Some(*point)
+        })
+        .collect_vec();
+    let mut bytes = (0..height)
+        .flat_map(|y| (0..width).map(move |x| Point::new(x, y)))
+        .filter(|&point| point != Point::new(0, 0) && point != end)
+        .collect_vec();
+    rng.shuffle(&mut bytes);
+    let (mut first, mut rest): (Vec<_>, Vec<_>) =
+        bytes.into_iter().partition(|point| !path.contains(point));
+    rest.extend(first.drain(n_fallen.min(first.len())..));
+    rng.shuffle(&mut rest);
+    first.iter().chain(&rest).join("\n")
+}
+
+fn day19(rng: &mut Rng, size: usize) -> String {
+    const STRIPES: [char; 5] = ['w', 'u', 'b', 'r', 'g'];
+    let mut patterns = (0..size)
+        .map(|_| {
+            let length = rng.range(1..=8);
+            (0..length)
+                .map(|_| *rng.choose(&STRIPES))
+                .collect::<String>()
+        })
+        .collect_vec();
+    patterns.sort();
+    patterns.dedup();
+    rng.shuffle(&mut patterns);
+    let designs = (0..size)
+        .map(|_| {
+            if rng.chance(0.5) {
+                let n_towels = rng.range(3..=15);
+                (0..n_towels)
+                    .map(|_| rng.choose(&patterns).as_str())
+                    .collect()
+            } else {
+                let length = rng.range(20..=60);
+                (0..length)
+                    .map(|_| *rng.choose(&STRIPES))
+                    .collect::<String>()
+            }
+        })
+        .join("\n");
+    format!("{}\n\n{designs}", patterns.join(", "))
+}
+
+fn day20(rng: &mut Rng, size: usize) -> String {
+    // a single track, along every other row and through the walls in between
+    let inner = size.max(3);
+    let mut track = Grid::new(inner + 2, inner + 2, '#');
+    let n_lanes = inner.div_ceil(2);
+    let mut x = rng.range(1..=inner as u64) as usize;
+    let start = Point::new(x, 1);
+    for lane in 0..n_lanes {
+        let y = 2 * lane + 1;
+        let next_x = loop {
+            let next_x = rng.range(1..=inner as u64) as usize;
+            if next_x != x {
+                break next_x;
+            }
+        };
+        for x in x.min(next_x)..=x.max(next_x) {
+            track[Point::new(x, y)] = '.';
+        }
+        x = next_x;
+        if lane + 1 < n_lanes {
+            track[Point::new(x, y + 1)] = '.';
+        }
+    }
+    track[start] = 'S';
+    track[Point::new(x, 2 * n_lanes - 1)] = 'E';
+    track.to_string()
+}
+
+fn day21(rng: &mut Rng) -> String {
+    (0..5)
+        .map(|_| format!("{:03}A", rng.range(0..=999)))
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{crosscheck, day18, registry, solve};
+
+    #[test]
+    fn rng_is_reproducible() {
+        let draws = |seed| {
+            let mut rng = Rng::new(seed);
+            (0..5).map(|_| rng.range(1..=6)).collect_vec()
+        };
+        assert_eq!(draws(7), draws(7));
+        assert_ne!(draws(7), draws(8));
+        assert!(draws(7).iter().all(|draw| (1..=6).contains(draw)));
+
+        let mut rng = Rng::new(0);
+        assert_eq!(rng.range(5..=5), 5);
+        let mut items = (0..10).collect_vec();
+        rng.shuffle(&mut items);
+        assert_eq!(
+            items.iter().sorted().copied().collect_vec(),
+            (0..10).collect_vec()
+        );
+    }
+
+    #[test]
+    fn generated_inputs_parse() {
+        for day in 1..=21 {
+            let solver = registry::select(Some(day), Some(1), None).next().unwrap();
+            for seed in 0..10 {
+                let input = generate_with(day, seed, 10, Params::EXAMPLE).unwrap();
+                assert_eq!(
+                    generate_with(day, seed, 10, Params::EXAMPLE),
+                    Some(input.clone())
+                );
+                if let Err(error) = solver.generate(&input) {
+                    panic!("day{day} seed {seed}: {error}\n{input}");
+                }
+            }
+        }
+        assert!(generate(22, 0, 10).is_none());
+    }
+
+    #[test]
+    fn generated_inputs_solve() {
+        for day in [5, 6, 7, 13, 16, 17, 20, 21] {
+            for seed in 0..3 {
+                let input = generate_with(day, seed, 8, Params::EXAMPLE).unwrap();
+                for part in 1..=2 {
+                    assert!(solve(day, part, &input).is_ok(), "day{day} seed {seed}");
+                }
+            }
+        }
+        for seed in 0..3 {
+            let input = generate_with(18, seed, 0, Params::EXAMPLE).unwrap();
+            let bytes = day18::parse(&input).unwrap();
+            day18::part1_with(&bytes, Day18Params::EXAMPLE);
+            day18::part2_with(&bytes, Day18Params::EXAMPLE);
+        }
+    }
+
+    #[test]
+    fn generated_inputs_cross_check() {
+        for day in [5, 7] {
+            for seed in 0..20 {
+                let input = generate(day, seed, 20).unwrap();
+                for part in 1..=2 {
+                    let check = crosscheck::cross_check(day, part, &input).unwrap();
+                    assert!(check.agrees(), "day{day} part{part} seed {seed}");
+                }
+            }
+        }
+    }
+}
@@ -1,7 +1,805 @@
-extern crate aoc2024;
-extern crate aoc_runner;
-extern crate aoc_runner_derive;
+//! `aoc24_bin`: runs the solvers of the `aoc2024` library on puzzle inputs.
 
-use aoc_runner_derive::aoc_main;
+use std::{
+    cmp::Reverse,
+    env,
+    error::Error,
+    fs,
+    io::{self, Read},
+    panic,
+    path::PathBuf,
+    process::ExitCode,
+    time::Duration,
+};
 
-aoc_main! {lib=aoc2024}
+use itertools::Itertools;
+
+use aoc2024::{
+    answers::{self, AnswerStore, Verdict},
+    crosscheck::{self, CrossCheck, Outcome},
+    inputs,
+    params::{self, Params},
+    profile::{self, CountingAllocator, Profile},
+    registry::{self, Solution, Solver},
+    table::Table,
+};
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator::new();
+
+const USAGE: &str = "\
+usage: aoc24_bin [run] [DAY [PART [VARIANT]]] [OPTIONS]
+       aoc24_bin cross-check [DAY [PART]] [OPTIONS]
+       aoc24_bin (record | verify) [DAY [PART [VARIANT]]] [OPTIONS]
+       aoc24_bin profile [DAY [PART [VARIANT]]] [--sort COLUMN] [OPTIONS]
+       aoc24_bin generate DAY [--seed N] [--size N] [--param KEY=VALUE]...
+
+run: runs the solvers for DAY (1-25, or all days), PART (1 or 2, or both parts)
+     and VARIANT (a name, or 'default' for the unnamed one, or all variants).
+cross-check: runs all variants of each selected day and part, and reports where they
+     disagree, with the fewest records of the input they still disagree on.
+record: runs the solvers, and stores their answers as the known answers for the input.
+verify: runs the solvers, and compares their answers to the known answers for the input.
+profile: runs the solvers, and prints a table of the time, peak heap use and number of
+     allocations of their generator and solver phases.
+generate: prints a random but valid input for DAY, the same one for the same seed and size.
+
+options:
+  -i, --input PATH      read the input from PATH ('-' for stdin)
+                        [default: input/2024/dayDAY.txt]
+  -r, --repeat N        run every solver N times, and report the mean times [default: 1]
+  -f, --format FORMAT   'text', or 'json' for one JSON object per line [default: text]
+  -p, --param KEY=VALUE override a puzzle parameter, like 'day14.width=11'
+                        (applied after the config file next to the input, e.g. day14.toml)
+  -a, --answers PATH    where record and verify keep the known answers [default: answers.txt]
+  -s, --sort COLUMN     sort the profile by 'day', 'generator', 'solver', 'total', 'heap'
+                        or 'allocations', the largest first [default: day]
+  --seed N              the seed for generate [default: 0]
+  --size N              how large generate makes the input, e.g. in lines [default: 100]
+  -l, --list            list the solvers instead of running them
+  -h, --help            print this message";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Run,
+    CrossCheck,
+    Record,
+    Verify,
+    Profile,
+    Generate,
+}
+
+/// What to sort the profile table by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortKey {
+    Day,
+    Generator,
+    Solver,
+    Total,
+    Heap,
+    Allocations,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Text,
+    Json,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum InputSource {
+    /// `input/2024/dayN.txt`, where cargo-aoc keeps the inputs
+    Default,
+    Path(PathBuf),
+    Stdin,
+}
+
+#[derive(Debug)]
+struct Options {
+    command: Command,
+    day: Option<u8>,
+    part: Option<u8>,
+    variant: Option<String>,
+    input: InputSource,
+    repeat: u32,
+    format: Format,
+    overrides: Vec<String>,
+    answers: PathBuf,
+    sort: SortKey,
+    seed: u64,
+    size: usize,
+    list: bool,
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(error) => {
+            eprintln!("error: {error}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    match run(&options) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// The options, or `None` if the user asked for help
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        command: Command::Run,
+        day: None,
+        part: None,
+        variant: None,
+        input: InputSource::Default,
+        repeat: 1,
+        format: Format::Text,
+        overrides: Vec::new(),
+        answers: answers::DEFAULT_PATH.into(),
+        sort: SortKey::Day,
+        seed: 0,
+        size: 100,
+        list: false,
+    };
+    let mut positionals = Vec::new();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| format!("missing a value for {name}"))
+        };
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-l" | "--list" => options.list = true,
+            "-i" | "--input" => {
+                options.input = match value(&arg)?.as_str() {
+                    "-" => InputSource::Stdin,
+                    path => InputSource::Path(path.into()),
+                }
+            }
+            "-r" | "--repeat" => {
+                options.repeat = value(&arg)?
+                    .parse()
+                    .ok()
+                    .filter(|&repeat| repeat > 0)
+                    .ok_or("the number of repeats must be a positive number")?
+            }
+            "-f" | "--format" => {
+                options.format = match value(&arg)?.as_str() {
+                    "text" => Format::Text,
+                    "json" => Format::Json,
+                    format => return Err(format!("unknown format '{format}'")),
+                }
+            }
+            "-p" | "--param" => options.overrides.push(value(&arg)?),
+            "-a" | "--answers" => options.answers = value(&arg)?.into(),
+            "-s" | "--sort" => {
+                options.sort = match value(&arg)?.as_str() {
+                    "day" => SortKey::Day,
+                    "generator" => SortKey::Generator,
+                    "solver" => SortKey::Solver,
+                    "total" => SortKey::Total,
+                    "heap" => SortKey::Heap,
+                    "allocations" => SortKey::Allocations,
+                    column => return Err(format!("cannot sort by '{column}'")),
+                }
+            }
+            "--seed" => {
+                options.seed = value(&arg)?
+                    .parse()
+                    .map_err(|_| "the seed must be a number")?
+            }
+            "--size" => {
+                options.size = value(&arg)?
+                    .parse()
+                    .ok()
+                    .filter(|&size| size > 0)
+                    .ok_or("the size must be a positive number")?
+            }
+            flag if flag.starts_with('-') && flag != "-" => {
+                return Err(format!("unknown option '{flag}'"))
+            }
+            _ => positionals.push(arg),
+        }
+    }
+    match positionals.first().map(String::as_str) {
+        Some("run") => {
+            positionals.remove(0);
+        }
+        Some("cross-check") => {
+            options.command = Command::CrossCheck;
+            positionals.remove(0);
+        }
+        Some("record") => {
+            options.command = Command::Record;
+            positionals.remove(0);
+        }
+        Some("verify") => {
+            options.command = Command::Verify;
+            positionals.remove(0);
+        }
+        Some("profile") => {
+            options.command = Command::Profile;
+            positionals.remove(0);
+        }
+        Some("generate") => {
+            options.command = Command::Generate;
+            positionals.remove(0);
+        }
+        _ => {}
+    }
+    let mut positionals = positionals.into_iter();
+    if let Some(day) = positionals.next() {
+        options.day = Some(parse_number(&day, "day", 1..=25)?);
+    }
+    if let Some(part) = positionals.next() {
+        options.part = Some(parse_number(&part, "part", 1..=2)?);
+    }
+    if let Some(variant) = positionals.next() {
+        if options.command == Command::CrossCheck {
+            return Err(format!("cross-check runs all variants, got '{variant}'"));
+        }
+        options.variant = Some(if variant == "default" {
+            String::new()
+        } else {
+            variant
+        });
+    }
+    if let Some(extra) = positionals.next() {
+        return Err(format!("unexpected argument '{extra}'"));
+    }
+    if options.command == Command::Generate && (options.day.is_none() || options.part.is_some()) {
+        return Err("generate needs a day, and only a day".to_string());
+    }
+    if options.input != InputSource::Default && options.day.is_none() {
+        return Err("an input can only be given for a single day".to_string());
+    }
+    Ok(Some(options))
+}
+
+/// Parses `2` or `day2` (for `name` = "day") within `range`
+fn parse_number(arg: &str, name: &str, range: std::ops::RangeInclusive<u8>) -> Result<u8, String> {
+    arg.strip_prefix(name)
+        .unwrap_or(arg)
+        .parse()
+        .ok()
+        .filter(|number| range.contains(number))
+        .ok_or_else(|| {
+            format!(
+                "expected a {name} from {} to {}, got '{arg}'",
+                range.start(),
+                range.end()
+            )
+        })
+}
+
+fn run(options: &Options) -> Result<bool, Box<dyn Error>> {
+    match options.command {
+        Command::Run => run_solvers(options),
+        Command::CrossCheck => run_cross_check(options),
+        Command::Record => record_answers(options),
+        Command::Verify => verify_answers(options),
+        Command::Profile => run_profile(options),
+        Command::Generate => print_generated(options),
+    }
+}
+
+/// Runs the selected solvers, returning whether all of them succeeded
+fn run_solvers(options: &Options) -> Result<bool, Box<dyn Error>> {
+    let solvers = selected_solvers(options)?;
+    if options.list {
+        for solver in solvers {
+            println!("{solver}");
+        }
+        return Ok(true);
+    }
+
+    let mut all_ok = true;
+    let mut loaded = None;
+    for solver in solvers {
+        let Some(input) = input_for(options, solver.day, &mut loaded)? else {
+            continue;
+        };
+        let outcome = solve_repeatedly(solver, input, options.repeat);
+        all_ok &= outcome.is_ok();
+        match options.format {
+            Format::Text => print_text(solver, &outcome, options.repeat),
+            Format::Json => print_json(solver, &outcome, options.repeat),
+        }
+    }
+    Ok(all_ok)
+}
+
+/// Stores the answers of the selected solvers, returning whether all of them succeeded
+fn record_answers(options: &Options) -> Result<bool, Box<dyn Error>> {
+    let mut store = AnswerStore::load(&options.answers)?;
+    let mut all_ok = true;
+    let mut loaded = None;
+    for solver in selected_solvers(options)? {
+        let Some(input) = input_for(options, solver.day, &mut loaded)? else {
+            continue;
+        };
+        match solver.solve(input) {
+            Ok(solution) => {
+                println!("{solver}: {}", solution.answer);
+                store.record(solver, input, solution.answer);
+            }
+            Err(error) => {
+                println!("{solver}: error: {error}, not recorded");
+                all_ok = false;
+            }
+        }
+    }
+    store.save(&options.answers)?;
+    Ok(all_ok)
+}
+
+/// Compares the answers of the selected solvers to the known ones,
+/// returning whether none of them regressed
+fn verify_answers(options: &Options) -> Result<bool, Box<dyn Error>> {
+    let store = AnswerStore::load(&options.answers)?;
+    let (mut correct, mut unknown, mut regressions) = (0, 0, 0);
+    let mut loaded = None;
+    for solver in selected_solvers(options)? {
+        let Some(input) = input_for(options, solver.day, &mut loaded)? else {
+            continue;
+        };
+        match store.check(solver, input) {
+            Verdict::Correct => {
+                correct += 1;
+                println!("{solver}: correct");
+            }
+            Verdict::Unknown { actual } => {
+                unknown += 1;
+                println!("{solver}: no known answer, got {actual}");
+            }
+            Verdict::Wrong { expected, actual } => {
+                regressions += 1;
+                println!("{solver}: WRONG, expected {expected}, got {actual}");
+            }
+            Verdict::Failed { error } => {
+                regressions += 1;
+                println!("{solver}: FAILED: {error}");
+            }
+        }
+    }
+    println!("{correct} correct, {regressions} regressed, {unknown} without a known answer");
+    Ok(regressions == 0)
+}
+
+/// Cross-checks the variants of the selected days and parts, returning whether they all agree
+fn run_cross_check(options: &Options) -> Result<bool, Box<dyn Error>> {
+    // a reduced input may well make some variants panic, which is not worth a backtrace each
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if !crosscheck::is_running_solver() {
+            hook(info)
+        }
+    }));
+    let day_parts = registry::select(options.day, options.part, None)
+        .map(|solver| (solver.day, solver.part))
+        .dedup()
+        .collect_vec();
+    let mut all_agree = true;
+    let mut loaded = None;
+    for (day, part) in day_parts {
+        let Some(input) = input_for(options, day, &mut loaded)? else {
+            continue;
+        };
+        let Some(check) = crosscheck::cross_check(day, part, input) else {
+            continue;
+        };
+        all_agree &= check.agrees();
+        print_cross_check(&check);
+    }
+    Ok(all_agree)
+}
+
+/// Profiles the selected solvers, returning whether all of them succeeded
+fn run_profile(options: &Options) -> Result<bool, Box<dyn Error>> {
+    let mut profiles = Vec::new();
+    let mut all_ok = true;
+    let mut loaded = None;
+    for solver in selected_solvers(options)? {
+        let Some(input) = input_for(options, solver.day, &mut loaded)? else {
+            continue;
+        };
+        match profile_repeatedly(solver, input, options.repeat) {
+            Ok(profile) => profiles.push((solver, profile)),
+            Err(error) => {
+                eprintln!("{solver}: error: {error}");
+                all_ok = false;
+            }
+        }
+    }
+    sort_profiles(&mut profiles, options.sort);
+    match options.format {
+        Format::Text => print_profile_table(&profiles, options.repeat),
+        Format::Json => {
+            for (solver, profile) in &profiles {
+                print_profile_json(solver, profile, options.repeat);
+            }
+        }
+    }
+    Ok(all_ok)
+}
+
+/// Profiles `input` `repeat` times, returning the mean times and the heap use of the last run
+fn profile_repeatedly(
+    solver: &Solver,
+    input: &str,
+    repeat: u32,
+) -> Result<Profile, Box<dyn Error>> {
+    let mut generator_time = Duration::ZERO;
+    let mut solver_time = Duration::ZERO;
+    let mut last = None;
+    for _ in 0..repeat {
+        let profile = profile::profile(solver, input, &ALLOCATOR)?;
+        generator_time += profile.generator.time;
+        solver_time += profile.solver.time;
+        last = Some(profile);
+    }
+    let mut profile = last.expect("at least one run");
+    profile.generator.time = generator_time / repeat;
+    profile.solver.time = solver_time / repeat;
+    Ok(profile)
+}
+
+/// Sorts by day, part and variant, or by `key` with the largest first (and ties in day order)
+fn sort_profiles(profiles: &mut [(&Solver, Profile)], key: SortKey) {
+    profiles.sort_by_key(|(solver, _)| (solver.day, solver.part, solver.variant));
+    let measure = |profile: &Profile| match key {
+        SortKey::Day => 0,
+        SortKey::Generator => profile.generator.time.as_nanos(),
+        SortKey::Solver => profile.solver.time.as_nanos(),
+        SortKey::Total => (profile.generator.time + profile.solver.time).as_nanos(),
+        SortKey::Heap => profile.peak_heap() as u128,
+        SortKey::Allocations => profile.allocations() as u128,
+    };
+    profiles.sort_by_key(|(_, profile)| Reverse(measure(profile)));
+}
+
+fn print_profile_table(profiles: &[(&Solver, Profile)], repeat: u32) {
+    let mut table = Table::new([
+        "solver",
+        "generator",
+        "solver",
+        "total",
+        "peak heap",
+        "allocations",
+    ]);
+    for (solver, profile) in profiles {
+        table.push_row([
+            solver.to_string(),
+            format!("{:.1?}", profile.generator.time),
+            format!("{:.1?}", profile.solver.time),
+            format!("{:.1?}", profile.generator.time + profile.solver.time),
+            format_bytes(profile.peak_heap()),
+            format!(
+                "{} + {}",
+                profile.generator.allocations, profile.solver.allocations
+            ),
+        ]);
+    }
+    print!("{table}");
+    if repeat > 1 {
+        println!("(times are the mean of {repeat} runs, allocations are generator + solver)");
+    } else {
+        println!("(allocations are generator + solver)");
+    }
+}
+
+fn print_profile_json(solver: &Solver, profile: &Profile, repeat: u32) {
+    let variant = solver.variant.map_or("null".to_string(), json_string);
+    println!(
+        concat!(
+            r#"{{"day":{},"part":{},"variant":{},"answer":{},"runs":{},"#,
+            r#""generator_ns":{},"generator_allocations":{},"generator_peak_bytes":{},"#,
+            r#""solver_ns":{},"solver_allocations":{},"solver_peak_bytes":{},"#,
+            r#""parsed_bytes":{},"peak_bytes":{}}}"#
+        ),
+        solver.day,
+        solver.part,
+        variant,
+        json_string(&profile.answer),
+        repeat,
+        profile.generator.time.as_nanos(),
+        profile.generator.allocations,
+        profile.generator.peak_heap,
+        profile.solver.time.as_nanos(),
+        profile.solver.allocations,
+        profile.solver.peak_heap,
+        profile.parsed_heap,
+        profile.peak_heap()
+    );
+}
+
+/// `bytes` in B, KiB, MiB or GiB, whichever keeps the number below 1024
+fn format_bytes(bytes: usize) -> String {
+    let mut size = bytes as f64;
+    for unit in ["B", "KiB", "MiB"] {
+        if size < 1024.0 {
+            return if unit == "B" {
+                format!("{bytes} B")
+            } else {
+                format!("{size:.1} {unit}")
+            };
+        }
+        size /= 1024.0;
+    }
+    format!("{size:.1} GiB")
+}
+
+/// Prints a random input for the selected day
+fn print_generated(options: &Options) -> Result<bool, Box<dyn Error>> {
+    let day = options.day.ok_or("generate needs a day")?;
+    let mut params = Params::default();
+    for assignment in &options.overrides {
+        params
+            .apply_override(assignment)
+            .map_err(|error| format!("in the parameter '{assignment}': {error}"))?;
+    }
+    let input = inputs::generate_with(day, options.seed, options.size, params)
+        .ok_or_else(|| format!("there is no generator for day{day}"))?;
+    print!("{input}");
+    Ok(true)
+}
+
+fn selected_solvers(options: &Options) -> Result<Vec<&'static Solver>, Box<dyn Error>> {
+    let solvers =
+        registry::select(options.day, options.part, options.variant.as_deref()).collect_vec();
+    if solvers.is_empty() {
+        return Err("no solver matches the given day, part and variant".into());
+    }
+    Ok(solvers)
+}
+
+/// The input for `day`, loading it (and setting its parameters) unless it is `loaded` already,
+/// or `None` if the day is skipped
+fn input_for<'a>(
+    options: &Options,
+    day: u8,
+    loaded: &'a mut Option<(u8, Option<String>)>,
+) -> Result<Option<&'a str>, Box<dyn Error>> {
+    if loaded
+        .as_ref()
+        .is_none_or(|(loaded_day, _)| *loaded_day != day)
+    {
+        let input = load_input(options, day)?.map(|(input, params)| {
+            params::set(params);
+            input
+        });
+        *loaded = Some((day, input));
+    }
+    Ok(loaded.as_ref().and_then(|(_, input)| input.as_deref()))
+}
+
+/// The input for `day` and the parameters to solve it with,
+/// or `None` if all days are run and there is no input for this one
+fn load_input(options: &Options, day: u8) -> Result<Option<(String, Params)>, Box<dyn Error>> {
+    let mut params = Params::default();
+    let input = match &options.input {
+        InputSource::Stdin => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input)?;
+            input
+        }
+        InputSource::Path(path) => {
+            params = params::load_for_input(path)?;
+            fs::read_to_string(path).map_err(|error| format!("{}: {error}", path.display()))?
+        }
+        InputSource::Default => {
+            let path = PathBuf::from(format!("input/2024/day{day}.txt"));
+            if options.day.is_none() && !path.exists() {
+                eprintln!("skipping day{day}: there is no {}", path.display());
+                return Ok(None);
+            }
+            params = params::load_for_input(&path)?;
+            fs::read_to_string(&path).map_err(|error| format!("{}: {error}", path.display()))?
+        }
+    };
+    for assignment in &options.overrides {
+        params
+            .apply_override(assignment)
+            .map_err(|error| format!("in the parameter '{assignment}': {error}"))?;
+    }
+    Ok(Some((input, params)))
+}
+
+/// Solves `input` `repeat` times, returning the last answer and the mean times
+fn solve_repeatedly(solver: &Solver, input: &str, repeat: u32) -> Result<Solution, Box<dyn Error>> {
+    let mut total = solver.solve(input)?;
+    for _ in 1..repeat {
+        let solution = solver.solve(input)?;
+        total.generator_time += solution.generator_time;
+        total.solver_time += solution.solver_time;
+        total.answer = solution.answer;
+    }
+    total.generator_time /= repeat;
+    total.solver_time /= repeat;
+    Ok(total)
+}
+
+fn print_text(solver: &Solver, outcome: &Result<Solution, Box<dyn Error>>, repeat: u32) {
+    match outcome {
+        Ok(solution) => {
+            let separator = if solution.answer.contains('\n') {
+                '\n'
+            } else {
+                ' '
+            };
+            let mean = if repeat > 1 {
+                format!(", mean of {repeat} runs")
+            } else {
+                String::new()
+            };
+            println!(
+                "{solver}:{separator}{}  [generator {:.1?}, solver {:.1?}{mean}]",
+                solution.answer, solution.generator_time, solution.solver_time
+            );
+        }
+        Err(error) => println!("{solver}: error: {error}"),
+    }
+}
+
+fn print_cross_check(check: &CrossCheck) {
+    let day_part = format!("day{} part{}", check.day, check.part);
+    if check.agrees() {
+        let answer = match &check.outcomes[0].answer {
+            Ok(answer) => answer,
+            Err(error) => error,
+        };
+        println!(
+            "{day_part}: all {} variants agree on {answer}",
+            check.outcomes.len()
+        );
+        return;
+    }
+    println!("{day_part}: the variants disagree");
+    print_outcomes(&check.outcomes);
+    if let Some(minimal) = &check.minimal {
+        println!(
+            "  they still disagree on {} of the {} records:",
+            minimal.n_records, minimal.n_records_total
+        );
+        for line in minimal.input.lines() {
+            println!("    {line}");
+        }
+        print_outcomes(&minimal.outcomes);
+    }
+}
+
+fn print_outcomes(outcomes: &[Outcome]) {
+    for outcome in outcomes {
+        let variant = outcome.solver.variant.unwrap_or("default");
+        match &outcome.answer {
+            Ok(answer) => println!("  {variant}: {answer}"),
+            Err(error) => println!("  {variant}: error: {error}"),
+        }
+    }
+}
+
+fn print_json(solver: &Solver, outcome: &Result<Solution, Box<dyn Error>>, repeat: u32) {
+    let variant = solver.variant.map_or("null".to_string(), json_string);
+    let result = match outcome {
+        Ok(solution) => format!(
+            r#""answer":{},"runs":{repeat},"generator_ns":{},"solver_ns":{}"#,
+            json_string(&solution.answer),
+            solution.generator_time.as_nanos(),
+            solution.solver_time.as_nanos()
+        ),
+        Err(error) => format!(r#""error":{}"#, json_string(&error.to_string())),
+    };
+    println!(
+        r#"{{"day":{},"part":{},"variant":{variant},{result}}}"#,
+        solver.day, solver.part
+    );
+}
+
+/// `text` as a JSON string literal
+fn json_string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str(r#"\""#),
+            '\\' => json.push_str(r"\\"),
+            '\n' => json.push_str(r"\n"),
+            '\r' => json.push_str(r"\r"),
+            '\t' => json.push_str(r"\t"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Option<Options>, String> {
+        parse_args(args.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn parse_selection() {
+        let options = parse("run day2 2 brute_force -r 3 --format json")
+            .unwrap()
+            .unwrap();
+        assert_eq!(options.day, Some(2));
+        assert_eq!(options.part, Some(2));
+        assert_eq!(options.variant.as_deref(), Some("brute_force"));
+        assert_eq!((options.repeat, options.format), (3, Format::Json));
+
+        let options = parse("5 1 default -i - -p day14.width=11")
+            .unwrap()
+            .unwrap();
+        assert_eq!(options.variant.as_deref(), Some(""));
+        assert_eq!(options.input, InputSource::Stdin);
+        assert_eq!(options.overrides, ["day14.width=11"]);
+
+        let options = parse("").unwrap().unwrap();
+        assert_eq!((options.day, options.part), (None, None));
+        assert!(parse("--help 3").unwrap().is_none());
+
+        let options = parse("cross-check 7").unwrap().unwrap();
+        assert_eq!(
+            (options.command, options.day),
+            (Command::CrossCheck, Some(7))
+        );
+
+        let options = parse("profile -s heap").unwrap().unwrap();
+        assert_eq!(
+            (options.command, options.sort),
+            (Command::Profile, SortKey::Heap)
+        );
+
+        let options = parse("generate 5 --seed 7 --size 20").unwrap().unwrap();
+        assert_eq!(
+            (options.command, options.day, options.seed, options.size),
+            (Command::Generate, Some(5), 7, 20)
+        );
+    }
+
+    #[test]
+    fn parse_bad_args() {
+        assert!(parse("26").is_err());
+        assert!(parse("1 3").is_err());
+        assert!(parse("1 1 default extra").is_err());
+        assert!(parse("1 --repeat 0").is_err());
+        assert!(parse("1 --repeat 4294967296").is_err());
+        assert!(parse("1 --repeat").is_err());
+        assert!(parse("--input day1.txt").is_err());
+        assert!(parse("1 --fast").is_err());
+        assert!(parse("cross-check 2 2 brute_force").is_err());
+        assert!(parse("verify 1 --answers").is_err());
+        assert!(parse("profile --sort speed").is_err());
+        assert!(parse("generate").is_err());
+        assert!(parse("generate 5 1").is_err());
+        assert!(parse("generate 5 --size 0").is_err());
+    }
+
+    #[test]
+    fn json_strings() {
+        assert_eq!(json_string("42"), r#""42""#);
+        assert_eq!(json_string("a \"b\"\n\\\u{1}"), r#""a \"b\"\n\\\u0001""#);
+    }
+
+    #[test]
+    fn byte_sizes() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(3 << 30), "3.0 GiB");
+    }
+}
@@ -0,0 +1,417 @@
+//! Puzzle parameters that are not part of the puzzle input, like the size of the space
+//! the robots of day 14 walk around in, or how many robots type on the keypads of day 21.
+//!
+//! The solvers read them from a process-wide [`Params`], which defaults to the values for the
+//! real puzzle inputs. Runners can replace it via [`set`], e.g. after reading a config file like
+//!
+//! ```text
+//! # day14.toml, next to day14.txt
+//! [day14]
+//! width = 11
+//! height = 7
+//! ```
+//!
+//! and applying overrides like `day14.width=11` from the command line.
+//! The solvers themselves also take their parameters explicitly, so the tests can pass the
+//! example parameters without touching the process-wide ones; to run a whole solver with other
+//! parameters, [`with`] overrides them for the current thread only.
+
+use std::{
+    cell::Cell,
+    fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::RwLock,
+};
+
+use crate::{
+    day2::SafetyPolicy,
+    parsing::{parse_token, split_once, ParseError},
+};
+
+/// The rules for the reports of day 2, which differ only in the removals between the parts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Day2Params {
+    pub part1: SafetyPolicy,
+    pub part2: SafetyPolicy,
+}
+
+impl Day2Params {
+    pub const REAL: Self = Self {
+        part1: SafetyPolicy::PART1,
+        part2: SafetyPolicy::PART2,
+    };
+    pub const EXAMPLE: Self = Self::REAL;
+}
+
+/// The size of the space the robots of day 14 walk around in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Day14Params {
+    pub width: i64,
+    pub height: i64,
+}
+
+impl Day14Params {
+    pub const REAL: Self = Self {
+        width: 101,
+        height: 103,
+    };
+    pub const EXAMPLE: Self = Self {
+        width: 11,
+        height: 7,
+    };
+}
+
+/// The size of the memory space of day 18, and how many bytes have fallen for part 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Day18Params {
+    pub width: usize,
+    pub height: usize,
+    pub n_fallen: usize,
+}
+
+impl Day18Params {
+    pub const REAL: Self = Self {
+        width: 71,
+        height: 71,
+        n_fallen: 1024,
+    };
+    pub const EXAMPLE: Self = Self {
+        width: 7,
+        height: 7,
+        n_fallen: 12,
+    };
+}
+
+/// The least number of picoseconds a cheat on day 20 must save to be counted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Day20Params {
+    pub part1_min_gain: isize,
+    pub part2_min_gain: isize,
+}
+
+impl Day20Params {
+    pub const REAL: Self = Self {
+        part1_min_gain: 100,
+        part2_min_gain: 100,
+    };
+    pub const EXAMPLE: Self = Self {
+        part1_min_gain: 1,
+        part2_min_gain: 50,
+    };
+}
+
+/// The number of robots on direction pads between us and the numeric pad of day 21
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Day21Params {
+    pub part1_robots: usize,
+    pub part2_robots: usize,
+}
+
+impl Day21Params {
+    pub const REAL: Self = Self {
+        part1_robots: 2,
+        part2_robots: 5,
+    };
+    pub const EXAMPLE: Self = Self::REAL;
+}
+
+/// The parameters of every day that has some
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Params {
+    pub day2: Day2Params,
+    pub day14: Day14Params,
+    pub day18: Day18Params,
+    pub day20: Day20Params,
+    pub day21: Day21Params,
+}
+
+impl Params {
+    pub const REAL: Self = Self {
+        day2: Day2Params::REAL,
+        day14: Day14Params::REAL,
+        day18: Day18Params::REAL,
+        day20: Day20Params::REAL,
+        day21: Day21Params::REAL,
+    };
+    pub const EXAMPLE: Self = Self {
+        day2: Day2Params::EXAMPLE,
+        day14: Day14Params::EXAMPLE,
+        day18: Day18Params::EXAMPLE,
+        day20: Day20Params::EXAMPLE,
+        day21: Day21Params::EXAMPLE,
+    };
+
+    /// Sets the parameter named `key` (like `day14.width`) to `value`
+    ///
+    /// Both are sub-slices of `source`, which is only used to point at them in errors.
+    pub fn set(&mut self, source: &str, key: &str, value: &str) -> Result<(), ParseError> {
+        let (day, name) = split_once(source, key, ".", "a key like 'day14.width'")?;
+        self.set_in(source, day, name, key, value)
+    }
+
+    /// Applies an override like `day14.width=11`
+    pub fn apply_override(&mut self, assignment: &str) -> Result<(), ParseError> {
+        let (key, value) = split_once(assignment, assignment, "=", "a 'key=value' pair")?;
+        self.set(assignment, key.trim(), value.trim())
+    }
+
+    /// Applies a config file of `[dayN]` sections with `key = value` lines
+    ///
+    /// Keys outside any section must name their day, as in `day14.width = 11`.
+    /// Everything after a `#` is a comment.
+    pub fn apply_config(&mut self, config: &str) -> Result<(), ParseError> {
+        let mut section = None;
+        for line in config.lines() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(header) = line.strip_prefix('[') {
+                let day = header
+                    .strip_suffix(']')
+                    .ok_or_else(|| ParseError::at(config, line, "a section like '[day14]'"))?
+                    .trim();
+                section = Some(day);
+                continue;
+            }
+            let (key, value) = split_once(config, line, "=", "a 'key = value' line")?;
+            let (key, value) = (key.trim(), value.trim());
+            match section {
+                Some(_) if key.contains('.') => {
+                    return Err(ParseError::at(config, key, "a key without a day"));
+                }
+                Some(day) => self.set_in(config, day, key, key, value)?,
+                None => self.set(config, key, value)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets the parameter `name` of `day` to `value`, pointing at `key` in errors about the name
+    fn set_in(
+        &mut self,
+        source: &str,
+        day: &str,
+        name: &str,
+        key: &str,
+        value: &str,
+    ) -> Result<(), ParseError> {
+        macro_rules! number {
+            ($field:expr) => {
+                $field = parse_token(source, value, "a number")?
+            };
+        }
+        macro_rules! size {
+            ($field:expr) => {
+                $field = parse_positive(source, value)?
+            };
+        }
+        macro_rules! both_parts {
+            ($field:ident, $expected:literal) => {{
+                let value = parse_token(source, value, $expected)?;
+                self.day2.part1.$field = value;
+                self.day2.part2.$field = value;
+            }};
+        }
+        match (day, name) {
+            ("day2", "min_step") => both_parts!(min_step, "a number"),
+            ("day2", "max_step") => both_parts!(max_step, "a number"),
+            ("day2", "strictly_monotonic") => both_parts!(strictly_monotonic, "true or false"),
+            ("day2", "max_direction_changes") => both_parts!(max_direction_changes, "a number"),
+            ("day2", "part1_removals") => number!(self.day2.part1.max_removals),
+            ("day2", "part2_removals") => number!(self.day2.part2.max_removals),
+            ("day14", "width") => size!(self.day14.width),
+            ("day14", "height") => size!(self.day14.height),
+            ("day18", "width") => size!(self.day18.width),
+            ("day18", "height") => size!(self.day18.height),
+            ("day18", "n_fallen") => number!(self.day18.n_fallen),
+            ("day20", "part1_min_gain") => number!(self.day20.part1_min_gain),
+            ("day20", "part2_min_gain") => number!(self.day20.part2_min_gain),
+            ("day21", "part1_robots") => number!(self.day21.part1_robots),
+            ("day21", "part2_robots") => number!(self.day21.part2_robots),
+            _ => return Err(ParseError::at(source, key, "a known parameter")),
+        }
+        Ok(())
+    }
+}
+
+/// Parses `value` (a sub-slice of `source`) as a number greater than zero, like a size
+fn parse_positive<T: FromStr + Default + PartialOrd>(
+    source: &str,
+    value: &str,
+) -> Result<T, ParseError> {
+    let number = parse_token(source, value, "a positive number")?;
+    if number > T::default() {
+        Ok(number)
+    } else {
+        Err(ParseError::at(source, value, "a positive number"))
+    }
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self::REAL
+    }
+}
+
+/// Where the config file for `input` goes: next to it, with the extension `.toml`
+pub fn config_path(input: &Path) -> PathBuf {
+    input.with_extension("toml")
+}
+
+/// The default parameters, with the config file next to `input` applied if there is one
+pub fn load_for_input(input: &Path) -> Result<Params, Box<dyn std::error::Error>> {
+    let mut params = Params::default();
+    match fs::read_to_string(config_path(input)) {
+        Ok(config) => params.apply_config(&config)?,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+        Err(error) => return Err(error.into()),
+    }
+    Ok(params)
+}
+
+static CURRENT: RwLock<Params> = RwLock::new(Params::REAL);
+
+thread_local! {
+    static SCOPED: Cell<Option<Params>> = const { Cell::new(None) };
+}
+
+/// The parameters the solvers run with: those of the innermost [`with`] on this thread,
+/// or else the process-wide ones
+pub fn current() -> Params {
+    SCOPED.get().unwrap_or_else(|| *CURRENT.read().unwrap())
+}
+
+/// Replaces the process-wide parameters
+pub fn set(params: Params) {
+    *CURRENT.write().unwrap() = params;
+}
+
+/// Runs `f` with `params` as the current parameters on this thread only,
+/// leaving the process-wide ones and other threads alone
+pub fn with<T>(params: Params, f: impl FnOnce() -> T) -> T {
+    struct Restore(Option<Params>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            SCOPED.set(self.0);
+        }
+    }
+    let _restore = Restore(SCOPED.replace(Some(params)));
+    f()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn overrides() {
+        let mut params = Params::default();
+        params.apply_override("day14.width=11").unwrap();
+        params.apply_override("day18.n_fallen = 12").unwrap();
+        assert_eq!(params.day14.width, 11);
+        assert_eq!(params.day14.height, 103);
+        assert_eq!(params.day18.n_fallen, 12);
+
+        for zero in [
+            "day14.width=0",
+            "day14.height=-1",
+            "day18.width=0",
+            "day18.height=0",
+        ] {
+            let error = params.apply_override(zero).unwrap_err();
+            assert_eq!(error.expected, "a positive number");
+        }
+
+        params.apply_override("day2.max_step=4").unwrap();
+        params
+            .apply_override("day2.strictly_monotonic=false")
+            .unwrap();
+        params.apply_override("day2.part2_removals=2").unwrap();
+        assert_eq!(params.day2.part1.max_step, 4);
+        assert_eq!(params.day2.part2.max_step, 4);
+        assert!(!params.day2.part2.strictly_monotonic);
+        assert_eq!(params.day2.part1.max_removals, 0);
+        assert_eq!(params.day2.part2.max_removals, 2);
+        let error = params
+            .apply_override("day2.strictly_monotonic=yes")
+            .unwrap_err();
+        assert_eq!(error.expected, "true or false");
+
+        let error = params.apply_override("day14.depth=3").unwrap_err();
+        assert_eq!(error.snippet, "day14.depth");
+        let error = params.apply_override("day14.width=eleven").unwrap_err();
+        assert_eq!((error.column, error.snippet.as_str()), (13, "eleven"));
+        assert!(params.apply_override("day14.width").is_err());
+    }
+
+    #[test]
+    fn config() {
+        let mut params = Params::default();
+        params
+            .apply_config(indoc! {"
+                # the example of day 14
+                [day14]
+                width = 11
+                height = 7  # not 103
+
+                [day20]
+                part1_min_gain = 1
+            "})
+            .unwrap();
+        assert_eq!(params.day14, Day14Params::EXAMPLE);
+        assert_eq!(params.day20.part1_min_gain, 1);
+        assert_eq!(params.day20.part2_min_gain, 100);
+
+        params.apply_config("day18.width = 7\n").unwrap();
+        assert_eq!(params.day18.width, 7);
+    }
+
+    #[test]
+    fn malformed_config() {
+        let mut params = Params::default();
+        let error = params
+            .apply_config("[day14]\nwidth = 11\nheigth = 7\n")
+            .unwrap_err();
+        assert_eq!((error.line, error.column), (3, 1));
+        assert_eq!(error.expected, "a known parameter");
+
+        let error = params.apply_config("[day14]\nwidth = -\n").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 9));
+
+        let error = params.apply_config("[day14\n").unwrap_err();
+        assert_eq!(error.line, 1);
+        let error = params.apply_config("day14.width 11\n").unwrap_err();
+        assert_eq!(error.expected, "a 'key = value' line");
+    }
+
+    #[test]
+    fn config_next_to_input() {
+        assert_eq!(
+            config_path(Path::new("input/2024/day14.txt")),
+            Path::new("input/2024/day14.toml")
+        );
+        assert_eq!(
+            load_for_input(Path::new("no/such/day14.txt")).unwrap(),
+            Params::REAL
+        );
+    }
+
+    #[test]
+    fn with_overrides_this_thread_only() {
+        let mut params = Params::default();
+        params.apply_override("day14.width=11").unwrap();
+        let width = with(params, || {
+            let elsewhere = std::thread::spawn(|| current().day14.width);
+            assert_eq!(
+                elsewhere.join().unwrap(),
+                CURRENT.read().unwrap().day14.width
+            );
+            current().day14.width
+        });
+        assert_eq!(width, 11);
+        assert_eq!(current().day14.width, CURRENT.read().unwrap().day14.width);
+    }
+}
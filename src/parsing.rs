@@ -0,0 +1,173 @@
+//! The error type returned by all the `aoc_generator`s, plus a few helpers for building it.
+//!
+//! A [`ParseError`] points at the offending part of the puzzle input by line and column,
+//! so malformed inputs can be reported instead of aborting (or worse, silently mis-parsing).
+//! The helpers take the whole `source` next to the sub-slice they work on,
+//! and work out the position from where the sub-slice lies within `source`.
+
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+/// Snippets longer than this (in chars) are cut short in the error
+const MAX_SNIPPET_LEN: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based line of the start of the snippet
+    pub line: usize,
+    /// 1-based column (in chars) of the start of the snippet
+    pub column: usize,
+    /// The offending part of the input, empty when something is missing
+    pub snippet: String,
+    /// What should have been there instead
+    pub expected: String,
+}
+
+impl ParseError {
+    /// An error about `snippet`, which should be a sub-slice of `source`
+    ///
+    /// If it is not, the position of its first occurrence in `source` is used,
+    /// or the end of `source` if it does not occur at all.
+    pub fn at(source: &str, snippet: &str, expected: impl Into<String>) -> Self {
+        let offset = (snippet.as_ptr() as usize)
+            .checked_sub(source.as_ptr() as usize)
+            .filter(|&offset| offset + snippet.len() <= source.len())
+            .or_else(|| source.find(snippet))
+            .unwrap_or(source.len());
+        let before = &source[..offset];
+        let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+        let mut truncated: String = snippet
+            .lines()
+            .next()
+            .unwrap_or("")
+            .chars()
+            .take(MAX_SNIPPET_LEN)
+            .collect();
+        if truncated.len() < snippet.len() {
+            truncated.push('…');
+        }
+        Self {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            snippet: truncated,
+            expected: expected.into(),
+        }
+    }
+
+    /// An error about something missing right after `text` (a sub-slice of `source`)
+    pub fn after(source: &str, text: &str, expected: impl Into<String>) -> Self {
+        Self::at(source, &text[text.len()..], expected)
+    }
+
+    /// An error about something missing at the end of `source`
+    pub fn at_end(source: &str, expected: impl Into<String>) -> Self {
+        Self::after(source, source, expected)
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: expected {}, ",
+            self.line, self.column, self.expected
+        )?;
+        if self.snippet.is_empty() {
+            write!(f, "found nothing")
+        } else {
+            write!(f, "found {:?}", self.snippet)
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// Parses `token` (a sub-slice of `source`) via its `FromStr` impl
+pub fn parse_token<T: FromStr>(source: &str, token: &str, expected: &str) -> Result<T, ParseError> {
+    token
+        .parse()
+        .map_err(|_| ParseError::at(source, token, expected))
+}
+
+/// Splits `text` (a sub-slice of `source`) around the first `delimiter`
+pub fn split_once<'a>(
+    source: &str,
+    text: &'a str,
+    delimiter: &str,
+    expected: &str,
+) -> Result<(&'a str, &'a str), ParseError> {
+    text.split_once(delimiter)
+        .ok_or_else(|| ParseError::at(source, text, expected))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    const SOURCE: &str = indoc! {"
+        12 34
+        56 7x
+        9ä 10
+    "};
+
+    #[test]
+    fn position_of_snippet() {
+        let snippet = &SOURCE[9..11];
+        assert_eq!(snippet, "7x");
+        let error = ParseError::at(SOURCE, snippet, "a number");
+        assert_eq!(error.line, 2);
+        assert_eq!(error.column, 4);
+        assert_eq!(error.snippet, "7x");
+        assert_eq!(
+            error.to_string(),
+            "line 2, column 4: expected a number, found \"7x\""
+        );
+    }
+
+    #[test]
+    fn columns_count_chars() {
+        let line = SOURCE.lines().nth(2).unwrap();
+        let token = line.split_whitespace().nth(1).unwrap();
+        let error = ParseError::at(SOURCE, token, "a number");
+        assert_eq!((error.line, error.column), (3, 4));
+    }
+
+    #[test]
+    fn missing_at_the_end() {
+        let error = ParseError::at_end(SOURCE.trim(), "a third line");
+        assert_eq!((error.line, error.column), (3, 6));
+        assert_eq!(
+            error.to_string(),
+            "line 3, column 6: expected a third line, found nothing"
+        );
+    }
+
+    #[test]
+    fn snippet_from_elsewhere() {
+        let error = ParseError::at(SOURCE, &String::from("56"), "nothing");
+        assert_eq!((error.line, error.column), (2, 1));
+    }
+
+    #[test]
+    fn long_snippets_are_truncated() {
+        let source = "x".repeat(100);
+        let error = ParseError::at(&source, &source, "something shorter");
+        assert_eq!(error.snippet.chars().count(), MAX_SNIPPET_LEN + 1);
+        assert!(error.snippet.ends_with('…'));
+    }
+
+    #[test]
+    fn parse_tokens() {
+        let line = SOURCE.lines().nth(1).unwrap();
+        let (left, right) = split_once(SOURCE, line, " ", "two numbers").unwrap();
+        assert_eq!(parse_token::<u32>(SOURCE, left, "a number"), Ok(56));
+        let error = parse_token::<u32>(SOURCE, right, "a number").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 4));
+        let error = split_once(SOURCE, right, " ", "two numbers").unwrap_err();
+        assert_eq!(error.snippet, "7x");
+    }
+}
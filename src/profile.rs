@@ -0,0 +1,225 @@
+//! Timing and heap use of the generator and solver phases of each solver.
+//!
+//! The heap is measured by a [`CountingAllocator`], which the binary installs as its
+//! `#[global_allocator]` (the library leaves that choice to whoever links it):
+//!
+//! ```ignore
+//! #[global_allocator]
+//! static ALLOCATOR: CountingAllocator = CountingAllocator::new();
+//! ```
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    error::Error,
+    sync::atomic::{AtomicUsize, Ordering::Relaxed},
+    time::{Duration, Instant},
+};
+
+use crate::registry::Solver;
+
+/// Wraps the system allocator, counting allocations and the bytes in use
+pub struct CountingAllocator {
+    in_use: AtomicUsize,
+    peak: AtomicUsize,
+    allocations: AtomicUsize,
+}
+
+/// What a [`CountingAllocator`] has counted so far
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocStats {
+    /// Bytes currently allocated
+    pub in_use: usize,
+    /// The most bytes allocated at once since the last [`CountingAllocator::reset_peak`]
+    pub peak: usize,
+    /// Number of allocations (including reallocations) so far
+    pub allocations: usize,
+}
+
+impl CountingAllocator {
+    pub const fn new() -> Self {
+        Self {
+            in_use: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+            allocations: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn stats(&self) -> AllocStats {
+        AllocStats {
+            in_use: self.in_use.load(Relaxed),
+            peak: self.peak.load(Relaxed),
+            allocations: self.allocations.load(Relaxed),
+        }
+    }
+
+    /// Starts measuring the peak from the bytes currently in use
+    pub fn reset_peak(&self) {
+        self.peak.store(self.in_use.load(Relaxed), Relaxed);
+    }
+
+    fn grow(&self, bytes: usize) {
+        let in_use = self.in_use.fetch_add(bytes, Relaxed) + bytes;
+        self.peak.fetch_max(in_use, Relaxed);
+    }
+
+    fn shrink(&self, bytes: usize) {
+        self.in_use.fetch_sub(bytes, Relaxed);
+    }
+}
+
+impl Default for CountingAllocator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            self.allocations.fetch_add(1, Relaxed);
+            self.grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            self.allocations.fetch_add(1, Relaxed);
+            self.grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        self.shrink(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            self.allocations.fetch_add(1, Relaxed);
+            if new_size > layout.size() {
+                self.grow(new_size - layout.size());
+            } else {
+                self.shrink(layout.size() - new_size);
+            }
+        }
+        new_ptr
+    }
+}
+
+/// How long a phase took, and how much it allocated
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Phase {
+    pub time: Duration,
+    pub allocations: usize,
+    /// The most bytes the phase had allocated at once, on top of what was allocated before it
+    pub peak_heap: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Profile {
+    pub answer: String,
+    /// Parsing the input
+    pub generator: Phase,
+    /// Solving the parsed input
+    pub solver: Phase,
+    /// Bytes still allocated after parsing, which the solver phase starts from
+    pub parsed_heap: usize,
+}
+
+impl Profile {
+    /// The most bytes allocated at once while parsing and solving
+    pub fn peak_heap(&self) -> usize {
+        self.generator
+            .peak_heap
+            .max(self.parsed_heap + self.solver.peak_heap)
+    }
+
+    pub fn allocations(&self) -> usize {
+        self.generator.allocations + self.solver.allocations
+    }
+}
+
+/// Solves `input` with `solver`, measuring each phase
+///
+/// The allocations are only counted if `allocator` is the global allocator,
+/// and are only meaningful if nothing else allocates concurrently.
+pub fn profile(
+    solver: &Solver,
+    input: &str,
+    allocator: &CountingAllocator,
+) -> Result<Profile, Box<dyn Error>> {
+    let before = allocator.stats().in_use;
+    let (runner, generator) = measure(allocator, || solver.generate(input));
+    let runner = runner?;
+    let parsed_heap = allocator.stats().in_use.saturating_sub(before);
+    let (answer, solver) = measure(allocator, || runner.try_run());
+    Ok(Profile {
+        answer: answer?.to_string(),
+        generator,
+        solver,
+        parsed_heap,
+    })
+}
+
+fn measure<T>(allocator: &CountingAllocator, phase: impl FnOnce() -> T) -> (T, Phase) {
+    allocator.reset_peak();
+    let before = allocator.stats();
+    let start = Instant::now();
+    let result = phase();
+    let time = start.elapsed();
+    let after = allocator.stats();
+    let phase = Phase {
+        time,
+        allocations: after.allocations - before.allocations,
+        peak_heap: after.peak.saturating_sub(before.in_use),
+    };
+    (result, phase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry;
+
+    #[test]
+    fn counting() {
+        let allocator = CountingAllocator::new();
+        let small = Layout::from_size_align(16, 8).unwrap();
+        let large = Layout::from_size_align(1024, 8).unwrap();
+        unsafe {
+            let a = allocator.alloc(small);
+            let b = allocator.alloc_zeroed(large);
+            allocator.dealloc(b, large);
+            let a = allocator.realloc(a, small, 64);
+            assert_eq!(
+                allocator.stats(),
+                AllocStats {
+                    in_use: 64,
+                    peak: 16 + 1024,
+                    allocations: 3
+                }
+            );
+            allocator.reset_peak();
+            assert_eq!(allocator.stats().peak, 64);
+            allocator.dealloc(a, Layout::from_size_align(64, 8).unwrap());
+        }
+        assert_eq!(allocator.stats().in_use, 0);
+    }
+
+    #[test]
+    fn phases() {
+        // not the global allocator, so nothing gets counted, but the times do
+        let allocator = CountingAllocator::new();
+        let solver = registry::select(Some(11), Some(1), None).next().unwrap();
+        let stones = profile(solver, "125 17", &allocator).unwrap();
+        assert_eq!(stones.answer, "55312");
+        assert_eq!((stones.allocations(), stones.peak_heap()), (0, 0));
+        assert!(stones.solver.time > Duration::ZERO);
+        assert!(profile(solver, "125 x", &allocator).is_err());
+    }
+}
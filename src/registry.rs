@@ -0,0 +1,224 @@
+//! Every solver `aoc_lib!` knows about, so runners can pick them by day, part and variant.
+//!
+//! Running a solver happens in two phases, which runners may want to time separately:
+//! [`Solver::generate`] parses the input with the day's `aoc_generator`,
+//! and `try_run` on the returned [`Runner`] solves the puzzle.
+
+use std::{error::Error, fmt::Display, time::Duration, time::Instant};
+
+use aoc_runner::{ArcStr, Runner};
+
+use crate::*;
+
+/// The factory method `aoc_lib!` generates for each day, part and variant
+pub type Generate = fn(ArcStr) -> Result<Box<dyn Runner>, Box<dyn Error>>;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Solver {
+    pub day: u8,
+    pub part: u8,
+    /// The name of the variant, `None` for the unnamed one
+    pub variant: Option<&'static str>,
+    generate: Generate,
+}
+
+/// The answer of a solver, and how long each phase took to get there
+#[derive(Debug, Clone)]
+pub struct Solution {
+    pub answer: String,
+    pub generator_time: Duration,
+    pub solver_time: Duration,
+}
+
+impl Solver {
+    /// Parses `input`, ready to be solved
+    pub fn generate(&self, input: &str) -> Result<Box<dyn Runner>, Box<dyn Error>> {
+        (self.generate)(ArcStr::from(input))
+    }
+
+    /// Parses and solves `input`
+    pub fn solve(&self, input: &str) -> Result<Solution, Box<dyn Error>> {
+        let start = Instant::now();
+        let runner = self.generate(input)?;
+        let generated = Instant::now();
+        let answer = runner.try_run()?.to_string();
+        Ok(Solution {
+            answer,
+            generator_time: generated - start,
+            solver_time: generated.elapsed(),
+        })
+    }
+}
+
+impl Display for Solver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "day{} part{}", self.day, self.part)?;
+        if let Some(variant) = self.variant {
+            write!(f, " ({variant})")?;
+        }
+        Ok(())
+    }
+}
+
+macro_rules! solvers {
+    ($(($day:literal, $part:literal, $variant:expr, $generate:ident)),* $(,)?) => {
+        &[$(Solver {
+            day: $day,
+            part: $part,
+            variant: $variant,
+            generate: Factory::$generate,
+        }),*]
+    };
+}
+
+/// All solvers, ordered by day and part
+pub const SOLVERS: &[Solver] = solvers![
+    (1, 1, None, day1_part1),
+    (1, 2, None, day1_part2),
+    (2, 1, None, day2_part1),
+    (2, 2, Some("clever_but_wrong"), day2_part2_clever_but_wrong),
+    (2, 2, Some("brute_force"), day2_part2_brute_force),
+    (2, 2, Some("bruter_force"), day2_part2_bruter_force),
+    (
+        2,
+        2,
+        Some("dynamic_programming"),
+        day2_part2_dynamic_programming
+    ),
+    (3, 1, None, day3_part1),
+    (3, 2, None, day3_part2),
+    (4, 1, None, day4_part1),
+    (4, 2, None, day4_part2),
+    (4, 2, Some("template"), day4_part2_template),
+    (5, 1, None, day5_part1),
+    (5, 1, Some("sorting"), day5_part1_sorting),
+    (5, 1, Some("bitset"), day5_part1_bitset),
+    (5, 2, None, day5_part2),
+    (5, 2, Some("sorting"), day5_part2_sorting),
+    (5, 2, Some("topological"), day5_part2_topological),
+    (5, 2, Some("bitset"), day5_part2_bitset),
+    (6, 1, None, day6_part1),
+    (6, 2, None, day6_part2),
+    (7, 1, Some("check_fwd"), day7_part1_check_fwd),
+    (7, 1, Some("check_backwards"), day7_part1_check_backwards),
+    (7, 2, Some("check_fwd"), day7_part2_check_fwd),
+    (7, 2, Some("check_backwards"), day7_part2_check_backwards),
+    (8, 1, None, day8_part1),
+    (8, 2, None, day8_part2),
+    (9, 1, None, day9_part1),
+    (9, 2, None, day9_part2),
+    (10, 1, None, day10_part1),
+    (10, 2, None, day10_part2),
+    (11, 1, None, day11_part1),
+    (11, 2, None, day11_part2),
+    (12, 1, None, day12_part1),
+    (12, 2, None, day12_part2),
+    (13, 1, None, day13_part1),
+    (13, 2, None, day13_part2),
+    (14, 1, None, day14_part1),
+    (14, 2, None, day14_part2),
+    (15, 1, None, day15_part1),
+    (15, 2, None, day15_part2),
+    (16, 1, None, day16_part1),
+    (16, 2, None, day16_part2),
+    (17, 1, None, day17_part1),
+    (17, 2, None, day17_part2),
+    (18, 1, None, day18_part1),
+    (18, 2, None, day18_part2),
+    (19, 1, None, day19_part1),
+    (19, 2, None, day19_part2),
+    (20, 1, None, day20_part1),
+    (20, 2, None, day20_part2),
+    (21, 1, None, day21_part1),
+    (21, 2, None, day21_part2),
+];
+
+/// The solvers matching the given day, part and variant, where `None` matches anything
+///
+/// Pass `Some("")` as the variant to select only the unnamed ones.
+pub fn select(
+    day: Option<u8>,
+    part: Option<u8>,
+    variant: Option<&str>,
+) -> impl Iterator<Item = &'static Solver> + '_ {
+    SOLVERS.iter().filter(move |solver| {
+        day.is_none_or(|day| solver.day == day)
+            && part.is_none_or(|part| solver.part == part)
+            && variant.is_none_or(|variant| solver.variant.unwrap_or("") == variant)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::Itertools;
+    use std::{fs, path::Path};
+
+    #[test]
+    fn solvers_are_unique_and_sorted() {
+        let keys = SOLVERS
+            .iter()
+            .map(|solver| (solver.day, solver.part, solver.variant))
+            .collect_vec();
+        assert!(keys.iter().all_unique());
+        assert!(keys.is_sorted_by_key(|&(day, part, _)| (day, part)));
+        assert!((1..=21).all(|day| keys.iter().any(|&(d, _, _)| d == day)));
+    }
+
+    /// `aoc_lib!` only exposes its `Factory` at compile time, so this compares against the
+    /// `#[aoc(...)]` attributes it is generated from, to catch a variant missing from the list
+    #[test]
+    fn solvers_match_the_aoc_attributes() {
+        let attribute =
+            regex::Regex::new(r"(?m)^#\[aoc\(day(\d+), part(\d)(?:, (\w+))?\)\]").unwrap();
+        let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+        let sources = fs::read_dir(src)
+            .unwrap()
+            .map(|entry| fs::read_to_string(entry.unwrap().path()).unwrap())
+            .collect_vec();
+        let mut attributes = sources
+            .iter()
+            .flat_map(|source| attribute.captures_iter(source))
+            .map(|captures| {
+                let number = |i: usize| captures[i].parse::<u8>().unwrap();
+                let variant = captures.get(3).map(|variant| variant.as_str());
+                (number(1), number(2), variant)
+            })
+            .collect_vec();
+        attributes.sort();
+        let mut keys = SOLVERS
+            .iter()
+            .map(|solver| (solver.day, solver.part, solver.variant))
+            .collect_vec();
+        keys.sort();
+        assert_eq!(keys, attributes);
+    }
+
+    #[test]
+    fn select_variants() {
+        let names = |day, part, variant| {
+            select(day, part, variant)
+                .map(|solver| solver.to_string())
+                .collect_vec()
+        };
+        assert_eq!(
+            names(Some(5), Some(1), None),
+            ["day5 part1", "day5 part1 (sorting)", "day5 part1 (bitset)"]
+        );
+        assert_eq!(names(Some(5), None, Some("")), ["day5 part1", "day5 part2"]);
+        assert_eq!(
+            names(None, Some(2), Some("check_fwd")),
+            ["day7 part2 (check_fwd)"]
+        );
+    }
+
+    #[test]
+    fn solve_an_example() {
+        let solver = select(Some(1), Some(2), None).next().unwrap();
+        let solution = solver
+            .solve("3   4\n4   3\n2   5\n1   3\n3   9\n3   3\n")
+            .unwrap();
+        assert_eq!(solution.answer, "31");
+        assert!(solver.solve("3   x\n").is_err());
+    }
+}
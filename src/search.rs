@@ -0,0 +1,486 @@
+//! Shortest-path searches over implicit graphs, shared by the maze-like puzzles.
+//!
+//! The graph is given by a start state and a successor function, so the states can be anything
+//! hashable: grid points, reindeer with a facing, stacks of keypads...
+//! All searches stop once every goal state at the optimal distance has been popped
+//! (pass `|_| false` as the goal to explore everything reachable instead),
+//! and can remember the predecessors of each state to reconstruct the optimal path(s).
+
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, VecDeque},
+    hash::Hash,
+};
+
+use rustc_hash::{FxHashMap, FxHashSet};
+
+/// Which predecessors of each state to remember while searching
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Predecessors {
+    /// None, only distances are needed
+    None,
+    /// The first one found on an optimal path, enough to reconstruct one optimal path per state
+    First,
+    /// All of them on optimal paths, to enumerate every optimal path
+    All,
+}
+
+/// The outcome of a search: distances of every state reached,
+/// the goal states found (if any), and the predecessors (if tracked)
+#[derive(Debug, Clone)]
+pub struct SearchResult<S> {
+    start: S,
+    distances: FxHashMap<S, u64>,
+    predecessors: FxHashMap<S, Vec<S>>,
+    goals: Vec<S>,
+    track: Predecessors,
+}
+
+/// Breadth-first search, where every step costs 1
+///
+/// States are expanded in the order they are first discovered, and with [`Predecessors::First`]
+/// the remembered predecessor of each state is the one that discovered it.
+/// So the first goal in [`SearchResult::goals`] is the first one reached at the optimal distance,
+/// and the path to it prefers the earlier successors at every step.
+pub fn bfs<S, I>(
+    start: S,
+    mut successors: impl FnMut(&S) -> I,
+    mut is_goal: impl FnMut(&S) -> bool,
+    track: Predecessors,
+) -> SearchResult<S>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    let mut result = SearchResult::new(start.clone(), track);
+    let mut queue = VecDeque::from([start]);
+    let mut best = None;
+    while let Some(state) = queue.pop_front() {
+        let distance = result.distances[&state];
+        if best.is_some_and(|best| distance > best) {
+            break;
+        }
+        if is_goal(&state) {
+            best = Some(distance);
+            result.goals.push(state);
+            continue;
+        }
+        for next in successors(&state) {
+            if result.relax(&state, &next, distance + 1) {
+                queue.push_back(next);
+            }
+        }
+    }
+    result
+}
+
+/// Dijkstra's shortest path search, where `successors` yields each next state with the cost to get there
+pub fn dijkstra<S, I>(
+    start: S,
+    successors: impl FnMut(&S) -> I,
+    is_goal: impl FnMut(&S) -> bool,
+    track: Predecessors,
+) -> SearchResult<S>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = (S, u64)>,
+{
+    astar(start, successors, |_| 0, is_goal, track)
+}
+
+/// A* search, i.e. Dijkstra's guided by a `heuristic` estimate of the remaining cost to a goal
+///
+/// The heuristic must be consistent (never overestimate, not even for a single step),
+/// and zero at the goals. Otherwise the distances found may not be the shortest.
+pub fn astar<S, I>(
+    start: S,
+    mut successors: impl FnMut(&S) -> I,
+    mut heuristic: impl FnMut(&S) -> u64,
+    mut is_goal: impl FnMut(&S) -> bool,
+    track: Predecessors,
+) -> SearchResult<S>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = (S, u64)>,
+{
+    let mut result = SearchResult::new(start.clone(), track);
+    let mut queue = BinaryHeap::new();
+    queue.push(QueueItem {
+        priority: heuristic(&start),
+        distance: 0,
+        state: start,
+    });
+    let mut best = None;
+    while let Some(QueueItem {
+        priority,
+        distance,
+        state,
+    }) = queue.pop()
+    {
+        if best.is_some_and(|best| priority > best) {
+            break;
+        }
+        if distance > result.distances[&state] {
+            // a stale entry, the state was queued again after finding a shorter path to it
+            continue;
+        }
+        if is_goal(&state) {
+            best = Some(distance);
+            result.goals.push(state);
+            continue;
+        }
+        for (next, cost) in successors(&state) {
+            let next_distance = distance + cost;
+            if result.relax(&state, &next, next_distance) {
+                queue.push(QueueItem {
+                    priority: next_distance + heuristic(&next),
+                    distance: next_distance,
+                    state: next,
+                });
+            }
+        }
+    }
+    result
+}
+
+impl<S> SearchResult<S>
+where
+    S: Clone + Eq + Hash,
+{
+    fn new(start: S, track: Predecessors) -> Self {
+        let mut distances = FxHashMap::default();
+        distances.insert(start.clone(), 0);
+        Self {
+            start,
+            distances,
+            predecessors: FxHashMap::default(),
+            goals: Vec::new(),
+            track,
+        }
+    }
+
+    /// Records reaching `to` from `from` at `distance`, returning whether that is a new best
+    fn relax(&mut self, from: &S, to: &S, distance: u64) -> bool {
+        let ordering = self
+            .distances
+            .get(to)
+            .map_or(Ordering::Less, |known| distance.cmp(known));
+        match ordering {
+            Ordering::Greater => false,
+            Ordering::Less => {
+                self.distances.insert(to.clone(), distance);
+                if self.track != Predecessors::None {
+                    self.predecessors.insert(to.clone(), vec![from.clone()]);
+                }
+                true
+            }
+            Ordering::Equal => {
+                if self.track == Predecessors::All {
+                    // it is possible that more than one optimal path leads to the same state,
+                    // so we need to keep track of all the previous states on them
+                    let previous = self.predecessors.entry(to.clone()).or_default();
+                    if !previous.contains(from) {
+                        previous.push(from.clone());
+                    }
+                }
+                false
+            }
+        }
+    }
+
+    /// The shortest distance from the start to `state`, if it was reached
+    pub fn distance(&self, state: &S) -> Option<u64> {
+        self.distances.get(state).copied()
+    }
+
+    /// The shortest distances from the start to all states reached
+    pub fn distances(&self) -> &FxHashMap<S, u64> {
+        &self.distances
+    }
+
+    pub fn into_distances(self) -> FxHashMap<S, u64> {
+        self.distances
+    }
+
+    /// The goal states at the optimal distance, in the order they were found
+    pub fn goals(&self) -> &[S] {
+        &self.goals
+    }
+
+    /// The distance to the nearest goal, if any was reached
+    pub fn goal_distance(&self) -> Option<u64> {
+        self.goals.first().and_then(|goal| self.distance(goal))
+    }
+
+    /// The predecessors of `state` on optimal paths (as far as they were tracked)
+    pub fn predecessors(&self, state: &S) -> &[S] {
+        self.predecessors.get(state).map_or(&[], Vec::as_slice)
+    }
+
+    /// An optimal path from the start to `state` (both inclusive), if it was reached
+    ///
+    /// Panics if predecessors were not tracked.
+    pub fn path_to(&self, state: &S) -> Option<Vec<S>> {
+        assert_ne!(
+            self.track,
+            Predecessors::None,
+            "predecessors were not tracked"
+        );
+        self.distances.get(state)?;
+        let mut path = vec![state.clone()];
+        let mut at = state;
+        while *at != self.start {
+            at = &self.predecessors[at][0];
+            path.push(at.clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// All states on any optimal path from the start to any of the goals
+    ///
+    /// Panics if predecessors were not tracked, and is only complete with [`Predecessors::All`].
+    pub fn states_on_optimal_paths(&self) -> FxHashSet<S> {
+        assert_ne!(
+            self.track,
+            Predecessors::None,
+            "predecessors were not tracked"
+        );
+        let mut on_paths = FxHashSet::default();
+        let mut todo = self.goals.clone();
+        while let Some(state) = todo.pop() {
+            if !on_paths.insert(state.clone()) {
+                continue;
+            }
+            todo.extend(self.predecessors(&state).iter().cloned());
+        }
+        on_paths
+    }
+
+    /// Every optimal path from the start to any of the goals (both inclusive)
+    ///
+    /// Beware that their number can grow exponentially with the path length.
+    /// Panics if predecessors were not tracked, and is only complete with [`Predecessors::All`].
+    pub fn optimal_paths(&self) -> Vec<Vec<S>> {
+        assert_ne!(
+            self.track,
+            Predecessors::None,
+            "predecessors were not tracked"
+        );
+        let mut paths = Vec::new();
+        let mut todo: Vec<Vec<S>> = self.goals.iter().map(|goal| vec![goal.clone()]).collect();
+        while let Some(reversed_path) = todo.pop() {
+            let at = reversed_path.last().unwrap();
+            if *at == self.start {
+                paths.push(reversed_path.into_iter().rev().collect());
+                continue;
+            }
+            for previous in self.predecessors(at) {
+                let mut longer = reversed_path.clone();
+                longer.push(previous.clone());
+                todo.push(longer);
+            }
+        }
+        paths
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct QueueItem<S> {
+    priority: u64,
+    distance: u64,
+    state: S,
+}
+
+impl<S: Eq> Ord for QueueItem<S> {
+    // We stick these into a std::collections::BinaryHeap above, to implement
+    // the queue in Dijkstra's shortest path algo. However, the aforementioned struct
+    // implements max-heap, and we want a min-heap. So this custom impl. flips
+    // when Ordering::Less is returned vs Ordering::Greater, by flipping
+    // callee (self) and arg (other) when forwarding to u64::cmp.
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.cmp(&self.priority)
+    }
+}
+
+impl<S: Eq> PartialOrd for QueueItem<S> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::{Grid, Point};
+    use indoc::indoc;
+
+    const MAZE: &str = indoc! {"
+        ..#....
+        .##.##.
+        .......
+        .#.#.#.
+        ...#...
+    "};
+
+    fn maze() -> Grid<bool> {
+        Grid::from_char_map(MAZE, "'.' or '#'", |_, c| match c {
+            '.' => Some(true),
+            '#' => Some(false),
+            _ => None,
+        })
+        .unwrap()
+    }
+
+    fn open_neighbours(maze: &Grid<bool>, point: Point) -> Vec<Point> {
+        maze.neighbours(point).filter(|&p| maze[p]).collect()
+    }
+
+    /// A diamond with two equally short routes from 0 to 3, and a longer one via 4
+    fn diamond(state: &u8) -> Vec<(u8, u64)> {
+        match state {
+            0 => vec![(1, 1), (2, 2), (4, 1)],
+            1 => vec![(3, 2)],
+            2 => vec![(3, 1)],
+            4 => vec![(3, 5)],
+            _ => vec![],
+        }
+    }
+
+    #[test]
+    fn bfs_finds_shortest_distance() {
+        let maze = maze();
+        let end = Point::new(6, 0);
+        let result = bfs(
+            Point::new(0, 0),
+            |&p| open_neighbours(&maze, p),
+            |&p| p == end,
+            Predecessors::First,
+        );
+        assert_eq!(result.goals(), [end]);
+        assert_eq!(result.goal_distance(), Some(10));
+        let path = result.path_to(&end).unwrap();
+        assert_eq!(path.len(), 11);
+        assert_eq!(path[0], Point::new(0, 0));
+        for (from, to) in path.iter().zip(path.iter().skip(1)) {
+            assert_eq!(from.manhattan_distance(to), 1);
+        }
+    }
+
+    #[test]
+    fn bfs_prefers_earlier_successors() {
+        // north comes before east, so in an open field we go all the way north first
+        let field = Grid::new(3, 3, true);
+        let result = bfs(
+            Point::new(0, 2),
+            |&p| open_neighbours(&field, p),
+            |&p| p == Point::new(2, 0),
+            Predecessors::First,
+        );
+        assert_eq!(
+            result.path_to(&Point::new(2, 0)).unwrap(),
+            [
+                Point::new(0, 2),
+                Point::new(0, 1),
+                Point::new(0, 0),
+                Point::new(1, 0),
+                Point::new(2, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn bfs_explores_everything_without_goal() {
+        let maze = maze();
+        let result = bfs(
+            Point::new(0, 0),
+            |&p| open_neighbours(&maze, p),
+            |_| false,
+            Predecessors::None,
+        );
+        assert_eq!(
+            result.distances().len(),
+            maze.iter().filter(|&&open| open).count()
+        );
+        assert_eq!(result.goal_distance(), None);
+        assert_eq!(result.distance(&Point::new(6, 4)), Some(10));
+    }
+
+    #[test]
+    fn exits_early_at_the_goal() {
+        let field = Grid::new(50, 50, true);
+        let result = bfs(
+            Point::new(0, 0),
+            |&p| open_neighbours(&field, p),
+            |&p| p == Point::new(1, 1),
+            Predecessors::None,
+        );
+        assert_eq!(result.goal_distance(), Some(2));
+        assert!(result.distances().values().all(|&d| d <= 3));
+    }
+
+    #[test]
+    fn dijkstra_on_weighted_graph() {
+        let result = dijkstra(0u8, diamond, |&s| s == 3, Predecessors::None);
+        assert_eq!(result.goal_distance(), Some(3));
+        assert_eq!(result.distance(&4), Some(1));
+    }
+
+    #[test]
+    fn all_optimal_paths() {
+        let result = dijkstra(0u8, diamond, |&s| s == 3, Predecessors::All);
+        let mut paths = result.optimal_paths();
+        paths.sort();
+        assert_eq!(paths, [vec![0, 1, 3], vec![0, 2, 3]]);
+        let on_paths = result.states_on_optimal_paths();
+        assert_eq!(on_paths, FxHashSet::from_iter([0, 1, 2, 3]));
+
+        let result = dijkstra(0u8, diamond, |&s| s == 3, Predecessors::First);
+        assert_eq!(result.optimal_paths().len(), 1);
+    }
+
+    #[test]
+    fn all_optimal_goals() {
+        // both 1 and 2 are goals at distance 2, 3 is a goal as well but further away
+        let successors = |&s: &u8| match s {
+            0 => vec![(1, 2), (2, 2), (3, 3)],
+            _ => vec![],
+        };
+        let result = dijkstra(0u8, successors, |&s| s > 0, Predecessors::All);
+        let mut goals = result.goals().to_vec();
+        goals.sort();
+        assert_eq!(goals, [1, 2]);
+    }
+
+    #[test]
+    fn astar_agrees_with_dijkstra() {
+        let maze = maze();
+        let end = Point::new(6, 4);
+        let successors = |&p: &Point| {
+            open_neighbours(&maze, p)
+                .into_iter()
+                .map(|n| (n, 1))
+                .collect::<Vec<_>>()
+        };
+        let with_heuristic = astar(
+            Point::new(0, 0),
+            successors,
+            |p| p.manhattan_distance(&end) as u64,
+            |&p| p == end,
+            Predecessors::All,
+        );
+        let without = dijkstra(
+            Point::new(0, 0),
+            successors,
+            |&p| p == end,
+            Predecessors::All,
+        );
+        assert_eq!(with_heuristic.goal_distance(), Some(10));
+        assert_eq!(with_heuristic.goal_distance(), without.goal_distance());
+        assert_eq!(
+            with_heuristic.states_on_optimal_paths(),
+            without.states_on_optimal_paths()
+        );
+        assert!(with_heuristic.distances().len() <= without.distances().len());
+    }
+}
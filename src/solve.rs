@@ -0,0 +1,176 @@
+//! One typed entry point to the parsers and solvers of every day.
+
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+use crate::{grid::Point, parsing::ParseError, *};
+
+/// The answer to a puzzle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Number(u64),
+    Text(String),
+    Position(Point),
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Number(number) => write!(f, "{number}"),
+            Self::Text(text) => write!(f, "{text}"),
+            Self::Position(point) => write!(f, "{point}"),
+        }
+    }
+}
+
+impl From<u64> for Answer {
+    fn from(number: u64) -> Self {
+        Self::Number(number)
+    }
+}
+
+impl From<u32> for Answer {
+    fn from(number: u32) -> Self {
+        Self::Number(number.into())
+    }
+}
+
+impl From<String> for Answer {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+impl From<Point> for Answer {
+    fn from(point: Point) -> Self {
+        Self::Position(point)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolveError {
+    /// There is no solver for this day and part (yet)
+    NoSuchPuzzle {
+        day: u8,
+        part: u8,
+    },
+    Parse(ParseError),
+}
+
+impl Display for SolveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoSuchPuzzle { day, part } => {
+                write!(f, "there is no solver for day{day} part{part}")
+            }
+            Self::Parse(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl Error for SolveError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::NoSuchPuzzle { .. } => None,
+            Self::Parse(error) => Some(error),
+        }
+    }
+}
+
+impl From<ParseError> for SolveError {
+    fn from(error: ParseError) -> Self {
+        Self::Parse(error)
+    }
+}
+
+/// Parses `input` for `day`, and solves `part` of the puzzle with the default variant
+///
+/// Like the runners, this ignores trailing newlines,
+/// and solves with the process-wide [`params`](crate::params).
+pub fn solve(day: u8, part: u8, input: &str) -> Result<Answer, SolveError> {
+    let input = input.trim_end_matches('\n');
+    macro_rules! dispatch {
+        ($($day:literal => $module:ident),* $(,)?) => {
+            match (day, part) {
+                $(
+                    ($day, 1) => Ok($module::part1(&$module::parse(input)?).into()),
+                    ($day, 2) => Ok($module::part2(&$module::parse(input)?).into()),
+                )*
+                _ => Err(SolveError::NoSuchPuzzle { day, part }),
+            }
+        };
+    }
+    dispatch! {
+        1 => day1,
+        2 => day2,
+        3 => day3,
+        4 => day4,
+        5 => day5,
+        6 => day6,
+        7 => day7,
+        8 => day8,
+        9 => day9,
+        10 => day10,
+        11 => day11,
+        12 => day12,
+        13 => day13,
+        14 => day14,
+        15 => day15,
+        16 => day16,
+        17 => day17,
+        18 => day18,
+        19 => day19,
+        20 => day20,
+        21 => day21,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn solve_examples() {
+        let day1 = indoc! {"
+            3   4
+            4   3
+            2   5
+            1   3
+            3   9
+            3   3
+        "};
+        assert_eq!(solve(1, 2, day1), Ok(Answer::Number(31)));
+        let day17 = indoc! {"
+            Register A: 729
+            Register B: 0
+            Register C: 0
+
+            Program: 0,1,5,4,3,0
+        "};
+        assert_eq!(
+            solve(17, 1, day17),
+            Ok(Answer::Text("4,6,3,5,6,3,5,2,1,0".to_string()))
+        );
+        assert_eq!(Answer::Position(Point::new(6, 1)).to_string(), "6,1");
+    }
+
+    #[test]
+    fn solve_errors() {
+        assert_eq!(
+            solve(25, 1, ""),
+            Err(SolveError::NoSuchPuzzle { day: 25, part: 1 })
+        );
+        assert_eq!(
+            solve(1, 3, "1 2").unwrap_err().to_string(),
+            "there is no solver for day1 part3"
+        );
+        let error = solve(1, 1, "1 x").unwrap_err();
+        assert!(matches!(
+            error,
+            SolveError::Parse(ParseError { column: 3, .. })
+        ));
+    }
+}
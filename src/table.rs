@@ -0,0 +1,117 @@
+//! Tables of text cells, rendered aligned for reading or as CSV for other tools.
+
+use std::fmt::{self, Display, Formatter};
+
+use itertools::Itertools;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Table {
+    header: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new<S: ToString>(header: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            header: header.into_iter().map(|cell| cell.to_string()).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Adds a row, which must have as many cells as the header
+    pub fn push_row<S: ToString>(&mut self, row: impl IntoIterator<Item = S>) {
+        let row = row.into_iter().map(|cell| cell.to_string()).collect_vec();
+        assert_eq!(
+            row.len(),
+            self.header.len(),
+            "a row of {} cells in a table of {} columns",
+            row.len(),
+            self.header.len()
+        );
+        self.rows.push(row);
+    }
+
+    pub fn rows(&self) -> &[Vec<String>] {
+        &self.rows
+    }
+
+    /// The header and the rows as comma-separated lines, quoting cells where needed
+    pub fn to_csv(&self) -> String {
+        let quote = |cell: &String| {
+            if cell.contains([',', '"', '\n']) {
+                format!("\"{}\"", cell.replace('"', "\"\""))
+            } else {
+                cell.clone()
+            }
+        };
+        let mut csv = String::new();
+        for row in [&self.header].into_iter().chain(&self.rows) {
+            csv.push_str(&row.iter().map(quote).join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+/// The first column aligned to the left, the others (usually numbers) to the right
+impl Display for Table {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let widths = (0..self.header.len())
+            .map(|column| {
+                [&self.header]
+                    .into_iter()
+                    .chain(&self.rows)
+                    .map(|row| row[column].chars().count())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect_vec();
+        for row in [&self.header].into_iter().chain(&self.rows) {
+            let line = row
+                .iter()
+                .zip(&widths)
+                .enumerate()
+                .map(|(column, (cell, &width))| {
+                    if column == 0 {
+                        format!("{cell:<width$}")
+                    } else {
+                        format!("{cell:>width$}")
+                    }
+                })
+                .join("  ");
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn render() {
+        let mut table = Table::new(["name", "count"]);
+        table.push_row(["apples", "3"]);
+        table.push_row(["kiwis, green", "12"]);
+        assert_eq!(
+            table.to_string(),
+            indoc! {"
+                name          count
+                apples            3
+                kiwis, green     12
+            "}
+        );
+        assert_eq!(
+            table.to_csv(),
+            "name,count\napples,3\n\"kiwis, green\",12\n"
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn ragged_row() {
+        Table::new(["name", "count"]).push_row(["apples"]);
+    }
+}